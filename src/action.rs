@@ -0,0 +1,54 @@
+use color_eyre::Result;
+
+use crate::bridge::{self, PlayerStatus};
+use crate::volume::Volume;
+
+/// A player command, shared by the keyboard handler and the remote API so
+/// both behave the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Play,
+    Pause,
+    TogglePlayback,
+    Next,
+    Previous,
+    SetVolume(Volume),
+    /// Relative volume change, in percentage points.
+    ChangeVolume(i32),
+    /// Absolute position in seconds.
+    Seek(f64),
+    /// normal → shuffle → repeat all → repeat one
+    CycleMode,
+}
+
+impl Action {
+    /// What the action is called in error messages.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Play => "Play",
+            Action::Pause => "Pause",
+            Action::TogglePlayback => "Play/pause",
+            Action::Next => "Next track",
+            Action::Previous => "Previous track",
+            Action::SetVolume(_) | Action::ChangeVolume(_) => "Volume",
+            Action::Seek(_) => "Seek",
+            Action::CycleMode => "Mode change",
+        }
+    }
+
+    /// Carry out the action. `player` is the last polled status, which
+    /// relative changes are based on.
+    pub fn perform(self, player: &PlayerStatus) -> Result<()> {
+        match self {
+            Action::Play => bridge::play(),
+            Action::Pause => bridge::pause(),
+            Action::TogglePlayback => bridge::toggle_playback(),
+            Action::Next => bridge::next_track(),
+            Action::Previous => bridge::previous_track(),
+            Action::SetVolume(volume) => bridge::set_volume(volume),
+            Action::ChangeVolume(delta) => bridge::set_volume(player.volume.step(delta)),
+            Action::Seek(position) => bridge::seek_to(position.clamp(0.0, player.duration.max(0.0))),
+            Action::CycleMode => bridge::cycle_play_mode(player),
+        }
+    }
+}
@@ -1,93 +1,1022 @@
-use crate::bridge::PlayerStatus;
-use crate::library::{PlaylistEntry, TrackEntry};
-use ratatui::widgets::ListState;
+use crate::artwork::Candidate;
+use crate::browser::ColumnBrowser;
+use crate::bridge::{AudioSettings, PlayState, PlayerStatus, UpNext};
+use crate::config::Config;
+use crate::filter;
+use crate::history::{History, HistoryEntry};
+use crate::capabilities::Capabilities;
+use crate::lyrics::{Lyrics, LyricsQuery, Provider, StoredLyrics};
+use crate::meter::LevelMeter;
+use crate::volume::Volume;
+use crate::library::{Opinion, PlaylistEntry, SmartList, TrackEntry};
+use ratatui::widgets::{ListState, TableState};
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
-use std::time::Instant;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 pub struct App {
     pub should_quit: bool,
+    pub config: Config,
+    // Optional features that work here, probed at startup
+    pub capabilities: Capabilities,
     pub player: PlayerStatus,
+    // When the last PlayerUpdate arrived and the position it reported,
+    // used to interpolate `player.position` between polls
+    pub player_updated_at: Option<Instant>,
+    pub polled_position: f64,
+    // Volume to go back to on unmute, while muted
+    pub muted_volume: Option<Volume>,
+    // When the volume was last set from here; polls just after may predate it
+    pub volume_set_at: Option<Instant>,
     pub active_panel: Panel,
     // Library browser state
     pub playlists: Vec<PlaylistEntry>,
     pub playlist_state: ListState,
     pub tracks: Vec<TrackEntry>,
-    pub track_state: ListState,
+    pub track_state: TableState,
+    pub history_state: ListState,
+    pub menu_state: ListState,
     pub view: LibraryView,
+    // Artists | Albums | Tracks column browser, replacing the playlist list
+    // while open; its tracks column is `tracks`
+    pub browser: Option<ColumnBrowser>,
     pub search_mode: bool,
     pub search_query: String,
+    // Why the track filter query doesn't parse; the list keeps the last
+    // good results meanwhile
+    pub filter_error: Option<String>,
     pub loading: bool,
-    pub track_cache: HashMap<String, Vec<TrackEntry>>,
+    // Track lists by playlist id, with when they were fetched
+    pub track_cache: HashMap<i32, (Instant, Vec<TrackEntry>)>,
+    // The open playlist, when its tracks stopped loading partway
+    pub incomplete: Option<Incomplete>,
+    // The playlist whose tracks are streaming in, chunk by chunk
+    pub load_progress: Option<LoadProgress>,
+    // Ids of recently opened playlists, most recent first
+    pub recent_playlists: Vec<i32>,
+    // How many times each playlist was opened, for prefetching
+    pub playlist_opens: HashMap<i32, u32>,
+    // Where each playlist's (or album's) track list was left; see
+    // `track_list_key`
+    pub track_positions: HashMap<String, TrackPosition>,
+    // Persistent ids of the playlists cli-music made to play tracks in
+    // order (queue, mixes, sessions); only these are ever replaced
+    pub generated_playlists: Vec<String>,
+    // Library-wide search shown in the Tracks view instead of a playlist
+    pub open_search: Option<String>,
+    // Library menu list shown in the Tracks view instead of a playlist
+    pub open_list: Option<SmartList>,
+    // Search results by normalized query, with when they were fetched
+    pub search_cache: HashMap<String, (Instant, Vec<TrackEntry>)>,
+    pub sort_key: SortKey,
+    pub sort_descending: bool,
+    // Why the last attempt to play a track failed, by track id
+    pub play_errors: HashMap<i32, String>,
+    // Multi-select: marked track ids, and the row a visual range started at
+    pub marked: HashSet<i32>,
+    pub visual_anchor: Option<usize>,
     // Snapshot of full list before search filtering
     pub pre_search_playlists: Vec<PlaylistEntry>,
     pub pre_search_tracks: Vec<TrackEntry>,
     // Artwork
     pub artwork: Option<image::DynamicImage>,
     pub artwork_track: String,
-    // Mini-player mode: hide library, show only now playing
+    // Blurred, darkened artwork for drawing behind text
+    pub backdrop: Option<image::DynamicImage>,
+    // Cover of the open playlist (own artwork or mosaic), by mosaic key
+    pub playlist_cover: Option<(String, image::DynamicImage)>,
+    // Mosaic key last requested, so each is only asked for once
+    pub playlist_cover_requested: String,
+    // Mini-player mode: track, artist, progress and a hint on four lines
     pub mini_player: bool,
-    // Temporary notification overlay (message, when it was set)
-    pub notification: Option<(String, Instant)>,
+    // Now Playing panel width in percent, None for the responsive default
+    pub split_percent: Option<u16>,
+    // Focused panel maximized
+    pub zoomed: bool,
+    // Terminal width at the last draw
+    pub terminal_width: u16,
+    // Launched with --safe-mode: default config, network off, state not saved
+    pub safe_mode: bool,
+    // Full-screen karaoke lyrics
+    pub karaoke: bool,
+    // Speak track changes (radio-DJ mode)
+    pub announce: bool,
+    // Waiting for a 0–5 star rating key
+    pub rating_mode: bool,
+    // BPM tap tool, open while Some
+    pub bpm_tap: Option<BpmTap>,
+    // Lyrics editor, open while Some
+    pub lyrics_editor: Option<LyricsEditor>,
+    // Alternate artwork chooser, open while Some
+    pub artwork_chooser: Option<ArtworkChooser>,
+    // Transient messages, oldest first
+    pub toasts: Vec<Toast>,
+    // Next track in the current playlist, and whether its toast was shown
+    pub up_next: Option<UpNext>,
+    pub up_next_shown: bool,
+    // Active output (AirPlay) device and when it last changed
+    pub output_device: String,
+    pub output_changed_at: Option<Instant>,
+    // Live output level, when a capture device is configured
+    pub level_meter: Option<LevelMeter>,
+    // Animation counter advanced on every Tick (drives marquee scrolling)
+    pub tick: u64,
+    // Last keypress, and whether the idle screensaver is showing
+    pub last_input: Instant,
+    pub screensaver: bool,
+    // `:` command prompt
+    pub command_mode: bool,
+    pub command_input: String,
+    // Modal text popup, dismissed by any key
+    pub popup: Option<Popup>,
+    // Input prompt or confirmation dialog
+    pub modal: Option<Modal>,
+    // Play history and named listening sessions
+    pub history: History,
+    // Lyrics view (replaces the artwork in the Now Playing panel)
+    pub show_lyrics: bool,
+    pub lyrics: Option<Lyrics>,
+    // Track key `lyrics` was resolved for (empty until a lookup finishes)
+    pub lyrics_for: String,
+    pub lyrics_loading: bool,
+    // Line picked with j/k or search; None follows playback (synced lyrics)
+    pub lyrics_cursor: Option<usize>,
+    // Search within the lyrics
+    pub lyrics_search_mode: bool,
+    pub lyrics_query: String,
+    // Lyrics by "artist - title"; None means no provider had any
+    pub lyrics_cache: HashMap<String, Option<Lyrics>>,
+    // Vim-style navigation: pending count prefix and a pending first `g`
+    pub pending_count: Option<usize>,
+    pub pending_g: bool,
+    // Rows visible in the library list at the last draw (for half-page scrolls)
+    pub library_height: u16,
+    // Library tabs: the views of the inactive ones (the active tab's view is
+    // in the fields above, and its slot here is empty) and which is active
+    pub tabs: Vec<TabView>,
+    pub active_tab: usize,
 }
 
+/// The selected track of a track list and how far down the screen it was.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TrackPosition {
+    pub track_id: i32,
+    /// Rows shown above the selected one.
+    pub above: usize,
+    /// When it was last noted, as a running count: the highest is the most
+    /// recent. Used to drop the oldest past `MAX_TRACK_POSITIONS`.
+    #[serde(default)]
+    pub noted: u64,
+}
+
+/// Most track list positions remembered; the least recently noted go first.
+pub const MAX_TRACK_POSITIONS: usize = 200;
+
+/// Most library tabs open at once.
+pub const MAX_TABS: usize = 9;
+
+/// A library tab's view: what the library panel shows and where it is in
+/// it. Swapped with `App`'s fields on switching tabs (see `swap`).
+#[derive(Debug, Default)]
+pub struct TabView {
+    view: LibraryView,
+    playlist_state: ListState,
+    tracks: Vec<TrackEntry>,
+    track_state: TableState,
+    history_state: ListState,
+    menu_state: ListState,
+    browser: Option<ColumnBrowser>,
+    search_query: String,
+    filter_error: Option<String>,
+    incomplete: Option<Incomplete>,
+    open_search: Option<String>,
+    open_list: Option<SmartList>,
+    sort_key: SortKey,
+    sort_descending: bool,
+    marked: HashSet<i32>,
+    visual_anchor: Option<usize>,
+    pre_search_playlists: Vec<PlaylistEntry>,
+    pre_search_tracks: Vec<TrackEntry>,
+}
+
+impl TabView {
+    /// A fresh tab on the playlist list, sorted like `app`'s view.
+    fn new(app: &App) -> Self {
+        let mut tab = Self {
+            sort_key: app.sort_key,
+            sort_descending: app.sort_descending,
+            ..Self::default()
+        };
+        tab.playlist_state.select((!app.playlists.is_empty()).then_some(0));
+        tab
+    }
+
+    /// Exchange this view with the one `app` shows.
+    fn swap(&mut self, app: &mut App) {
+        use std::mem::swap;
+        swap(&mut self.view, &mut app.view);
+        swap(&mut self.playlist_state, &mut app.playlist_state);
+        swap(&mut self.tracks, &mut app.tracks);
+        swap(&mut self.track_state, &mut app.track_state);
+        swap(&mut self.history_state, &mut app.history_state);
+        swap(&mut self.menu_state, &mut app.menu_state);
+        swap(&mut self.browser, &mut app.browser);
+        swap(&mut self.search_query, &mut app.search_query);
+        swap(&mut self.filter_error, &mut app.filter_error);
+        swap(&mut self.incomplete, &mut app.incomplete);
+        swap(&mut self.open_search, &mut app.open_search);
+        swap(&mut self.open_list, &mut app.open_list);
+        swap(&mut self.sort_key, &mut app.sort_key);
+        swap(&mut self.sort_descending, &mut app.sort_descending);
+        swap(&mut self.marked, &mut app.marked);
+        swap(&mut self.visual_anchor, &mut app.visual_anchor);
+        swap(&mut self.pre_search_playlists, &mut app.pre_search_playlists);
+        swap(&mut self.pre_search_tracks, &mut app.pre_search_tracks);
+    }
+
+    fn label(&self, playlists: &[PlaylistEntry]) -> String {
+        let playlist = self.playlist_state.selected().and_then(|i| playlists.get(i));
+        view_label(&self.view, &self.open_search, self.open_list, self.browser.is_some(), playlist)
+    }
+}
+
+/// A tab's name: its view, or what's open in the Tracks view.
+fn view_label(
+    view: &LibraryView,
+    open_search: &Option<String>,
+    open_list: Option<SmartList>,
+    browser: bool,
+    playlist: Option<&PlaylistEntry>,
+) -> String {
+    match view {
+        LibraryView::Menu => "Library".to_string(),
+        LibraryView::Playlists => "Playlists".to_string(),
+        LibraryView::History => "History".to_string(),
+        LibraryView::Tracks => match (open_search, open_list) {
+            (Some(query), _) => format!("Search: {query}"),
+            (None, Some(list)) => list.label().to_string(),
+            (None, None) if browser => "Browser".to_string(),
+            (None, None) => playlist.map_or("Tracks".to_string(), |p| p.name.clone()),
+        },
+    }
+}
+
+/// Artwork chooser state: covers from the album's releases, one of which
+/// is picked for the album.
+#[derive(Debug, Clone)]
+pub struct ArtworkChooser {
+    pub track_name: String,
+    pub artist: String,
+    pub album: String,
+    /// None while the candidates are loading.
+    pub candidates: Option<Vec<Candidate>>,
+    pub selected: usize,
+}
+
+impl ArtworkChooser {
+    pub fn new(track_name: String, artist: String, album: String) -> Self {
+        Self {
+            track_name,
+            artist,
+            album,
+            candidates: None,
+            selected: 0,
+        }
+    }
+
+    /// Move the selection by `delta`, clamped to the candidates.
+    pub fn move_by(&mut self, delta: isize) {
+        let len = self.candidates.as_ref().map_or(0, Vec::len);
+        if len > 0 {
+            self.selected = self.selected.saturating_add_signed(delta).min(len - 1);
+        }
+    }
+
+    pub fn selected_candidate(&self) -> Option<&Candidate> {
+        self.candidates.as_ref()?.get(self.selected)
+    }
+}
+
+/// BPM tap tool state: taps are timed to estimate a track's tempo.
+#[derive(Debug, Clone)]
+pub struct BpmTap {
+    pub target_id: i32,
+    pub target_name: String,
+    pub taps: Vec<Instant>,
+}
+
+/// Taps further apart than this start a new measurement.
+const BPM_TAP_RESET: Duration = Duration::from_secs(2);
+/// Only the most recent taps count, so the estimate follows tempo corrections.
+const BPM_TAP_WINDOW: usize = 12;
+
+impl BpmTap {
+    pub fn new(target_id: i32, target_name: String) -> Self {
+        Self {
+            target_id,
+            target_name,
+            taps: Vec::new(),
+        }
+    }
+
+    /// Register a tap now.
+    pub fn tap(&mut self) {
+        let now = Instant::now();
+        if self.taps.last().is_some_and(|t| now.duration_since(*t) > BPM_TAP_RESET) {
+            self.taps.clear();
+        }
+        self.taps.push(now);
+        if self.taps.len() > BPM_TAP_WINDOW {
+            self.taps.remove(0);
+        }
+    }
+
+    /// Tempo from the average interval between taps (needs at least 2).
+    pub fn bpm(&self) -> Option<f64> {
+        if self.taps.len() < 2 {
+            return None;
+        }
+        let (first, last) = (self.taps[0], self.taps[self.taps.len() - 1]);
+        let avg = last.duration_since(first).as_secs_f64() / (self.taps.len() - 1) as f64;
+        (avg > 0.0).then(|| 60.0 / avg)
+    }
+}
+
+/// Lyrics editor state: the playing track's lyrics as editable lines, written
+/// back to Music (and the track's `.lrc` file) on save.
+#[derive(Debug, Clone)]
+pub struct LyricsEditor {
+    pub track_id: i32,
+    /// The track being edited, for updating the lyrics cache.
+    pub query: LyricsQuery,
+    pub lines: Vec<String>,
+    /// Cursor line, and column in chars.
+    pub row: usize,
+    pub col: usize,
+    /// The track's lyrics when the editor opened.
+    pub stored: StoredLyrics,
+    /// Write the `.lrc` file too (local files with the local provider on,
+    /// or that already have one).
+    pub write_lrc: bool,
+    /// Waiting for the write to finish.
+    pub saving: bool,
+    original: String,
+}
+
+impl LyricsEditor {
+    pub fn new(track_id: i32, query: LyricsQuery, text: &str, stored: StoredLyrics, write_lrc: bool) -> Self {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+        // Drop the final newline's empty line
+        if lines.len() > 1 && lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        Self {
+            track_id,
+            query,
+            original: lines.join("\n"),
+            lines,
+            row: 0,
+            col: 0,
+            stored,
+            write_lrc,
+            saving: false,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn modified(&self) -> bool {
+        self.text() != self.original
+    }
+
+    /// Byte offset of the cursor in its line.
+    fn offset(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map_or(line.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        if c == '\n' {
+            let at = self.offset();
+            let rest = self.lines[self.row].split_off(at);
+            self.row += 1;
+            self.lines.insert(self.row, rest);
+            self.col = 0;
+        } else if c != '\r' {
+            let at = self.offset();
+            self.lines[self.row].insert(at, c);
+            self.col += 1;
+        }
+    }
+
+    /// Insert pasted text at the cursor.
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.replace("\r\n", "\n").chars() {
+            self.insert(if c == '\r' { '\n' } else { c });
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.offset();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.lines[self.row].chars().count();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        let at = self.offset();
+        if at < self.lines[self.row].len() {
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.lines[self.row].chars().count();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.lines[self.row].chars().count() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Move the cursor `delta` lines, keeping the column where it fits.
+    pub fn move_lines(&mut self, delta: isize) {
+        self.row = self.row.saturating_add_signed(delta).min(self.lines.len() - 1);
+        self.col = self.col.min(self.lines[self.row].chars().count());
+    }
+
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.col = self.lines[self.row].chars().count();
+    }
+}
+
+/// A modal dialog that captures all keys until confirmed or cancelled.
+#[derive(Debug, Clone)]
+pub enum Modal {
+    /// Single-line text prompt.
+    Input {
+        title: String,
+        value: String,
+        action: InputAction,
+    },
+    /// Yes/no confirmation.
+    Confirm {
+        message: String,
+        action: ConfirmAction,
+    },
+    /// Pick a playlist from a list narrowed by typing.
+    PickPlaylist {
+        title: String,
+        filter: String,
+        selected: usize,
+        action: PickAction,
+    },
+    /// A shuffled order for a playlist, played through the queue once
+    /// accepted; `r` re-rolls it.
+    ShufflePreview {
+        playlist: PlaylistEntry,
+        tracks: Vec<TrackEntry>,
+    },
+    /// Music's EQ and crossfade: row 0 toggles the EQ, row 1 crossfade,
+    /// and the rows from [`AUDIO_PRESET_ROW`] pick a preset.
+    Audio {
+        /// None while loading.
+        settings: Option<AudioSettings>,
+        selected: usize,
+    },
+}
+
+/// The first EQ preset row in [`Modal::Audio`].
+pub const AUDIO_PRESET_ROW: usize = 2;
+
+/// What to do with the playlist chosen in a picker modal.
+#[derive(Debug, Clone)]
+pub enum PickAction {
+    AddTracks(Vec<i32>),
+}
+
+/// What to do with the text entered in an input modal.
+#[derive(Debug, Clone)]
+pub enum InputAction {
+    CreatePlaylist,
+    RenamePlaylist(PlaylistEntry),
+}
+
+/// What to do once a confirm modal is accepted.
+#[derive(Debug, Clone)]
+pub enum ConfirmAction {
+    DeletePlaylist(PlaylistEntry),
+    RemoveTracks { playlist: PlaylistEntry, track_ids: Vec<i32> },
+    /// Save the lyrics editor over the track's existing lyrics.
+    WriteLyrics,
+}
+
+/// Severity of a toast message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Success,
+    Error,
+}
+
+/// A transient message shown in the corner until it expires.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: Level,
+    pub text: String,
+    pub at: Instant,
+}
+
+impl Toast {
+    fn expired(&self) -> bool {
+        // Errors stay up longer so there's time to read them
+        let ttl = match self.level {
+            Level::Error => TOAST_ERROR_TTL,
+            _ => TOAST_TTL,
+        };
+        self.at.elapsed() >= ttl
+    }
+}
+
+/// How long info and success toasts stay up.
+const TOAST_TTL: Duration = Duration::from_secs(3);
+/// How long error toasts stay up.
+const TOAST_ERROR_TTL: Duration = Duration::from_secs(6);
+/// At most this many toasts are stacked; older ones are dropped.
+const MAX_TOASTS: usize = 4;
+
+/// A titled block of text shown over the UI until a key is pressed.
+#[derive(Debug, Clone)]
+pub struct Popup {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// How many recently opened playlists are remembered.
+const RECENT_PLAYLISTS: usize = 5;
+
+/// Library search results younger than this are served without refetching.
+pub const SEARCH_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Normalize a library search query so equivalent searches share a cache
+/// entry: lowercased, with runs of whitespace collapsed.
+pub fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// How long after setting the volume polls are ignored for it, since one
+/// started before the change would report the old level.
+const VOLUME_SETTLE: Duration = Duration::from_secs(2);
+
+/// How long before the end of a track the "Up next" toast appears (seconds).
+const UP_NEXT_LEAD: f64 = 3.0;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Panel {
     NowPlaying,
     Library,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub enum LibraryView {
+    /// Top-level menu of library sections (see `MENU`).
+    Menu,
+    #[default]
     Playlists,
     Tracks,
+    /// Tracks played, most recent first (see `App::history_rows`).
+    History,
+}
+
+/// A playlist whose tracks only partly loaded; the rest can be retried.
+#[derive(Debug, Clone)]
+pub struct Incomplete {
+    pub playlist_id: i32,
+    /// Tracks fetched so far, in playlist order.
+    pub fetched: Vec<TrackEntry>,
+    pub total: usize,
+}
+
+/// Progress of a playlist loading in chunks.
+#[derive(Debug, Clone)]
+pub struct LoadProgress {
+    pub playlist_id: i32,
+    pub loaded: usize,
+    /// Tracks in the playlist; 0 until the first chunk arrives.
+    pub total: usize,
+}
+
+/// An entry of the top-level library menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuItem {
+    Playlists,
+    Smart(SmartList),
+    Browser,
+    History,
+}
+
+impl MenuItem {
+    pub fn label(self) -> &'static str {
+        match self {
+            MenuItem::Playlists => "Playlists",
+            MenuItem::Smart(list) => list.label(),
+            MenuItem::Browser => "Artists & Albums",
+            MenuItem::History => "History",
+        }
+    }
+}
+
+/// The library menu, in display order.
+pub const MENU: [MenuItem; 6] = [
+    MenuItem::Playlists,
+    MenuItem::Smart(SmartList::RecentlyAdded),
+    MenuItem::Smart(SmartList::RecentlyPlayed),
+    MenuItem::Smart(SmartList::ForgottenFavorites),
+    MenuItem::Browser,
+    MenuItem::History,
+];
+
+/// Column the track list is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SortKey {
+    /// The playlist's own order.
+    #[default]
+    Position,
+    Title,
+    Artist,
+    Album,
+    Duration,
+    DateAdded,
+    PlayCount,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Title => SortKey::Artist,
+            SortKey::Artist => SortKey::Album,
+            SortKey::Album => SortKey::Duration,
+            SortKey::Duration => SortKey::DateAdded,
+            SortKey::DateAdded => SortKey::PlayCount,
+            SortKey::PlayCount => SortKey::Position,
+            SortKey::Position => SortKey::Title,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Position => "order",
+            SortKey::Title => "title",
+            SortKey::Artist => "artist",
+            SortKey::Album => "album",
+            SortKey::Duration => "time",
+            SortKey::DateAdded => "added",
+            SortKey::PlayCount => "plays",
+        }
+    }
+
+    fn compare(self, a: &TrackEntry, b: &TrackEntry) -> Ordering {
+        let text = |x: &str, y: &str| x.to_lowercase().cmp(&y.to_lowercase());
+        match self {
+            SortKey::Position => a.position.cmp(&b.position),
+            SortKey::Title => text(&a.name, &b.name),
+            SortKey::Artist => text(&a.artist, &b.artist).then_with(|| text(&a.album, &b.album)),
+            SortKey::Album => text(&a.album, &b.album),
+            SortKey::Duration => a.duration.total_cmp(&b.duration),
+            SortKey::DateAdded => a.date_added.cmp(&b.date_added),
+            SortKey::PlayCount => a.play_count.cmp(&b.play_count),
+        }
+    }
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
             should_quit: false,
+            config: Config::default(),
+            capabilities: Capabilities::default(),
             player: PlayerStatus::default(),
+            player_updated_at: None,
+            muted_volume: None,
+            volume_set_at: None,
+            polled_position: 0.0,
             active_panel: Panel::Library,
             playlists: Vec::new(),
             playlist_state: ListState::default(),
             tracks: Vec::new(),
-            track_state: ListState::default(),
+            track_state: TableState::default(),
+            history_state: ListState::default(),
+            menu_state: ListState::default().with_selected(Some(0)),
             view: LibraryView::Playlists,
+            browser: None,
             search_mode: false,
             search_query: String::new(),
+            filter_error: None,
             loading: false,
             track_cache: HashMap::new(),
+            recent_playlists: Vec::new(),
+            generated_playlists: Vec::new(),
+            playlist_opens: HashMap::new(),
+            track_positions: HashMap::new(),
+            open_search: None,
+            open_list: None,
+            incomplete: None,
+            load_progress: None,
+            search_cache: HashMap::new(),
+            sort_key: SortKey::DateAdded,
+            sort_descending: true,
+            play_errors: HashMap::new(),
+            marked: HashSet::new(),
+            visual_anchor: None,
             pre_search_playlists: Vec::new(),
             pre_search_tracks: Vec::new(),
             artwork: None,
             artwork_track: String::new(),
+            backdrop: None,
+            playlist_cover: None,
+            playlist_cover_requested: String::new(),
             mini_player: false,
-            notification: None,
+            split_percent: None,
+            zoomed: false,
+            terminal_width: 0,
+            safe_mode: false,
+            karaoke: false,
+            announce: false,
+            rating_mode: false,
+            bpm_tap: None,
+            lyrics_editor: None,
+            artwork_chooser: None,
+            toasts: Vec::new(),
+            up_next: None,
+            up_next_shown: false,
+            output_device: String::new(),
+            output_changed_at: None,
+            level_meter: None,
+            tick: 0,
+            last_input: Instant::now(),
+            screensaver: false,
+            command_mode: false,
+            command_input: String::new(),
+            popup: None,
+            modal: None,
+            history: History::default(),
+            show_lyrics: false,
+            lyrics: None,
+            lyrics_for: String::new(),
+            lyrics_loading: false,
+            lyrics_cursor: None,
+            lyrics_search_mode: false,
+            lyrics_query: String::new(),
+            lyrics_cache: HashMap::new(),
+            pending_count: None,
+            pending_g: false,
+            library_height: 0,
+            tabs: Vec::new(),
+            active_tab: 0,
         }
     }
 }
 
 impl App {
     pub fn update_player_status(&mut self, status: PlayerStatus) {
+        self.polled_position = status.position;
+        self.player_updated_at = Some(Instant::now());
+        let settling = self.volume_set_at.is_some_and(|t| t.elapsed() < VOLUME_SETTLE);
+        let volume = if settling { self.player.volume } else { status.volume };
         self.player = status;
+        self.player.volume = volume;
+        // Turned up elsewhere (Music, volume keys) while muted: that's an unmute
+        if !settling && !volume.is_muted() {
+            self.muted_volume = None;
+        }
     }
 
-    pub fn notify(&mut self, msg: impl Into<String>) {
-        self.notification = Some((msg.into(), Instant::now()));
+    /// Mute, remembering the current volume, or unmute back to it. Returns
+    /// the volume to set in Music.
+    pub fn toggle_mute(&mut self) -> Volume {
+        let volume = match self.muted_volume.take() {
+            Some(previous) => previous,
+            None => {
+                self.muted_volume = Some(self.player.volume);
+                Volume::MIN
+            }
+        };
+        self.player.volume = volume;
+        self.volume_set_at = Some(Instant::now());
+        volume
     }
 
-    pub fn clear_expired_notification(&mut self) {
-        if let Some((_, when)) = &self.notification {
-            if when.elapsed() >= std::time::Duration::from_secs(2) {
-                self.notification = None;
+    /// Advance `player.position` from the last polled value while playing, so
+    /// the progress bar moves every frame instead of once per poll.
+    pub fn interpolate_position(&mut self) {
+        if self.player.state != PlayState::Playing {
+            return;
+        }
+        let Some(updated_at) = self.player_updated_at else { return };
+        let position = self.polled_position + updated_at.elapsed().as_secs_f64();
+        self.player.position = if self.player.duration > 0.0 {
+            position.min(self.player.duration)
+        } else {
+            position
+        };
+    }
+
+    /// Start the screensaver once music has played for the configured time
+    /// without any input. Not over karaoke, which is watched, not typed at.
+    pub fn check_idle(&mut self) {
+        let Some(minutes) = self.config.screensaver_minutes else { return };
+        if self.karaoke {
+            return;
+        }
+        if self.player.state == PlayState::Playing
+            && self.last_input.elapsed() >= Duration::from_secs(minutes * 60)
+        {
+            self.screensaver = true;
+        }
+    }
+
+    /// Show the "Up next" toast once, shortly before the current track ends.
+    pub fn check_up_next(&mut self) {
+        if self.up_next_shown || self.player.state != PlayState::Playing {
+            return;
+        }
+        let remaining = self.player.duration - self.player.position;
+        if self.player.duration <= 0.0 || remaining > UP_NEXT_LEAD {
+            return;
+        }
+        if let Some(next) = &self.up_next {
+            let msg = format!("Up next: {} \u{2013} {}", next.artist, next.name);
+            self.up_next_shown = true;
+            self.notify(msg);
+        }
+    }
+
+    /// Configured lyrics providers that can work here.
+    pub fn usable_lyrics_providers(&self) -> Vec<Provider> {
+        self.config
+            .lyrics_providers
+            .iter()
+            .copied()
+            .filter(|p| match p {
+                Provider::Local | Provider::Music => self.capabilities.lyrics.usable(),
+                Provider::Lrclib | Provider::Genius => self.capabilities.network.usable(),
+            })
+            .collect()
+    }
+
+    /// Why lyrics can't be shown at all, if no configured provider can work.
+    pub fn lyrics_unavailable(&self) -> Option<String> {
+        if !self.usable_lyrics_providers().is_empty() {
+            return None;
+        }
+        let reasons: Vec<&str> = [&self.capabilities.lyrics, &self.capabilities.network]
+            .into_iter()
+            .filter_map(|s| s.reason())
+            .collect();
+        Some(if reasons.is_empty() {
+            "no lyrics providers configured".to_string()
+        } else {
+            reasons.join("; ")
+        })
+    }
+
+    /// Cache key identifying the playing track's lyrics.
+    pub fn lyrics_key(&self) -> String {
+        format!("{} - {}", self.player.artist, self.player.track_name)
+    }
+
+    /// Section start times for the playing track, from its synced lyrics once
+    /// they're loaded. Music doesn't expose chapters to scripts, so lyrics
+    /// are the only source.
+    pub fn sections(&self) -> Vec<f64> {
+        if self.lyrics_for != self.lyrics_key() {
+            return Vec::new();
+        }
+        self.lyrics.as_ref().map(Lyrics::sections).unwrap_or_default()
+    }
+
+    /// Move the lyrics cursor by `delta` lines, starting from the line being
+    /// sung when the cursor isn't set yet.
+    pub fn move_lyrics_cursor(&mut self, delta: isize) {
+        let Some(ref lyrics) = self.lyrics else { return };
+        if lyrics.lines.is_empty() {
+            return;
+        }
+        let from = self
+            .lyrics_cursor
+            .or_else(|| lyrics.current_line(self.player.position))
+            .unwrap_or(0);
+        let last = lyrics.lines.len() - 1;
+        self.lyrics_cursor = Some(from.saturating_add_signed(delta).min(last));
+    }
+
+    /// Move the lyrics cursor to the next (or previous) line matching the
+    /// lyrics query, wrapping around. `include_current` lets the line under
+    /// the cursor match, so typing refines the current hit.
+    pub fn find_lyric(&mut self, forward: bool, include_current: bool) {
+        let Some(ref lyrics) = self.lyrics else { return };
+        let query = self.lyrics_query.to_lowercase();
+        let len = lyrics.lines.len();
+        if query.is_empty() || len == 0 {
+            return;
+        }
+        let start = self.lyrics_cursor.unwrap_or(0);
+        let first_step = if include_current { 0 } else { 1 };
+        let found = (first_step..len + first_step)
+            .map(|step| {
+                if forward {
+                    (start + step) % len
+                } else {
+                    (start + len - step % len) % len
+                }
+            })
+            .find(|&i| lyrics.lines[i].text.to_lowercase().contains(&query));
+        if found.is_some() {
+            self.lyrics_cursor = found;
+        }
+    }
+
+    /// Record the active output device, flashing it in the status bar when
+    /// it changes (but not on the first reading).
+    pub fn set_output_device(&mut self, device: String) {
+        if device == self.output_device {
+            return;
+        }
+        if !self.output_device.is_empty() {
+            self.output_changed_at = Some(Instant::now());
+        }
+        self.output_device = device;
+    }
+
+    /// Whether the output device changed recently enough to be highlighted.
+    pub fn output_flashing(&self) -> bool {
+        self.output_changed_at
+            .is_some_and(|t| t.elapsed() < Duration::from_secs(3))
+    }
+
+    /// Show a transient message. A repeat of the newest toast just restarts
+    /// its timer instead of stacking.
+    pub fn push_message(&mut self, level: Level, text: impl Into<String>) {
+        let text = text.into();
+        crate::log::write(format!("{level:?}: {text}"));
+        if let Some(last) = self.toasts.last_mut() {
+            if last.level == level && last.text == text {
+                last.at = Instant::now();
+                return;
             }
         }
+        self.toasts.push(Toast {
+            level,
+            text,
+            at: Instant::now(),
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+    }
+
+    /// Show an informational toast.
+    pub fn notify(&mut self, msg: impl Into<String>) {
+        self.push_message(Level::Info, msg);
+    }
+
+    pub fn clear_expired_toasts(&mut self) {
+        self.toasts.retain(|t| !t.expired());
     }
 
     /// Move selection down by `n` in the current list.
     pub fn select_next_by(&mut self, n: usize) {
         match self.view {
+            LibraryView::Menu => {
+                let i = self.menu_state.selected().map_or(0, |i| (i + n).min(MENU.len() - 1));
+                self.menu_state.select(Some(i));
+            }
             LibraryView::Playlists => {
                 let len = self.playlists.len();
                 if len == 0 {
@@ -108,12 +1037,41 @@ impl App {
                 });
                 self.track_state.select(Some(i));
             }
+            LibraryView::History => {
+                let len = self.history_rows().len();
+                if len == 0 {
+                    return;
+                }
+                let i = self.history_state.selected().map_or(0, |i| {
+                    if i + n >= len { len - 1 } else { i + n }
+                });
+                self.history_state.select(Some(i));
+            }
+        }
+    }
+
+    /// Select row `index` in the current list, clamped to the last row.
+    pub fn select_index(&mut self, index: usize) {
+        let (_, len) = self.list_position();
+        if len == 0 {
+            return;
+        }
+        let i = Some(index.min(len - 1));
+        match self.view {
+            LibraryView::Playlists => self.playlist_state.select(i),
+            LibraryView::Tracks => self.track_state.select(i),
+            LibraryView::History => self.history_state.select(i),
+            LibraryView::Menu => self.menu_state.select(i),
         }
     }
 
     /// Move selection down by 1, wrapping.
     pub fn select_next(&mut self) {
         match self.view {
+            LibraryView::Menu => {
+                let i = self.menu_state.selected().map_or(0, |i| (i + 1) % MENU.len());
+                self.menu_state.select(Some(i));
+            }
             LibraryView::Playlists => {
                 let len = self.playlists.len();
                 if len == 0 {
@@ -134,12 +1092,26 @@ impl App {
                 });
                 self.track_state.select(Some(i));
             }
+            LibraryView::History => {
+                let len = self.history_rows().len();
+                if len == 0 {
+                    return;
+                }
+                let i = self.history_state.selected().map_or(0, |i| {
+                    if i + 1 >= len { 0 } else { i + 1 }
+                });
+                self.history_state.select(Some(i));
+            }
         }
     }
 
     /// Move selection up by `n` in the current list.
     pub fn select_previous_by(&mut self, n: usize) {
         match self.view {
+            LibraryView::Menu => {
+                let i = self.menu_state.selected().map_or(0, |i| i.saturating_sub(n));
+                self.menu_state.select(Some(i));
+            }
             LibraryView::Playlists => {
                 let len = self.playlists.len();
                 if len == 0 {
@@ -160,12 +1132,25 @@ impl App {
                 });
                 self.track_state.select(Some(i));
             }
+            LibraryView::History => {
+                if self.history_rows().is_empty() {
+                    return;
+                }
+                let i = self.history_state.selected().map_or(0, |i| {
+                    i.saturating_sub(n)
+                });
+                self.history_state.select(Some(i));
+            }
         }
     }
 
     /// Move selection up by 1, wrapping.
     pub fn select_previous(&mut self) {
         match self.view {
+            LibraryView::Menu => {
+                let i = self.menu_state.selected().map_or(0, |i| (i + MENU.len() - 1) % MENU.len());
+                self.menu_state.select(Some(i));
+            }
             LibraryView::Playlists => {
                 let len = self.playlists.len();
                 if len == 0 {
@@ -186,6 +1171,16 @@ impl App {
                 });
                 self.track_state.select(Some(i));
             }
+            LibraryView::History => {
+                let len = self.history_rows().len();
+                if len == 0 {
+                    return;
+                }
+                let i = self.history_state.selected().map_or(0, |i| {
+                    if i == 0 { len - 1 } else { i - 1 }
+                });
+                self.history_state.select(Some(i));
+            }
         }
     }
 
@@ -193,6 +1188,7 @@ impl App {
     pub fn enter_search(&mut self) {
         self.search_mode = true;
         self.search_query.clear();
+        self.filter_error = None;
         match self.view {
             LibraryView::Playlists => {
                 self.pre_search_playlists = self.playlists.clone();
@@ -200,6 +1196,8 @@ impl App {
             LibraryView::Tracks => {
                 self.pre_search_tracks = self.tracks.clone();
             }
+            // Filtered on the fly by `history_rows`; the menu isn't filtered
+            LibraryView::History | LibraryView::Menu => {}
         }
     }
 
@@ -224,24 +1222,30 @@ impl App {
                 });
             }
             LibraryView::Tracks => {
-                self.tracks = if query.is_empty() {
-                    self.pre_search_tracks.clone()
-                } else {
-                    self.pre_search_tracks
-                        .iter()
-                        .filter(|t| {
-                            t.name.to_lowercase().contains(&query)
-                                || t.artist.to_lowercase().contains(&query)
-                        })
-                        .cloned()
-                        .collect()
+                // Row numbers shift, so a visual range no longer makes sense
+                self.visual_anchor = None;
+                let filter = match filter::parse(&self.search_query) {
+                    Ok(filter) => filter,
+                    Err(e) => {
+                        self.filter_error = Some(e);
+                        return;
+                    }
                 };
+                self.filter_error = None;
+                self.tracks = self
+                    .pre_search_tracks
+                    .iter()
+                    .filter(|t| filter.matches(t))
+                    .cloned()
+                    .collect();
                 self.track_state.select(if self.tracks.is_empty() {
                     None
                 } else {
                     Some(0)
                 });
             }
+            LibraryView::History => self.select_history_start(),
+            LibraryView::Menu => {}
         }
     }
 
@@ -254,6 +1258,7 @@ impl App {
     pub fn cancel_search(&mut self) {
         self.search_mode = false;
         self.search_query.clear();
+        self.filter_error = None;
         match self.view {
             LibraryView::Playlists => {
                 self.playlists = std::mem::take(&mut self.pre_search_playlists);
@@ -271,6 +1276,454 @@ impl App {
                     Some(0)
                 });
             }
+            LibraryView::History => self.select_history_start(),
+            LibraryView::Menu => {}
+        }
+    }
+
+    /// Played tracks for the History view, most recent first, narrowed by
+    /// the search query.
+    pub fn history_rows(&self) -> Vec<&HistoryEntry> {
+        let query = self.search_query.to_lowercase();
+        self.history
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| {
+                query.is_empty()
+                    || e.name.to_lowercase().contains(&query)
+                    || e.artist.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Select the most recent row of the History view.
+    pub fn select_history_start(&mut self) {
+        let empty = self.history_rows().is_empty();
+        self.history_state.select(if empty { None } else { Some(0) });
+    }
+
+    /// Playlists tracks can be added to whose name contains `filter`
+    /// (case-insensitive), for the playlist picker.
+    pub fn picker_matches(&self, filter: &str) -> Vec<&PlaylistEntry> {
+        let filter = filter.to_lowercase();
+        self.playlists
+            .iter()
+            .filter(|p| p.editable && p.name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    /// Move playlist `id` to the front of the recently opened playlists.
+    pub fn note_recent_playlist(&mut self, id: i32) {
+        *self.playlist_opens.entry(id).or_default() += 1;
+        self.recent_playlists.retain(|&i| i != id);
+        self.recent_playlists.insert(0, id);
+        self.recent_playlists.truncate(RECENT_PLAYLISTS);
+    }
+
+    /// Ids of playlists worth loading ahead of time: pinned ones (every
+    /// playlist with a pinned name), then the `prefetch_top` most opened,
+    /// then recent ones, skipping any already cached.
+    pub fn prefetch_candidates(&self) -> Vec<i32> {
+        let pinned = self
+            .playlists
+            .iter()
+            .filter(|p| self.config.pinned_playlists.contains(&p.name))
+            .map(|p| p.id);
+        let mut most_used: Vec<(i32, u32)> = self
+            .playlist_opens
+            .iter()
+            .map(|(&id, &opens)| (id, opens))
+            .filter(|(id, _)| self.playlists.iter().any(|p| p.id == *id))
+            .collect();
+        most_used.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let most_used = most_used.into_iter().take(self.config.prefetch_top).map(|(id, _)| id);
+        let mut ids: Vec<i32> = Vec::new();
+        for id in pinned.chain(most_used).chain(self.recent_playlists.iter().copied()) {
+            if !ids.contains(&id) && !self.track_cache.contains_key(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Replace the playlist list, keeping the selection on the same playlist
+    /// when it still exists.
+    pub fn set_playlists(&mut self, playlists: Vec<PlaylistEntry>) {
+        let selected = self.selected_playlist().map(|p| p.id);
+        self.playlists = playlists;
+        self.prune_track_positions();
+        let pos = selected
+            .and_then(|id| self.playlists.iter().position(|p| p.id == id))
+            .or(if self.playlists.is_empty() { None } else { Some(0) })
+            .map(|i| i.min(self.playlists.len().saturating_sub(1)));
+        self.playlist_state.select(pos);
+    }
+
+    /// Replace the track list, sorting it and selecting the first row, or
+    /// the row the list was last left on.
+    pub fn set_tracks(&mut self, tracks: Vec<TrackEntry>) {
+        self.clear_marks();
+        self.tracks = tracks;
+        self.sort_tracks();
+        self.track_state.select(if self.tracks.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        self.restore_position();
+    }
+
+    /// What the track list belongs to, for remembering where it was left:
+    /// the album picked in the column browser, or the selected playlist.
+    /// None for searches, smart lists and the browser's "All" albums.
+    fn track_list_key(&self) -> Option<String> {
+        if self.open_search.is_some() || self.open_list.is_some() {
+            return None;
+        }
+        match &self.browser {
+            Some(browser) => browser.selected_album().map(|album| format!("album:{album}")),
+            None => self.selected_playlist().map(|p| format!("playlist:{}", p.id)),
+        }
+    }
+
+    /// Note where the open track list is, for `restore_position`. Positions
+    /// at the top aren't kept. Skipped while the list is still streaming in,
+    /// so the saved position isn't lost before its chunk arrives.
+    pub fn remember_position(&mut self) {
+        if self.view != LibraryView::Tracks || self.loading || self.open_progress().is_some() {
+            return;
+        }
+        let Some(key) = self.track_list_key() else { return };
+        let (Some(row), Some(track)) = (self.track_state.selected(), self.selected_track()) else { return };
+        if row == 0 {
+            self.track_positions.remove(&key);
+        } else {
+            let above = row.saturating_sub(self.track_state.offset());
+            let noted = self.track_positions.values().map(|p| p.noted).max().map_or(0, |n| n + 1);
+            self.track_positions.insert(key, TrackPosition { track_id: track.id, above, noted });
+            if self.track_positions.len() > MAX_TRACK_POSITIONS {
+                let oldest = self.track_positions.iter().min_by_key(|(_, p)| p.noted).map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    self.track_positions.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    /// Forget the positions of playlists that no longer exist. Skipped while
+    /// no playlists are loaded, which is more likely a failed fetch.
+    fn prune_track_positions(&mut self) {
+        if self.playlists.is_empty() {
+            return;
+        }
+        let playlists = &self.playlists;
+        self.track_positions.retain(|key, _| {
+            key.strip_prefix("playlist:")
+                .and_then(|id| id.parse::<i32>().ok())
+                .is_none_or(|id| playlists.iter().any(|p| p.id == id))
+        });
+    }
+
+    /// Select the track the list was left on, if it's (still) in it.
+    fn restore_position(&mut self) {
+        let Some(position) = self.track_list_key().and_then(|key| self.track_positions.get(&key).copied()) else {
+            return;
+        };
+        let Some(row) = self.tracks.iter().position(|t| t.id == position.track_id) else { return };
+        self.track_state.select(Some(row));
+        *self.track_state.offset_mut() = row.saturating_sub(position.above);
+    }
+
+    /// Whether a playlist's cached tracks are older than `cache_ttl_minutes`.
+    pub fn cache_is_stale(&self, id: i32) -> bool {
+        let ttl = Duration::from_secs(self.config.cache_ttl_minutes * 60);
+        self.track_cache
+            .get(&id)
+            .is_some_and(|(fetched_at, _)| ttl > Duration::ZERO && fetched_at.elapsed() >= ttl)
+    }
+
+    /// Replace a playlist's cached tracks with a fresh fetch and, if the
+    /// list is open, update it in place, keeping the selection, marks and
+    /// any search filter.
+    pub fn refresh_tracks(&mut self, id: i32, tracks: Vec<TrackEntry>) {
+        self.track_cache.insert(id, (Instant::now(), tracks.clone()));
+        if self.view != LibraryView::Tracks || self.open_playlist().is_none_or(|p| p.id != id) {
+            return;
+        }
+        let selected = self.selected_track().map(|t| t.id);
+        self.marked.retain(|id| tracks.iter().any(|t| t.id == *id));
+        if self.search_mode || !self.search_query.is_empty() {
+            let filter = filter::parse(&self.search_query).ok();
+            self.tracks = tracks
+                .iter()
+                .filter(|t| filter.as_ref().is_none_or(|f| f.matches(t)))
+                .cloned()
+                .collect();
+            self.pre_search_tracks = tracks;
+        } else {
+            self.tracks = tracks;
+        }
+        self.track_state.select(None);
+        self.sort_tracks();
+        let pos = selected
+            .and_then(|id| self.tracks.iter().position(|t| t.id == id))
+            .or(if self.tracks.is_empty() { None } else { Some(0) });
+        self.track_state.select(pos);
+    }
+
+    /// Add tracks that streamed in to the open list, keeping the selection,
+    /// marks and any search filter.
+    pub fn append_tracks(&mut self, batch: Vec<TrackEntry>) {
+        if self.search_mode || !self.search_query.is_empty() {
+            if let Ok(filter) = filter::parse(&self.search_query) {
+                self.tracks.extend(batch.iter().filter(|t| filter.matches(t)).cloned());
+            }
+            self.pre_search_tracks.extend(batch);
+        } else {
+            self.tracks.extend(batch);
+        }
+        self.sort_tracks();
+        if self.track_state.selected().is_none() && !self.tracks.is_empty() {
+            self.track_state.select(Some(0));
+        }
+        // The remembered row may be in this chunk; don't move anyone who
+        // has scrolled on already
+        if self.track_state.selected() == Some(0) {
+            self.restore_position();
+        }
+    }
+
+    /// Loading progress of the open playlist, while its tracks stream in.
+    pub fn open_progress(&self) -> Option<&LoadProgress> {
+        let id = self.open_playlist()?.id;
+        self.load_progress.as_ref().filter(|p| p.playlist_id == id)
+    }
+
+    /// The partial load of the open playlist, if it stopped partway.
+    pub fn open_incomplete(&self) -> Option<&Incomplete> {
+        let id = self.open_playlist()?.id;
+        self.incomplete.as_ref().filter(|i| i.playlist_id == id)
+    }
+
+    /// Show the column browser's current artist/album selection in the
+    /// track list.
+    pub fn show_browser_tracks(&mut self) {
+        let Some(tracks) = self.browser.as_ref().map(ColumnBrowser::tracks) else { return };
+        self.open_search = None;
+        self.open_list = None;
+        self.set_tracks(tracks);
+        self.view = LibraryView::Tracks;
+    }
+
+    /// Mark or unmark the selected track and move to the next row.
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_track().map(|t| t.id) else { return };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.select_next_by(1);
+    }
+
+    /// Start a visual range at the selected row, or mark the range and end it.
+    pub fn toggle_visual(&mut self) {
+        if self.visual_anchor.is_none() {
+            self.visual_anchor = self.track_state.selected();
+            return;
+        }
+        let ids: Vec<i32> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.in_visual_range(*i))
+            .map(|(_, t)| t.id)
+            .collect();
+        self.marked.extend(ids);
+        self.visual_anchor = None;
+    }
+
+    /// Drop all marks and any visual range. Returns whether anything was marked.
+    pub fn clear_marks(&mut self) -> bool {
+        let had_marks = !self.marked.is_empty() || self.visual_anchor.is_some();
+        self.marked.clear();
+        self.visual_anchor = None;
+        had_marks
+    }
+
+    /// Whether row `index` lies between the visual anchor and the selection.
+    fn in_visual_range(&self, index: usize) -> bool {
+        match (self.visual_anchor, self.track_state.selected()) {
+            (Some(anchor), Some(cursor)) => {
+                (anchor.min(cursor)..=anchor.max(cursor)).contains(&index)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether row `index` is marked or inside the visual range.
+    pub fn is_marked(&self, index: usize) -> bool {
+        self.in_visual_range(index)
+            || self.tracks.get(index).is_some_and(|t| self.marked.contains(&t.id))
+    }
+
+    /// Number of marked rows, counting the visual range.
+    pub fn marked_count(&self) -> usize {
+        (0..self.tracks.len()).filter(|&i| self.is_marked(i)).count()
+    }
+
+    /// Tracks a batch action applies to: the marked rows in list order, or
+    /// the selected track when nothing is marked.
+    pub fn target_tracks(&self) -> Vec<&TrackEntry> {
+        let marked: Vec<&TrackEntry> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_marked(*i))
+            .map(|(_, t)| t)
+            .collect();
+        if marked.is_empty() {
+            self.selected_track().into_iter().collect()
+        } else {
+            marked
+        }
+    }
+
+    /// Advance to the next sort column.
+    pub fn cycle_sort_key(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.sort_tracks();
+    }
+
+    /// Flip between ascending and descending order.
+    pub fn toggle_sort_direction(&mut self) {
+        self.sort_descending = !self.sort_descending;
+        self.sort_tracks();
+    }
+
+    /// Sort the track list (and its pre-search snapshot) by the active sort,
+    /// keeping the selection on the same track.
+    pub fn sort_tracks(&mut self) {
+        let selected_id = self.selected_track().map(|t| t.id);
+        self.visual_anchor = None;
+        let (key, descending) = (self.sort_key, self.sort_descending);
+        let cmp = |a: &TrackEntry, b: &TrackEntry| {
+            let ord = key.compare(a, b);
+            if descending { ord.reverse() } else { ord }
+        };
+        self.tracks.sort_by(cmp);
+        self.pre_search_tracks.sort_by(cmp);
+
+        if let Some(id) = selected_id {
+            let pos = self.tracks.iter().position(|t| t.id == id);
+            self.track_state.select(pos);
+        }
+    }
+
+    /// Reflect a favorite/dislike change on `ids` everywhere tracks are held,
+    /// and on the player if one of them is playing.
+    pub fn apply_opinion(&mut self, ids: &[i32], opinion: Opinion) {
+        let (favorited, disliked) = opinion.flags();
+        self.edit_tracks(ids, |t| {
+            t.favorited = favorited;
+            t.disliked = disliked;
+        });
+        if ids.contains(&self.player.track_id) {
+            self.player.favorited = favorited;
+            self.player.disliked = disliked;
+        }
+    }
+
+    /// Reflect a rating change on `ids`, like `apply_opinion`.
+    pub fn apply_rating(&mut self, ids: &[i32], rating: u8) {
+        self.edit_tracks(ids, |t| t.rating = rating);
+        if ids.contains(&self.player.track_id) {
+            self.player.rating = rating;
+        }
+    }
+
+    /// The current rating of each of `ids`, for undoing a failed change.
+    pub fn ratings_of(&self, ids: &[i32]) -> Vec<(i32, u8)> {
+        ids.iter()
+            .filter_map(|&id| match self.shown_track(id) {
+                Some(t) => Some((id, t.rating)),
+                None => (id == self.player.track_id).then_some((id, self.player.rating)),
+            })
+            .collect()
+    }
+
+    /// The current opinion of each of `ids`, like `ratings_of`.
+    pub fn opinions_of(&self, ids: &[i32]) -> Vec<(i32, Opinion)> {
+        ids.iter()
+            .filter_map(|&id| match self.shown_track(id) {
+                Some(t) => Some((id, Opinion::of(t.favorited, t.disliked))),
+                None => (id == self.player.track_id)
+                    .then(|| (id, Opinion::of(self.player.favorited, self.player.disliked))),
+            })
+            .collect()
+    }
+
+    /// Track `id` from the visible list or its pre-search snapshot.
+    fn shown_track(&self, id: i32) -> Option<&TrackEntry> {
+        self.tracks.iter().chain(&self.pre_search_tracks).find(|t| t.id == id)
+    }
+
+    /// Mark `ids` again after a failed batch edit, those still listed.
+    pub fn restore_marks(&mut self, ids: &[i32]) {
+        let listed: Vec<i32> = ids.iter().copied().filter(|id| self.tracks.iter().any(|t| t.id == *id)).collect();
+        self.marked.extend(listed);
+    }
+
+    /// Apply `edit` to every held copy of the tracks in `ids`: the visible
+    /// list, its pre-search snapshot and the playlist and search caches.
+    fn edit_tracks(&mut self, ids: &[i32], edit: impl Fn(&mut TrackEntry)) {
+        let lists = std::iter::once(&mut self.tracks)
+            .chain(std::iter::once(&mut self.pre_search_tracks))
+            .chain(self.track_cache.values_mut().map(|(_, tracks)| tracks))
+            .chain(self.search_cache.values_mut().map(|(_, tracks)| tracks));
+        for track in lists.flatten().filter(|t| ids.contains(&t.id)) {
+            edit(track);
+        }
+    }
+
+    /// Whether the open track list shows the playlist's own order, unfiltered,
+    /// so rows can be moved.
+    pub fn can_reorder(&self) -> bool {
+        self.open_playlist().is_some()
+            && self.sort_key == SortKey::Position
+            && self.search_query.is_empty()
+    }
+
+    /// Swap the playlist positions of tracks `a` and `b` in playlist
+    /// `playlist_id`, both in its cached track list and, if it's open, the
+    /// visible one.
+    pub fn swap_positions(&mut self, playlist_id: i32, a: i32, b: i32) {
+        fn swap(tracks: &mut [TrackEntry], a: i32, b: i32) {
+            let pa = tracks.iter().position(|t| t.id == a);
+            let pb = tracks.iter().position(|t| t.id == b);
+            if let (Some(pa), Some(pb)) = (pa, pb) {
+                let pos = tracks[pa].position;
+                tracks[pa].position = tracks[pb].position;
+                tracks[pb].position = pos;
+            }
+        }
+
+        if let Some((_, cached)) = self.track_cache.get_mut(&playlist_id) {
+            swap(cached, a, b);
+        }
+        if self.open_playlist().is_some_and(|p| p.id == playlist_id) {
+            swap(&mut self.tracks, a, b);
+            swap(&mut self.pre_search_tracks, a, b);
+            self.sort_tracks();
+        }
+    }
+
+    /// Selected index and length of the list shown in the current view.
+    pub fn list_position(&self) -> (Option<usize>, usize) {
+        match self.view {
+            LibraryView::Playlists => (self.playlist_state.selected(), self.playlists.len()),
+            LibraryView::Tracks => (self.track_state.selected(), self.tracks.len()),
+            LibraryView::History => (self.history_state.selected(), self.history_rows().len()),
+            LibraryView::Menu => (self.menu_state.selected(), MENU.len()),
         }
     }
 
@@ -281,6 +1734,20 @@ impl App {
             .and_then(|i| self.playlists.get(i))
     }
 
+    /// The playlist whose tracks are open in the Tracks view (None when the
+    /// view holds library search results, a library menu list or the column
+    /// browser's tracks).
+    pub fn open_playlist(&self) -> Option<&PlaylistEntry> {
+        if self.view != LibraryView::Tracks
+            || self.open_search.is_some()
+            || self.open_list.is_some()
+            || self.browser.is_some()
+        {
+            return None;
+        }
+        self.selected_playlist()
+    }
+
     /// Get a reference to the currently selected track, if any.
     pub fn selected_track(&self) -> Option<&TrackEntry> {
         self.track_state
@@ -288,6 +1755,164 @@ impl App {
             .and_then(|i| self.tracks.get(i))
     }
 
+    // -----------------------------------------------------------------------
+    // Tabs
+    // -----------------------------------------------------------------------
+
+    /// Names of the open tabs, in order.
+    pub fn tab_labels(&self) -> Vec<String> {
+        (0..self.tabs.len())
+            .map(|i| {
+                if i == self.active_tab {
+                    let playlist = self.selected_playlist();
+                    view_label(&self.view, &self.open_search, self.open_list, self.browser.is_some(), playlist)
+                } else {
+                    self.tabs[i].label(&self.playlists)
+                }
+            })
+            .collect()
+    }
+
+    /// Open a new tab on the playlist list, after the active one.
+    pub fn new_tab(&mut self) {
+        if self.tabs.len() >= MAX_TABS {
+            self.notify(format!("At most {MAX_TABS} tabs"));
+            return;
+        }
+        if self.tabs.is_empty() {
+            // The view so far becomes the first tab
+            self.tabs.push(TabView::default());
+        }
+        let tab = TabView::new(self);
+        self.tabs.insert(self.active_tab + 1, tab);
+        self.switch_tab(self.active_tab + 1);
+    }
+
+    /// Close the active tab, switching to its neighbour. False for the last
+    /// one.
+    pub fn close_tab(&mut self) -> bool {
+        if self.tabs.len() < 2 {
+            return false;
+        }
+        let closing = self.active_tab;
+        let next = if closing + 1 < self.tabs.len() { closing + 1 } else { closing - 1 };
+        self.switch_tab(next);
+        self.tabs.remove(closing);
+        if next > closing {
+            self.active_tab -= 1;
+        }
+        if self.tabs.len() == 1 {
+            self.tabs.clear();
+            self.active_tab = 0;
+        }
+        true
+    }
+
+    /// Show tab `index`, keeping the current view in its own tab.
+    pub fn switch_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() || index == self.active_tab {
+            return;
+        }
+        let mut current = std::mem::take(&mut self.tabs[self.active_tab]);
+        current.swap(self);
+        self.tabs[self.active_tab] = current;
+        let mut next = std::mem::take(&mut self.tabs[index]);
+        next.swap(self);
+        self.tabs[index] = next;
+        self.active_tab = index;
+        self.search_mode = false;
+
+        // Loads that finished while the tab was in the background went to
+        // the cache only
+        if let Some(id) = self.open_playlist().map(|p| p.id) {
+            if let Some((_, cached)) = self.track_cache.get(&id) {
+                if cached.len() != self.tracks.len() && self.search_query.is_empty() {
+                    let cached = cached.clone();
+                    self.refresh_tracks(id, cached);
+                }
+            }
+        }
+    }
+
+    /// Move to the next (or previous) tab, wrapping around.
+    pub fn cycle_tab(&mut self, forward: bool) {
+        let len = self.tabs.len();
+        if len < 2 {
+            return;
+        }
+        let index = if forward { (self.active_tab + 1) % len } else { (self.active_tab + len - 1) % len };
+        self.switch_tab(index);
+    }
+
+}
+
+/// What to open into, from command-line flags. A `view` replaces the
+/// configured `start_view` for the launch.
+#[derive(Debug, Default)]
+pub struct StartupOptions {
+    pub view: Option<StartView>,
+    pub mini: bool,
+    /// Default config, no network, verbose log (see `main::run`).
+    pub safe_mode: bool,
+}
+
+/// Views to open at launch, from `--view` or `start_view`. Albums and
+/// artists open the whole library's tracks sorted by that column.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartView {
+    /// The last session's view (the saved UI state).
+    #[default]
+    Last,
+    Playlists,
+    Albums,
+    Artists,
+    /// The playlist Music is playing from, at the playing track.
+    Queue,
+}
+
+impl StartupOptions {
+    /// Apply onto an App that already has playlists loaded (and the saved
+    /// UI state, for `StartView::Last`).
+    pub fn apply(self, app: &mut App, view: StartView) {
+        if self.mini {
+            app.mini_player = true;
+        }
+        if view == StartView::Last {
+            return;
+        }
+        app.active_panel = Panel::Library;
+        match view {
+            StartView::Last | StartView::Playlists => {}
+            StartView::Albums | StartView::Artists => {
+                let Ok(id) = crate::library::library_playlist_id() else { return };
+                if open_at_start(app, id) {
+                    app.sort_key = if view == StartView::Albums { SortKey::Album } else { SortKey::Artist };
+                    app.sort_descending = false;
+                    app.sort_tracks();
+                }
+            }
+            StartView::Queue => {
+                let Ok(Some(from)) = crate::library::playing_from() else { return };
+                if open_at_start(app, from.playlist) {
+                    let row = app.tracks.iter().position(|t| t.id == from.track);
+                    app.track_state.select(row.or(Some(0)));
+                }
+            }
+        }
+    }
+}
+
+/// Open playlist `id` in the Tracks view, loading its tracks in full.
+/// False if it isn't in the playlist list or its tracks can't be read.
+fn open_at_start(app: &mut App, id: i32) -> bool {
+    let Some(pos) = app.playlists.iter().position(|p| p.id == id) else { return false };
+    app.playlist_state.select(Some(pos));
+    let Ok(tracks) = crate::library::fetch_playlist_tracks(id) else { return false };
+    app.track_cache.insert(id, (Instant::now(), tracks.clone()));
+    app.set_tracks(tracks);
+    app.view = LibraryView::Tracks;
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -296,9 +1921,28 @@ pub struct PersistedState {
     pub active_panel: Panel,
     pub mini_player: bool,
     pub library_view: LibraryView,
+    pub zoomed: bool,
     pub playlist_index: Option<usize>,
     pub track_index: Option<usize>,
+    pub history_index: Option<usize>,
+    pub menu_index: Option<usize>,
+    // Rows scrolled past at the top of each list
+    pub playlist_offset: usize,
+    pub track_offset: usize,
+    pub history_offset: usize,
+    /// The view's filter when it was saved; empty for none.
+    pub search_query: String,
+    pub sort_key: SortKey,
+    pub sort_descending: bool,
+    // Playlists are saved by persistent id (Music's `id()` can change
+    // between runs), and mapped back to ids when applied
     pub open_playlist_name: Option<String>,
+    pub open_playlist: Option<String>,
+    pub recent_playlists: Vec<String>,
+    pub playlist_open_counts: HashMap<String, u32>,
+    /// Keyed like `App::track_positions`, with `playlist:<persistent id>`.
+    pub track_positions: HashMap<String, TrackPosition>,
+    pub generated_playlists: Vec<String>,
 }
 
 impl Default for PersistedState {
@@ -307,9 +1951,23 @@ impl Default for PersistedState {
             active_panel: Panel::Library,
             mini_player: false,
             library_view: LibraryView::Playlists,
+            zoomed: false,
             playlist_index: None,
             track_index: None,
+            history_index: None,
+            menu_index: None,
+            playlist_offset: 0,
+            track_offset: 0,
+            history_offset: 0,
+            search_query: String::new(),
+            sort_key: SortKey::DateAdded,
+            sort_descending: false,
             open_playlist_name: None,
+            open_playlist: None,
+            recent_playlists: Vec::new(),
+            playlist_open_counts: HashMap::new(),
+            track_positions: HashMap::new(),
+            generated_playlists: Vec::new(),
         }
     }
 }
@@ -317,22 +1975,44 @@ impl Default for PersistedState {
 impl PersistedState {
     /// Extract persistable state from the current App.
     pub fn from_app(app: &App) -> Self {
-        let open_playlist_name = if app.view == LibraryView::Tracks {
-            app.playlist_state
-                .selected()
-                .and_then(|i| app.playlists.get(i))
-                .map(|p| p.name.clone())
-        } else {
-            None
-        };
+        let open_playlist = app.open_playlist();
+        let persistent = |id: i32| app.playlists.iter().find(|p| p.id == id).map(|p| p.persistent_id.clone());
 
         Self {
             active_panel: app.active_panel.clone(),
             mini_player: app.mini_player,
             library_view: app.view.clone(),
+            zoomed: app.zoomed,
             playlist_index: app.playlist_state.selected(),
             track_index: app.track_state.selected(),
-            open_playlist_name,
+            history_index: app.history_state.selected(),
+            menu_index: app.menu_state.selected(),
+            playlist_offset: app.playlist_state.offset(),
+            track_offset: app.track_state.offset(),
+            history_offset: app.history_state.offset(),
+            search_query: app.search_query.clone(),
+            sort_key: app.sort_key,
+            sort_descending: app.sort_descending,
+            open_playlist_name: open_playlist.map(|p| p.name.clone()),
+            open_playlist: open_playlist.map(|p| p.persistent_id.clone()),
+            recent_playlists: app.recent_playlists.iter().filter_map(|&id| persistent(id)).collect(),
+            playlist_open_counts: app
+                .playlist_opens
+                .iter()
+                .filter_map(|(&id, &count)| Some((persistent(id)?, count)))
+                .collect(),
+            track_positions: app
+                .track_positions
+                .iter()
+                .filter_map(|(key, &position)| {
+                    let key = match key.strip_prefix("playlist:") {
+                        Some(id) => format!("playlist:{}", persistent(id.parse().ok()?)?),
+                        None => key.clone(),
+                    };
+                    Some((key, position))
+                })
+                .collect(),
+            generated_playlists: app.generated_playlists.clone(),
         }
     }
 
@@ -340,6 +2020,28 @@ impl PersistedState {
     pub fn apply(self, app: &mut App) {
         app.active_panel = self.active_panel;
         app.mini_player = self.mini_player;
+        app.zoomed = self.zoomed;
+        let id = |persistent: &str| app.playlists.iter().find(|p| p.persistent_id == persistent).map(|p| p.id);
+        app.recent_playlists = self.recent_playlists.iter().filter_map(|p| id(p)).collect();
+        app.playlist_opens = self
+            .playlist_open_counts
+            .iter()
+            .filter_map(|(p, &count)| Some((id(p)?, count)))
+            .collect();
+        // Playlists that are gone drop out here
+        app.track_positions = self
+            .track_positions
+            .into_iter()
+            .filter_map(|(key, position)| {
+                let key = match key.strip_prefix("playlist:") {
+                    Some(p) => format!("playlist:{}", id(p)?),
+                    None => key,
+                };
+                Some((key, position))
+            })
+            .collect();
+        app.sort_key = self.sort_key;
+        app.sort_descending = self.sort_descending;
         app.view = LibraryView::Playlists; // explicit default; overridden below if tracks restore succeeds
 
         // Restore playlist selection (clamped to actual count)
@@ -349,24 +2051,63 @@ impl PersistedState {
             }
         }
 
-        // If we were in Tracks view and have a playlist name, try to reload tracks
+        if self.library_view == LibraryView::History {
+            app.view = LibraryView::History;
+            app.select_history_start();
+        }
+        if self.library_view == LibraryView::Menu {
+            app.view = LibraryView::Menu;
+            app.menu_state.select(Some(self.menu_index.unwrap_or(0).min(MENU.len() - 1)));
+        }
+
+        // If we were in Tracks view, try to reload that playlist's tracks. Ids
+        // are preferred; the name covers state saved before ids were stored.
         if self.library_view == LibraryView::Tracks {
-            if let Some(ref name) = self.open_playlist_name {
-                if let Some(pos) = app.playlists.iter().position(|p| p.name == *name) {
-                    app.playlist_state.select(Some(pos));
-                    if let Ok(tracks) = crate::library::fetch_playlist_tracks(name) {
-                        app.track_cache.insert(name.clone(), tracks.clone());
-                        app.tracks = tracks;
-                        let track_idx = self.track_index
-                            .filter(|&i| i < app.tracks.len())
-                            .or(if app.tracks.is_empty() { None } else { Some(0) });
-                        app.track_state.select(track_idx);
-                        app.view = LibraryView::Tracks;
-                        return;
-                    }
+            let pos = match (&self.open_playlist, &self.open_playlist_name) {
+                (Some(persistent), _) => app.playlists.iter().position(|p| p.persistent_id == *persistent),
+                (None, Some(name)) => app.playlists.iter().position(|p| p.name == *name),
+                (None, None) => None,
+            };
+            if let Some(pos) = pos {
+                let id = app.playlists[pos].id;
+                app.playlist_state.select(Some(pos));
+                if let Ok(tracks) = crate::library::fetch_playlist_tracks(id) {
+                    app.track_cache.insert(id, (Instant::now(), tracks.clone()));
+                    app.set_tracks(tracks);
+                    app.view = LibraryView::Tracks;
                 }
             }
         }
+
+        // Refilter as before; the saved rows are rows of the filtered list
+        if !self.search_query.is_empty() && app.view != LibraryView::Menu {
+            app.enter_search();
+            app.search_query = self.search_query;
+            app.apply_search_filter();
+            app.confirm_search();
+            if app.filter_error.is_some() {
+                app.cancel_search();
+            }
+        }
+        match app.view {
+            LibraryView::Playlists => {
+                let (row, offset) = restored_row(self.playlist_index, self.playlist_offset, app.playlists.len());
+                app.playlist_state.select(row);
+                *app.playlist_state.offset_mut() = offset;
+            }
+            LibraryView::Tracks => {
+                let (row, offset) = restored_row(self.track_index, self.track_offset, app.tracks.len());
+                app.track_state.select(row);
+                *app.track_state.offset_mut() = offset;
+            }
+            LibraryView::History => {
+                let len = app.history_rows().len();
+                let (row, offset) = restored_row(self.history_index, self.history_offset, len);
+                app.history_state.select(row);
+                *app.history_state.offset_mut() = offset;
+            }
+            LibraryView::Menu => {}
+        }
     }
 
     /// State file path: ~/.config/cli-music/state.json
@@ -392,3 +2133,12 @@ impl PersistedState {
         }
     }
 }
+
+/// The row to select and the scroll offset in a list of `len` rows: the
+/// saved ones if the list still has the row, the first row otherwise.
+fn restored_row(row: Option<usize>, offset: usize, len: usize) -> (Option<usize>, usize) {
+    match row.filter(|&r| r < len) {
+        Some(row) => (Some(row), offset.min(row)),
+        None => ((len > 0).then_some(0), 0),
+    }
+}
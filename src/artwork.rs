@@ -1,11 +1,23 @@
+use color_eyre::{eyre::eyre, Result};
 use image::{DynamicImage, imageops::FilterType};
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::itunes;
+use crate::library::TrackEntry;
 
 const UPPER_HALF: char = '\u{2580}'; // ▀
 
+/// Size requested when exporting artwork. The iTunes CDN returns the largest
+/// image it has when asked for more than the source resolution.
+const EXPORT_SIZE: &str = "5000x5000bb";
+
 /// Convert a DynamicImage to ratatui Lines using half-block characters.
 /// Each terminal row represents 2 pixel rows.
 pub fn image_to_halfblocks(img: &DynamicImage, width: u16, height: u16) -> Vec<Line<'static>> {
@@ -41,31 +53,313 @@ pub fn image_to_halfblocks(img: &DynamicImage, width: u16, height: u16) -> Vec<L
     lines
 }
 
+/// A heavily blurred, darkened copy of `img` for drawing behind text.
+/// Works on a small thumbnail so it stays cheap to compute and scale up.
+pub fn blurred_backdrop(img: &DynamicImage) -> DynamicImage {
+    img.resize_exact(48, 48, FilterType::Triangle)
+        .blur(3.0)
+        .brighten(-80)
+}
+
 /// Fetch artwork URL for a track from iTunes Search API.
 pub fn fetch_artwork_url(track_name: &str, artist: &str) -> Option<String> {
     let query = format!("{} {}", track_name, artist);
-    let encoded = urlencoding::encode(&query);
-    let url = format!(
-        "https://itunes.apple.com/search?term={}&entity=song&limit=10",
-        encoded
-    );
-
-    let resp = reqwest::blocking::get(&url).ok()?;
-    let json: serde_json::Value = resp.json().ok()?;
-
-    let results = json["results"].as_array()?;
-    for result in results {
-        if let Some(art_url) = result["artworkUrl100"].as_str() {
-            // Upgrade to 300x300 for better quality
-            let high_res = art_url.replace("100x100bb", "300x300bb");
-            return Some(high_res);
+    let results = itunes::client().search(&query, "song", 10).ok()?;
+    results.into_iter().find_map(|r| {
+        // Upgrade to 300x300 for better quality
+        r.artwork_url100.map(|url| url.replace("100x100bb", "300x300bb"))
+    })
+}
+
+/// Artwork URL for a track: the cover picked for its album in the chooser,
+/// otherwise the first iTunes Search match.
+pub fn artwork_url(track_name: &str, artist: &str, album: &str) -> Option<String> {
+    chosen_url(artist, album).or_else(|| fetch_artwork_url(track_name, artist))
+}
+
+// ---------------------------------------------------------------------------
+// Alternate artwork
+// ---------------------------------------------------------------------------
+
+/// Most candidates offered by the artwork chooser.
+const MAX_CANDIDATES: usize = 8;
+
+/// One cover the chooser offers, typically from a different release
+/// (original, remaster, deluxe edition, compilation).
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// 300x300 artwork URL.
+    pub url: String,
+    /// 100x100 artwork URL, for the thumbnail.
+    pub thumb_url: String,
+    /// Name of the release the cover belongs to.
+    pub release: String,
+    /// None until loaded.
+    pub thumbnail: Option<DynamicImage>,
+}
+
+/// Look up the distinct covers of every release containing the track,
+/// without their thumbnails. Blocks on the network, so call it from a
+/// background thread.
+pub fn fetch_candidates(track_name: &str, artist: &str) -> Vec<Candidate> {
+    let query = format!("{track_name} {artist}");
+    let Ok(results) = itunes::client().search(&query, "song", 50) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for r in results {
+        let Some(thumb_url) = r.artwork_url100 else { continue };
+        let url = thumb_url.replace("100x100bb", "300x300bb");
+        if candidates.iter().any(|c| c.url == url) {
+            continue;
+        }
+        candidates.push(Candidate {
+            url,
+            thumb_url,
+            release: r.collection_name,
+            thumbnail: None,
+        });
+        if candidates.len() == MAX_CANDIDATES {
+            break;
         }
     }
-    None
+    candidates
+}
+
+/// Chosen covers by album, in `~/.config/cli-music/artwork.json`.
+fn choices_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("cli-music").join("artwork.json"))
+}
+
+fn load_choices() -> HashMap<String, String> {
+    choices_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn album_key(artist: &str, album: &str) -> String {
+    format!("{artist} - {album}")
+}
+
+/// Key a track's cover is cached under: its album, or the track itself
+/// when it has none.
+pub fn cover_key(track_name: &str, artist: &str, album: &str) -> String {
+    if album.is_empty() {
+        format!("{artist} - {track_name} (single)")
+    } else {
+        album_key(artist, album)
+    }
+}
+
+/// The cover picked for an album in the chooser, if any.
+pub fn chosen_url(artist: &str, album: &str) -> Option<String> {
+    load_choices().remove(&album_key(artist, album))
+}
+
+/// Remember `url` as the cover for an album.
+pub fn save_choice(artist: &str, album: &str, url: &str) -> Result<()> {
+    let path = choices_path().ok_or_else(|| eyre!("No config directory"))?;
+    let mut choices = load_choices();
+    choices.insert(album_key(artist, album), url.to_string());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&choices)?)?;
+    Ok(())
 }
 
 /// Download image from URL and decode it.
 pub fn download_image(url: &str) -> Option<DynamicImage> {
-    let bytes = reqwest::blocking::get(url).ok()?.bytes().ok()?;
+    let bytes = download_bytes(url)?;
     image::load_from_memory(&bytes).ok()
 }
+
+/// Download raw bytes from a URL.
+fn download_bytes(url: &str) -> Option<Vec<u8>> {
+    let bytes = reqwest::blocking::get(url).ok()?.bytes().ok()?;
+    Some(bytes.to_vec())
+}
+
+/// Default export location: ~/Pictures/<Artist> - <Album>.jpg
+pub fn default_export_path(artist: &str, album: &str) -> Option<PathBuf> {
+    let dir = dirs::picture_dir().or_else(dirs::home_dir)?;
+    let name = format!("{artist} - {album}.jpg").replace(['/', ':'], "_");
+    Some(dir.join(name))
+}
+
+/// Save a track's artwork at the highest available resolution.
+///
+/// Writes the original JPEG to `dest` (or stdout when `None`). Destinations
+/// with another image extension (e.g. `.png`) are re-encoded to match.
+pub fn export_artwork(
+    track_name: &str,
+    artist: &str,
+    album: &str,
+    dest: Option<&Path>,
+) -> Result<()> {
+    let url = artwork_url(track_name, artist, album)
+        .ok_or_else(|| eyre!("No artwork found for {track_name}"))?
+        .replace("300x300bb", EXPORT_SIZE);
+    let bytes = download_bytes(&url).ok_or_else(|| eyre!("Failed to download artwork"))?;
+
+    let Some(dest) = dest else {
+        std::io::stdout().write_all(&bytes)?;
+        return Ok(());
+    };
+
+    let is_jpeg = dest
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_none_or(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"));
+    if is_jpeg {
+        std::fs::write(dest, &bytes)?;
+    } else {
+        image::load_from_memory(&bytes)?.save(dest)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Playlist mosaics
+// ---------------------------------------------------------------------------
+
+/// Side of a generated playlist cover, in pixels.
+const MOSAIC_SIZE: u32 = 300;
+
+/// Covers that make up a playlist mosaic: (track, artist, album) of the
+/// first tracks with distinct covers, at most 4.
+pub fn mosaic_sources(tracks: &[TrackEntry]) -> Vec<(String, String, String)> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut sources = Vec::new();
+    for t in tracks {
+        let key = cover_key(&t.name, &t.artist, &t.album);
+        if keys.contains(&key) {
+            continue;
+        }
+        keys.push(key);
+        sources.push((t.name.clone(), t.artist.clone(), t.album.clone()));
+        if sources.len() == 4 {
+            break;
+        }
+    }
+    sources
+}
+
+/// Key a playlist's mosaic is cached under. It changes with the covers it's
+/// made of, so editing the playlist's first tracks makes a new one.
+pub fn mosaic_key(playlist_id: i32, sources: &[(String, String, String)]) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for (name, artist, album) in sources {
+        cover_key(name, artist, album).hash(&mut hasher);
+    }
+    format!("playlist-{playlist_id}-{:016x}", hasher.finish())
+}
+
+/// ~/Library/Caches/cli-music/artwork
+fn playlist_cover_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("cli-music").join("artwork"))
+}
+
+/// Mosaic cache file: <playlist cover dir>/<key>.jpg
+fn mosaic_path(key: &str) -> Option<PathBuf> {
+    playlist_cover_dir().map(|d| d.join(format!("{key}.jpg")))
+}
+
+/// A playlist cover: the playlist's own artwork when it has some, else its
+/// mosaic (see [`playlist_mosaic`]).
+pub fn playlist_cover(
+    playlist_id: i32,
+    key: &str,
+    sources: &[(String, String, String)],
+    network: bool,
+) -> Option<DynamicImage> {
+    if let Some(img) = own_playlist_artwork(playlist_id) {
+        remove_mosaics(playlist_id, None);
+        return Some(img);
+    }
+    playlist_mosaic(playlist_id, key, sources, network)
+}
+
+/// The artwork set on the playlist in Music, if any. It's exported to a
+/// scratch file, read back and removed.
+fn own_playlist_artwork(playlist_id: i32) -> Option<DynamicImage> {
+    let dir = playlist_cover_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("playlist-{playlist_id}.export"));
+    let exported = crate::library::export_playlist_artwork(playlist_id, &path);
+    let img = match exported {
+        Ok(true) => std::fs::read(&path).ok().and_then(|bytes| image::load_from_memory(&bytes).ok()),
+        Ok(false) => None,
+        Err(e) => {
+            crate::log::write(format!("couldn't read playlist artwork: {e}"));
+            None
+        }
+    };
+    let _ = std::fs::remove_file(&path);
+    img
+}
+
+/// The cached mosaic for `key`, else one made from the covers of `sources`
+/// and cached, replacing the playlist's previous mosaic. Four covers make a
+/// 2×2 grid; with fewer, the first cover stands in alone. Downloads only
+/// when `network` is on.
+fn playlist_mosaic(
+    playlist_id: i32,
+    key: &str,
+    sources: &[(String, String, String)],
+    network: bool,
+) -> Option<DynamicImage> {
+    let path = mosaic_path(key);
+    if let Some(img) = path.as_ref().and_then(|p| image::open(p).ok()) {
+        return Some(img);
+    }
+    if !network {
+        return None;
+    }
+    let covers: Vec<DynamicImage> = sources
+        .iter()
+        .filter_map(|(name, artist, album)| download_image(&artwork_url(name, artist, album)?))
+        .collect();
+    let mosaic = compose_mosaic(&covers)?;
+    if let Some(path) = path {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        remove_mosaics(playlist_id, Some(key));
+        let _ = mosaic.to_rgb8().save(path);
+    }
+    Some(mosaic)
+}
+
+/// Delete a playlist's cached mosaics, except the one for `keep`. Mosaics
+/// are keyed by their covers, so an edited playlist would otherwise leave its
+/// old ones behind.
+fn remove_mosaics(playlist_id: i32, keep: Option<&str>) {
+    let Some(dir) = playlist_cover_dir() else { return };
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let prefix = format!("playlist-{playlist_id}-");
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(stem) = name.to_str().and_then(|n| n.strip_suffix(".jpg")) else { continue };
+        if stem.starts_with(&prefix) && Some(stem) != keep {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn compose_mosaic(covers: &[DynamicImage]) -> Option<DynamicImage> {
+    let first = covers.first()?;
+    if covers.len() < 4 {
+        return Some(first.resize_to_fill(MOSAIC_SIZE, MOSAIC_SIZE, FilterType::Triangle));
+    }
+    let half = MOSAIC_SIZE / 2;
+    let mut canvas = image::RgbImage::new(MOSAIC_SIZE, MOSAIC_SIZE);
+    for (i, cover) in covers.iter().take(4).enumerate() {
+        let tile = cover.resize_to_fill(half, half, FilterType::Triangle).to_rgb8();
+        let (x, y) = ((i as u32 % 2) * half, (i as u32 / 2) * half);
+        image::imageops::replace(&mut canvas, &tile, x as i64, y as i64);
+    }
+    Some(DynamicImage::ImageRgb8(canvas))
+}
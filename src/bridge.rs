@@ -3,21 +3,30 @@ use color_eyre::Result;
 use serde::Deserialize;
 use std::process::Command;
 
+use crate::jxa::{self, Script};
+use crate::library::Opinion;
+use crate::volume::Volume;
+
 // ---------------------------------------------------------------------------
 // Core types
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone)]
 pub struct PlayerStatus {
+    pub track_id: i32,
     pub track_name: String,
     pub artist: String,
     pub album: String,
     pub duration: f64,
     pub position: f64,
     pub state: PlayState,
-    pub volume: i8,
+    pub volume: Volume,
     pub shuffle: bool,
     pub repeat: RepeatMode,
+    pub favorited: bool,
+    pub disliked: bool,
+    /// 0–100, 20 per star.
+    pub rating: u8,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -37,19 +46,69 @@ pub enum RepeatMode {
 impl Default for PlayerStatus {
     fn default() -> Self {
         Self {
+            track_id: 0,
             track_name: String::new(),
             artist: String::new(),
             album: String::new(),
             duration: 0.0,
             position: 0.0,
             state: PlayState::Stopped,
-            volume: 50,
+            volume: Volume::default(),
             shuffle: false,
             repeat: RepeatMode::Off,
+            favorited: false,
+            disliked: false,
+            rating: 0,
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// Access check
+// ---------------------------------------------------------------------------
+
+/// Whether Music can be controlled right now.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    Ready,
+    /// Music.app isn't running (scripting it would launch it behind the
+    /// user's back, so it's left to them).
+    MusicNotRunning,
+    /// The user denied (or hasn't yet granted) Automation permission.
+    NotAuthorized,
+    /// Anything else osascript reported.
+    Unavailable(String),
+}
+
+/// Check that Music is running and accepts Apple Events from us. The first
+/// call may block on macOS's permission prompt.
+pub fn check_access() -> Access {
+    let running = Command::new("pgrep")
+        .args(["-x", "Music"])
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if !running {
+        return Access::MusicNotRunning;
+    }
+    match Script::new("app.playerState()").run() {
+        Ok(_) => Access::Ready,
+        Err(e) if e.is::<jxa::NotAuthorized>() => Access::NotAuthorized,
+        Err(e) => Access::Unavailable(e.to_string()),
+    }
+}
+
+/// Launch Music.app.
+pub fn open_music() {
+    let _ = Command::new("open").args(["-a", "Music"]).output();
+}
+
+/// Open System Settings at Privacy & Security > Automation.
+pub fn open_automation_settings() {
+    let _ = Command::new("open")
+        .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_Automation")
+        .output();
+}
+
 // ---------------------------------------------------------------------------
 // Lightweight JXA polling
 // ---------------------------------------------------------------------------
@@ -63,6 +122,8 @@ struct JxaStatus {
     shuffle: bool,
     repeat: String,
     #[serde(default)]
+    id: i32,
+    #[serde(default)]
     name: String,
     #[serde(default)]
     artist: String,
@@ -70,34 +131,15 @@ struct JxaStatus {
     album: String,
     #[serde(default)]
     duration: f64,
+    #[serde(default)]
+    favorited: bool,
+    #[serde(default)]
+    disliked: bool,
+    #[serde(default)]
+    rating: u8,
 }
 
-const JXA_POLL_SCRIPT: &str = r#"
-(function() {
-    var app = Application('Music');
-    var state = app.playerState();
-    var result = {
-        state:    state,
-        position: 0,
-        volume:   app.soundVolume(),
-        shuffle:  app.shuffleEnabled(),
-        repeat:   app.songRepeat(),
-        name:     '',
-        artist:   '',
-        album:    '',
-        duration: 0
-    };
-    if (state !== 'stopped') {
-        result.position = app.playerPosition();
-        var t = app.currentTrack;
-        result.name     = t.name();
-        result.artist   = t.artist();
-        result.album    = t.album();
-        result.duration = t.duration();
-    }
-    return JSON.stringify(result);
-})()
-"#;
+const POLL_STATUS: &str = include_str!("scripts/poll_status.js");
 
 /// Poll Apple Music player status via a single lightweight JXA invocation.
 ///
@@ -105,20 +147,7 @@ const JXA_POLL_SCRIPT: &str = r#"
 /// fetches playlists, airplay devices, and much more. We only grab the fields
 /// the TUI status bar needs.
 pub fn poll_player_status() -> PlayerStatus {
-    let output = Command::new("osascript")
-        .arg("-l")
-        .arg("JavaScript")
-        .arg("-e")
-        .arg(JXA_POLL_SCRIPT)
-        .output();
-
-    let output = match output {
-        Ok(o) if o.status.success() => o,
-        _ => return PlayerStatus::default(),
-    };
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let raw: JxaStatus = match serde_json::from_str(stdout.trim()) {
+    let raw: JxaStatus = match Script::new(POLL_STATUS).run_json() {
         Ok(v) => v,
         Err(_) => return PlayerStatus::default(),
     };
@@ -136,114 +165,248 @@ pub fn poll_player_status() -> PlayerStatus {
     };
 
     PlayerStatus {
+        track_id: raw.id,
         track_name: raw.name,
         artist: raw.artist,
         album: raw.album,
         duration: raw.duration,
         position: raw.position,
         state,
-        volume: raw.volume,
+        volume: Volume::new(raw.volume.into()),
         shuffle: raw.shuffle,
         repeat,
+        favorited: raw.favorited,
+        disliked: raw.disliked,
+        rating: raw.rating,
     }
 }
 
+// ---------------------------------------------------------------------------
+// Up next
+// ---------------------------------------------------------------------------
+
+/// The track queued after the current one in the current playlist.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpNext {
+    pub name: String,
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+}
+
+const UP_NEXT: &str = include_str!("scripts/up_next.js");
+
+/// Look up the next track in the current playlist.
+///
+/// Returns `None` when nothing is playing, shuffle is on (the order isn't
+/// scriptable), or the current track is the last one without repeat-all.
+pub fn fetch_up_next() -> Option<UpNext> {
+    Script::new(UP_NEXT).run_json().ok().flatten()
+}
+
+// ---------------------------------------------------------------------------
+// Output device
+// ---------------------------------------------------------------------------
+
+const OUTPUT_DEVICES: &str = include_str!("scripts/output_devices.js");
+
+/// Names of the AirPlay devices Music is currently playing through, joined
+/// with ", " (e.g. "Computer" or "Kitchen, Living Room").
+///
+/// Returns `None` if Music can't be queried.
+pub fn fetch_output_device() -> Option<String> {
+    let names: Vec<String> = Script::new(OUTPUT_DEVICES).run_json().ok()?;
+    Some(names.join(", "))
+}
+
 // ---------------------------------------------------------------------------
 // Playback controls
 // ---------------------------------------------------------------------------
 
+const SET_PLAY_MODE: &str = include_str!("scripts/set_play_mode.js");
+const EDIT_CURRENT_TRACK: &str = include_str!("scripts/edit_current_track.js");
+const SEEK: &str = include_str!("scripts/seek.js");
+
+/// Resume playback.
+pub fn play() -> Result<()> {
+    AppleMusic::play().map_err(|e| jxa::error(&format!("{e:?}")))?;
+    Ok(())
+}
+
+/// Pause playback.
+pub fn pause() -> Result<()> {
+    AppleMusic::pause().map_err(|e| jxa::error(&format!("{e:?}")))?;
+    Ok(())
+}
+
 /// Toggle play/pause.
 pub fn toggle_playback() -> Result<()> {
-    AppleMusic::playpause().map_err(|e| color_eyre::eyre::eyre!("{e:?}"))?;
+    AppleMusic::playpause().map_err(|e| jxa::error(&format!("{e:?}")))?;
     Ok(())
 }
 
 /// Skip to the next track.
 pub fn next_track() -> Result<()> {
-    AppleMusic::next_track().map_err(|e| color_eyre::eyre::eyre!("{e:?}"))?;
+    AppleMusic::next_track().map_err(|e| jxa::error(&format!("{e:?}")))?;
     Ok(())
 }
 
 /// Go back to the previous track.
 pub fn previous_track() -> Result<()> {
-    AppleMusic::previous_track().map_err(|e| color_eyre::eyre::eyre!("{e:?}"))?;
+    AppleMusic::previous_track().map_err(|e| jxa::error(&format!("{e:?}")))?;
     Ok(())
 }
 
-/// Set the player volume, clamped to 0..=100.
-pub fn set_volume(vol: i8) -> Result<()> {
-    let clamped = vol.clamp(0, 100);
-    AppleMusic::set_sound_volume(clamped).map_err(|e| color_eyre::eyre::eyre!("{e:?}"))?;
+/// Set the player volume.
+pub fn set_volume(volume: Volume) -> Result<()> {
+    AppleMusic::set_sound_volume(volume.percent() as i8).map_err(|e| jxa::error(&format!("{e:?}")))?;
     Ok(())
 }
 
 /// Cycle play mode: normal → shuffle → repeat all → repeat one → normal.
 /// Uses the already-polled player state to decide what to set next.
-pub fn cycle_play_mode(player: &PlayerStatus) {
-    let script = if player.shuffle {
+pub fn cycle_play_mode(player: &PlayerStatus) -> Result<()> {
+    let (shuffle, repeat) = if player.shuffle {
         // shuffle on → turn off shuffle, turn on repeat all
-        r#"
-            var app = Application('Music');
-            app.shuffleEnabled = false;
-            app.songRepeat = 'all';
-        "#.to_string()
+        (false, "all")
     } else {
         match player.repeat {
-            RepeatMode::All => {
-                // repeat all → repeat one
-                r#"
-                    var app = Application('Music');
-                    app.songRepeat = 'one';
-                "#.to_string()
-            }
-            RepeatMode::One => {
-                // repeat one → normal (everything off)
-                r#"
-                    var app = Application('Music');
-                    app.songRepeat = 'off';
-                "#.to_string()
-            }
-            RepeatMode::Off => {
-                // normal → shuffle
-                r#"
-                    var app = Application('Music');
-                    app.shuffleEnabled = true;
-                "#.to_string()
-            }
+            // repeat all → repeat one
+            RepeatMode::All => (false, "one"),
+            // repeat one → normal (everything off)
+            RepeatMode::One => (false, "off"),
+            // normal → shuffle
+            RepeatMode::Off => (true, "off"),
         }
     };
-    let _ = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", &script])
-        .output();
+    Script::new(SET_PLAY_MODE)
+        .arg("shuffle", shuffle)
+        .arg("repeat", repeat)
+        .run()?;
+    Ok(())
+}
+
+/// Run a JXA script against the playing track (bound to `t`), failing if
+/// nothing is playing or Music reports an error.
+fn run_on_current_track(body: &str) -> Result<()> {
+    Script::new(EDIT_CURRENT_TRACK).code("edit", body).run()?;
+    Ok(())
 }
 
 /// Add the currently playing track to the user's library.
-pub fn add_to_library() {
-    let script = r#"
-        var app = Application('Music');
-        if (app.playerState() !== 'stopped') {
-            var t = app.currentTrack;
-            t.favorited = true;
-        }
-    "#;
-    let _ = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", script])
-        .output();
+pub fn add_to_library() -> Result<()> {
+    run_on_current_track("app.duplicate(t, {to: app.libraryPlaylists[0]});")
+}
+
+/// Set the playing track's star rating (0–100).
+pub fn set_current_rating(rating: u8) -> Result<()> {
+    run_on_current_track(&format!("t.rating = {rating};"))
 }
 
+/// Favorite, dislike or clear the flags on the playing track.
+pub fn set_current_opinion(opinion: Opinion) -> Result<()> {
+    run_on_current_track(opinion.jxa())
+}
 
 /// Seek to a specific position (in seconds) in the current track.
-pub fn seek_to(position: f64) {
-    let script = format!(
-        r#"
-        var Music = Application("Music");
-        if (Music.playerState() !== "stopped") {{
-            Music.playerPosition = {};
-        }}
-        "#,
-        position
+pub fn seek_to(position: f64) -> Result<()> {
+    Script::new(SEEK).arg("position", position).run()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Audio settings
+// ---------------------------------------------------------------------------
+
+/// Music's equalizer and crossfade.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AudioSettings {
+    #[serde(rename = "eqEnabled")]
+    pub eq_enabled: bool,
+    /// Name of the current EQ preset.
+    pub preset: String,
+    /// Every EQ preset, built-in and custom.
+    pub presets: Vec<String>,
+    /// None when Music's settings couldn't be read (usually because the
+    /// terminal lacks Accessibility permission).
+    #[serde(skip)]
+    pub crossfade: Option<Crossfade>,
+}
+
+/// Crossfade isn't part of Music's scripting dictionary, so it's read and
+/// changed through the Playback pane of Music's settings.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Crossfade {
+    pub enabled: bool,
+    pub seconds: u8,
+}
+
+impl Crossfade {
+    /// The range of Music's crossfade slider, in seconds.
+    pub const MIN_SECONDS: u8 = 1;
+    pub const MAX_SECONDS: u8 = 12;
+}
+
+const AUDIO_SETTINGS: &str = include_str!("scripts/audio_settings.js");
+const SET_EQ: &str = include_str!("scripts/set_eq.js");
+const SETTINGS_PANE: &str = include_str!("scripts/settings_pane.js");
+const CROSSFADE: &str = include_str!("scripts/crossfade.js");
+const SET_CROSSFADE: &str = include_str!("scripts/set_crossfade.js");
+
+pub fn fetch_audio_settings() -> Result<AudioSettings> {
+    let mut settings: AudioSettings = Script::new(AUDIO_SETTINGS).run_json()?;
+    settings.crossfade = match Script::new(CROSSFADE).code("helpers", SETTINGS_PANE).run_json() {
+        Ok(crossfade) => Some(crossfade),
+        Err(e) => {
+            crate::log::write(format!("couldn't read crossfade: {e}"));
+            None
+        }
+    };
+    Ok(settings)
+}
+
+/// Turn crossfade on or off, fading over `seconds` when on.
+pub fn set_crossfade(crossfade: Crossfade) -> Result<()> {
+    Script::new(SET_CROSSFADE)
+        .code("helpers", SETTINGS_PANE)
+        .arg("enabled", crossfade.enabled)
+        .arg("seconds", crossfade.seconds)
+        .run()?;
+    Ok(())
+}
+
+/// Turn the EQ on or off and, if `preset` isn't empty, switch to it.
+pub fn set_eq(enabled: bool, preset: &str) -> Result<()> {
+    Script::new(SET_EQ).arg("enabled", enabled).arg("preset", preset).run()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Announcements
+// ---------------------------------------------------------------------------
+
+/// Speak "Now playing <track> by <artist>" with macOS `say`, between tracks:
+/// when `playing`, playback is paused while it speaks and resumed after.
+/// Blocks until speech finishes, so call it from a background thread.
+///
+/// `volume` is 0–100; `voice` is any name from `say -v '?'`.
+pub fn announce_track(track_name: &str, artist: &str, voice: Option<&str>, volume: u8, playing: bool) {
+    // `[[volm x]]` is an embedded speech command understood by `say`; the
+    // names can't be allowed to add their own
+    let plain = |s: &str| s.replace(['[', ']'], "");
+    let text = format!(
+        "[[volm {:.2}]] Now playing {} by {}",
+        volume.min(100) as f32 / 100.0,
+        plain(track_name),
+        plain(artist)
     );
-    let _ = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", &script])
-        .output();
+    let mut cmd = Command::new("say");
+    if let Some(voice) = voice {
+        cmd.args(["-v", voice]);
+    }
+    let paused = playing && pause().is_ok();
+    let _ = cmd.arg(text).output();
+    if paused {
+        let _ = play();
+    }
 }
@@ -0,0 +1,139 @@
+use ratatui::widgets::ListState;
+
+use crate::library::TrackEntry;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A column of the Artists | Albums | Tracks browser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrowserColumn {
+    Artists,
+    Albums,
+    Tracks,
+}
+
+/// Miller-column view of the whole library, like iTunes' column browser.
+/// Picking an artist narrows the albums, and picking an album narrows the
+/// tracks. Row 0 of the artist and album columns is "All".
+#[derive(Debug)]
+pub struct ColumnBrowser {
+    library: Vec<TrackEntry>,
+    pub artists: Vec<String>,
+    /// Albums of the selected artist (every album under "All").
+    pub albums: Vec<String>,
+    pub artist_state: ListState,
+    pub album_state: ListState,
+    pub focus: BrowserColumn,
+}
+
+// ---------------------------------------------------------------------------
+// Browser
+// ---------------------------------------------------------------------------
+
+impl ColumnBrowser {
+    pub fn new(library: Vec<TrackEntry>) -> Self {
+        let artists = sorted_unique(library.iter().map(|t| t.artist.as_str()));
+        let mut browser = Self {
+            library,
+            artists,
+            albums: Vec::new(),
+            artist_state: ListState::default().with_selected(Some(0)),
+            album_state: ListState::default(),
+            focus: BrowserColumn::Artists,
+        };
+        browser.refresh_albums();
+        browser
+    }
+
+    /// The selected artist, None for "All".
+    pub fn selected_artist(&self) -> Option<&str> {
+        let i = self.artist_state.selected()?.checked_sub(1)?;
+        self.artists.get(i).map(String::as_str)
+    }
+
+    /// The selected album, None for "All".
+    pub fn selected_album(&self) -> Option<&str> {
+        let i = self.album_state.selected()?.checked_sub(1)?;
+        self.albums.get(i).map(String::as_str)
+    }
+
+    /// Library tracks under the selected artist and album.
+    pub fn tracks(&self) -> Vec<TrackEntry> {
+        let artist = self.selected_artist();
+        let album = self.selected_album();
+        self.library
+            .iter()
+            .filter(|t| artist.is_none_or(|a| t.artist == a))
+            .filter(|t| album.is_none_or(|a| t.album == a))
+            .cloned()
+            .collect()
+    }
+
+    /// Move the selection in the focused list column by `delta` rows,
+    /// stopping at the ends. Returns whether it changed.
+    pub fn move_by(&mut self, delta: isize) -> bool {
+        let (state, _) = self.focused_list();
+        let current = state.selected().unwrap_or(0);
+        let target = current.saturating_add_signed(delta);
+        self.select(target)
+    }
+
+    /// Select row `index` (clamped) in the focused list column, cascading
+    /// to the columns on its right. Returns whether the selection changed.
+    pub fn select(&mut self, index: usize) -> bool {
+        let (state, len) = self.focused_list();
+        let previous = state.selected().unwrap_or(0);
+        let index = index.min(len.saturating_sub(1));
+        state.select(Some(index));
+        if self.focus == BrowserColumn::Artists && index != previous {
+            self.refresh_albums();
+        }
+        index != previous
+    }
+
+    pub fn focus_right(&mut self) {
+        self.focus = match self.focus {
+            BrowserColumn::Artists => BrowserColumn::Albums,
+            BrowserColumn::Albums | BrowserColumn::Tracks => BrowserColumn::Tracks,
+        };
+    }
+
+    pub fn focus_left(&mut self) {
+        self.focus = match self.focus {
+            BrowserColumn::Artists | BrowserColumn::Albums => BrowserColumn::Artists,
+            BrowserColumn::Tracks => BrowserColumn::Albums,
+        };
+    }
+
+    /// The focused list's state and row count (including "All"). The tracks
+    /// column lives in `App::tracks`, so it falls back to the albums.
+    fn focused_list(&mut self) -> (&mut ListState, usize) {
+        match self.focus {
+            BrowserColumn::Artists => (&mut self.artist_state, self.artists.len() + 1),
+            BrowserColumn::Albums | BrowserColumn::Tracks => {
+                (&mut self.album_state, self.albums.len() + 1)
+            }
+        }
+    }
+
+    fn refresh_albums(&mut self) {
+        let artist = self.selected_artist().map(str::to_string);
+        self.albums = sorted_unique(
+            self.library
+                .iter()
+                .filter(|t| artist.as_ref().is_none_or(|a| &t.artist == a))
+                .map(|t| t.album.as_str()),
+        );
+        self.album_state.select(Some(0));
+    }
+}
+
+/// Distinct non-empty names, sorted case-insensitively.
+fn sorted_unique<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut names: Vec<String> = names.filter(|n| !n.is_empty()).map(str::to_string).collect();
+    names.sort_by_cached_key(|n| (n.to_lowercase(), n.clone()));
+    names.dedup();
+    names
+}
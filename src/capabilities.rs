@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use crate::jxa::{self, Script};
+
+/// Whether an optional feature works on this machine.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Support {
+    /// Not probed (yet), or the probe was inconclusive. Treated as usable.
+    #[default]
+    Unknown,
+    Available,
+    /// With the reason, shown when the user tries the feature.
+    Unavailable(String),
+}
+
+impl Support {
+    pub fn usable(&self) -> bool {
+        !matches!(self, Support::Unavailable(_))
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Support::Unavailable(reason) => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+/// What the app can actually do, probed at startup. Internet access is
+/// probed again while it's off (see `NETWORK_RECHECK`).
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    /// Apple Events to Music (everything depends on this).
+    pub automation: Support,
+    /// Reading the AirPlay output devices.
+    pub airplay: Support,
+    /// Reading lyrics and file locations from tracks (the `music` and
+    /// `local` lyrics providers).
+    pub lyrics: Support,
+    /// Internet access (artwork and the online lyrics providers).
+    pub network: Support,
+}
+
+impl Capabilities {
+    /// Every capability with a display name, for the `:features` popup.
+    pub fn list(&self) -> [(&'static str, &Support); 4] {
+        [
+            ("Automation", &self.automation),
+            ("AirPlay devices", &self.airplay),
+            ("Track lyrics", &self.lyrics),
+            ("Network", &self.network),
+        ]
+    }
+}
+
+const PROBE_LYRICS: &str = include_str!("scripts/probe_lyrics.js");
+
+/// Host the network probe talks to (the artwork search API).
+const PROBE_HOST: &str = "https://itunes.apple.com";
+
+/// How often internet access is probed again while it's off.
+pub const NETWORK_RECHECK: Duration = Duration::from_secs(30);
+
+/// Run every probe. Blocks for a few seconds at most, so call it from a
+/// background thread. With `network` false, internet access is reported
+/// off without probing.
+pub fn probe(network: bool) -> Capabilities {
+    let automation = match Script::new("app.playerState()").run() {
+        Ok(_) => Support::Available,
+        Err(e) if e.is::<jxa::NotAuthorized>() => Support::Unavailable(e.to_string()),
+        Err(e) => Support::Unavailable(format!("Music isn't scriptable: {e}")),
+    };
+    // The other Music probes would fail for the same reason
+    let (airplay, lyrics) = if let Some(reason) = automation.reason() {
+        (
+            Support::Unavailable(reason.to_string()),
+            Support::Unavailable(reason.to_string()),
+        )
+    } else {
+        (probe_airplay(), probe_lyrics())
+    };
+
+    Capabilities {
+        automation,
+        airplay,
+        lyrics,
+        network: if network {
+            probe_network()
+        } else {
+            Support::Unavailable("Off in safe mode".to_string())
+        },
+    }
+}
+
+fn probe_airplay() -> Support {
+    match Script::new("app.currentAirPlayDevices().length").run() {
+        Ok(_) => Support::Available,
+        Err(e) => Support::Unavailable(format!("Music won't list AirPlay devices: {e}")),
+    }
+}
+
+fn probe_lyrics() -> Support {
+    match Script::new(PROBE_LYRICS).run().as_deref() {
+        Ok("ok") => Support::Available,
+        // Nothing to read from
+        Ok(_) => Support::Unknown,
+        Err(e) => Support::Unavailable(format!("Music won't share track lyrics: {e}")),
+    }
+}
+
+pub fn probe_network() -> Support {
+    let reachable = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .and_then(|client| client.head(PROBE_HOST).send());
+    match reachable {
+        Ok(_) => Support::Available,
+        Err(_) => Support::Unavailable("No internet connection".to_string()),
+    }
+}
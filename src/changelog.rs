@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::Popup;
+
+// ---------------------------------------------------------------------------
+// Release notes
+// ---------------------------------------------------------------------------
+
+/// The running version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Notes for one release.
+pub struct Release {
+    pub version: &'static str,
+    pub notes: &'static [&'static str],
+}
+
+/// Release notes, newest first. Add an entry when bumping the version.
+pub const RELEASES: &[Release] = &[Release {
+    version: "0.1.0",
+    notes: &[
+        "Library menu: Recently Added, Recently Played, Forgotten Favorites, History",
+        "Artists | Albums | Tracks column browser (C)",
+        "Filter queries in track search: artist:radiohead year:>2000 duration:<4:00",
+        "Seek between lyric sections with [ and ]",
+        ":queue save copies the play queue into a playlist",
+        "HTTP remote control, event stream and `cli-music events`",
+        "Screensaver after screensaver_minutes without input",
+    ],
+}];
+
+/// Default keybindings as (key, action), diffed against the ones the user
+/// last saw to point out changes after an upgrade. Keep in sync with the
+/// README's Keybindings table.
+pub const KEYMAP: &[(&str, &str)] = &[
+    ("space", "Play / pause, or mark the selected track"),
+    ("Shift+Left/Right", "Previous / next track"),
+    (", / .", "Seek backward / forward 5s"),
+    ("[ / ]", "Previous / next lyric section"),
+    ("j / k", "Navigate list"),
+    ("J / K", "Jump 5 items"),
+    ("gg / G", "Jump to top / bottom"),
+    ("Ctrl+d / Ctrl+u", "Scroll half a page"),
+    ("Enter", "Open playlist / play track"),
+    ("Left / Esc", "Go back"),
+    ("V", "Range selection"),
+    ("x / X", "Play playlist / play shuffled"),
+    ("H", "History view"),
+    ("C", "Column browser"),
+    ("c / r / d", "Create / rename / delete playlist"),
+    ("R", "Refresh the library view"),
+    ("a", "Add to playlist"),
+    ("Delete", "Remove from playlist"),
+    ("D", "DJ mix from the selected track"),
+    ("s / /", "Search / filter"),
+    ("o / O", "Sort column / direction"),
+    ("m", "Cycle shuffle / repeat mode"),
+    ("f", "Save track to library"),
+    ("F / U / !", "Favorite / unfavorite / dislike"),
+    ("i", "Track info"),
+    ("*", "Rate"),
+    ("+ / -", "Volume up / down"),
+    ("M", "Mute / unmute"),
+    ("y", "Toggle lyrics"),
+    ("e", "Edit lyrics (focused lyrics)"),
+    ("w", "Choose artwork"),
+    ("b", "BPM tap"),
+    ("E", "Audio settings (EQ, crossfade)"),
+    ("A", "Toggle announcements"),
+    ("z", "Karaoke mode"),
+    ("n", "Mini-player"),
+    ("Ctrl+h / Ctrl+l", "Resize panels"),
+    ("Z", "Zoom the focused panel"),
+    ("t / T", "New / close library tab"),
+    ("{ / }", "Previous / next tab"),
+    ("Alt+1-9", "Go to tab"),
+    ("Tab", "Switch panels"),
+    (":", "Command prompt"),
+    ("q", "Quit"),
+];
+
+// ---------------------------------------------------------------------------
+// Upgrade check
+// ---------------------------------------------------------------------------
+
+/// What the user saw last: the version and its default keybindings.
+#[derive(Debug, Serialize, Deserialize)]
+struct Seen {
+    version: String,
+    #[serde(default)]
+    keys: BTreeMap<String, String>,
+}
+
+impl Seen {
+    fn current() -> Self {
+        Self {
+            version: VERSION.to_string(),
+            keys: KEYMAP.iter().map(|&(k, a)| (k.to_string(), a.to_string())).collect(),
+        }
+    }
+}
+
+/// On the first launch after a version bump, the popup of what changed
+/// since the version last run. Records the running version either way;
+/// a fresh install shows nothing.
+pub fn check_upgrade() -> Option<Popup> {
+    let previous = load_seen();
+    let current = Seen::current();
+    if previous.as_ref().is_none_or(|p| p.version != current.version) {
+        save_seen(&current);
+    }
+    let previous = previous?;
+    if parse_version(&previous.version) >= parse_version(VERSION) {
+        return None;
+    }
+    Some(popup(Some(&previous)))
+}
+
+/// The `:changelog` popup: every release's notes.
+pub fn full() -> Popup {
+    popup(None)
+}
+
+fn popup(since: Option<&Seen>) -> Popup {
+    let since_version = since.map(|s| parse_version(&s.version));
+    let mut lines = Vec::new();
+    for release in RELEASES {
+        if since_version.as_ref().is_some_and(|v| parse_version(release.version) <= *v) {
+            break;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!("{}:", release.version));
+        lines.extend(release.notes.iter().map(|n| format!("  \u{2022} {n}")));
+    }
+    if let Some(seen) = since {
+        let diff = key_diff(&seen.keys);
+        if !diff.is_empty() {
+            lines.push(String::new());
+            lines.push("Keybinding changes:".to_string());
+            lines.extend(diff);
+        }
+    }
+    if lines.is_empty() {
+        lines.push("No notes for this version.".to_string());
+    }
+    let title = match since {
+        Some(seen) => format!(" What's new since {} ", seen.version),
+        None => format!(" Changelog (v{VERSION}) "),
+    };
+    Popup { title, lines }
+}
+
+/// `+` new, `~` changed and `-` removed default keybindings since `old`.
+/// Empty when `old` wasn't recorded (saved by a version without it).
+fn key_diff(old: &BTreeMap<String, String>) -> Vec<String> {
+    if old.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = Vec::new();
+    for &(key, action) in KEYMAP {
+        match old.get(key) {
+            None => lines.push(format!("  + {key}  {action}")),
+            Some(before) if before != action => {
+                lines.push(format!("  ~ {key}  {action} (was: {before})"));
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, action) in old {
+        if !KEYMAP.iter().any(|&(k, _)| k == key) {
+            lines.push(format!("  - {key}  {action}"));
+        }
+    }
+    lines
+}
+
+/// "1.2.3" as comparable numbers; unparsable parts count as 0.
+fn parse_version(version: &str) -> Vec<u32> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+/// Seen file path: ~/.config/cli-music/seen.json
+fn seen_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("cli-music").join("seen.json"))
+}
+
+fn load_seen() -> Option<Seen> {
+    let data = std::fs::read_to_string(seen_path()?).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn save_seen(seen: &Seen) {
+    let Some(path) = seen_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(seen) {
+        let _ = std::fs::write(path, json);
+    }
+}
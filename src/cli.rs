@@ -0,0 +1,274 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::Result;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use crate::app::{StartView, StartupOptions};
+use crate::bridge::{self, PlayState, PlayerStatus, RepeatMode};
+use crate::volume::Volume;
+use crate::{artwork, command, events, library, ui};
+
+/// A terminal UI for Apple Music. With a subcommand, performs that one
+/// action and exits instead of opening the UI.
+#[derive(Debug, Parser)]
+#[command(name = "cli-music", version)]
+pub struct Cli {
+    /// Open on the playlist list, the whole library sorted by album / artist,
+    /// the playlist Music is playing from, or the last session's view
+    #[arg(long, value_enum)]
+    pub view: Option<ViewArg>,
+    /// Start in mini-player mode
+    #[arg(long)]
+    pub mini: bool,
+    /// Player backend (Apple Music is the only one)
+    #[arg(long, value_parser = ["music"])]
+    pub backend: Option<String>,
+    /// Troubleshoot: ignore config.toml and saved state, stay offline and
+    /// log to ~/.cache/cli-music/cli-music.log
+    #[arg(long)]
+    pub safe_mode: bool,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ViewArg {
+    Playlists,
+    Albums,
+    Artists,
+    Queue,
+    Last,
+}
+
+/// One-shot actions for scripts, shell aliases and tmux bindings.
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Resume playback
+    Play,
+    /// Pause playback
+    Pause,
+    /// Toggle play / pause
+    Toggle,
+    /// Skip to the next track
+    Next,
+    /// Go back to the previous track
+    Prev,
+    /// Print the player state and current track
+    Status {
+        /// Print a JSON object instead of text
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Custom text with placeholders: {state} {title} {artist} {album}
+        /// {position} {duration} {volume} {rating}
+        #[arg(long)]
+        format: Option<String>,
+        /// Keep running and print again whenever the track or play state changes
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Stream player events as newline-delimited JSON until killed: a
+    /// `snapshot`, then `track`, `state`, `volume`, `mode` and `rating`
+    /// changes, each with the full status
+    Events,
+    /// Print the volume, or set it (0–100)
+    Volume {
+        #[arg(value_parser = clap::value_parser!(i8).range(0..=100))]
+        level: Option<i8>,
+    },
+    /// Search the library; prints one tab-separated `title artist album` line per track
+    Search { query: String },
+    /// Save the current track's artwork to PATH (or stdout with `-` or no PATH)
+    Artwork { path: Option<String> },
+}
+
+impl Cli {
+    /// UI options from the flags, or None when no flag was given (so the
+    /// configured start view applies).
+    pub fn startup_options(&self) -> Option<StartupOptions> {
+        if self.view.is_none() && !self.mini && self.backend.is_none() && !self.safe_mode {
+            return None;
+        }
+        Some(StartupOptions {
+            view: self.view.map(|v| match v {
+                ViewArg::Playlists => StartView::Playlists,
+                ViewArg::Albums => StartView::Albums,
+                ViewArg::Artists => StartView::Artists,
+                ViewArg::Queue => StartView::Queue,
+                ViewArg::Last => StartView::Last,
+            }),
+            mini: self.mini,
+            safe_mode: self.safe_mode,
+        })
+    }
+}
+
+/// Perform a one-shot subcommand.
+pub fn run(cmd: CliCommand) -> Result<()> {
+    match cmd {
+        CliCommand::Play => bridge::play(),
+        CliCommand::Pause => bridge::pause(),
+        CliCommand::Toggle => bridge::toggle_playback(),
+        CliCommand::Next => bridge::next_track(),
+        CliCommand::Prev => bridge::previous_track(),
+        CliCommand::Status { json, format, follow } => {
+            let mut out = std::io::stdout().lock();
+            // Like `events`, a closed pipe (e.g. `| head -1`) ends the output quietly
+            let mut print = |status: &PlayerStatus| -> Result<bool> {
+                let line = if json {
+                    serde_json::to_string(&StatusJson::from(status))?
+                } else {
+                    format_status(status, format.as_deref())
+                };
+                Ok(writeln!(out, "{line}").and_then(|()| out.flush()).is_ok())
+            };
+
+            let mut last = bridge::poll_player_status();
+            if !print(&last)? || !follow {
+                return Ok(());
+            }
+            loop {
+                thread::sleep(FOLLOW_INTERVAL);
+                let status = bridge::poll_player_status();
+                if (status.track_id != last.track_id || status.state != last.state) && !print(&status)? {
+                    return Ok(());
+                }
+                last = status;
+            }
+        }
+        CliCommand::Events => {
+            let mut out = std::io::stdout().lock();
+            // A closed pipe (e.g. `| head`) ends the stream quietly
+            let mut emit = |line: String| writeln!(out, "{line}").and_then(|()| out.flush()).is_ok();
+
+            let mut last = bridge::poll_player_status();
+            if !emit(events::message("snapshot", &last)) {
+                return Ok(());
+            }
+            loop {
+                thread::sleep(EVENTS_INTERVAL);
+                let status = bridge::poll_player_status();
+                for event in events::changes(&last, &status) {
+                    if !emit(events::message(event, &status)) {
+                        return Ok(());
+                    }
+                }
+                last = status;
+            }
+        }
+        CliCommand::Volume { level: Some(level) } => bridge::set_volume(Volume::new(level.into())),
+        CliCommand::Volume { level: None } => {
+            println!("{}", bridge::poll_player_status().volume);
+            Ok(())
+        }
+        CliCommand::Search { query } => {
+            for track in library::search_library(&query)? {
+                println!("{}\t{}\t{}", track.name, track.artist, track.album);
+            }
+            Ok(())
+        }
+        CliCommand::Artwork { path } => {
+            let status = bridge::poll_player_status();
+            if status.track_name.is_empty() {
+                color_eyre::eyre::bail!("Nothing is playing");
+            }
+            let dest: Option<PathBuf> = path
+                .filter(|p| p.as_str() != "-")
+                .map(|p| command::expand_path(&p));
+            artwork::export_artwork(&status.track_name, &status.artist, &status.album, dest.as_deref())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Status output
+// ---------------------------------------------------------------------------
+
+/// How often `status --follow` polls the player.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often `events` polls the player (the UI polls at the same rate).
+const EVENTS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `status --json` output, also served as the remote API's `/status`.
+#[derive(Serialize)]
+pub(crate) struct StatusJson<'a> {
+    state: &'static str,
+    title: &'a str,
+    artist: &'a str,
+    album: &'a str,
+    /// Seconds.
+    position: f64,
+    duration: f64,
+    volume: Volume,
+    shuffle: bool,
+    repeat: &'static str,
+    favorited: bool,
+    /// Stars, 0–5.
+    rating: u8,
+}
+
+impl<'a> From<&'a PlayerStatus> for StatusJson<'a> {
+    fn from(s: &'a PlayerStatus) -> Self {
+        Self {
+            state: state_name(&s.state),
+            title: &s.track_name,
+            artist: &s.artist,
+            album: &s.album,
+            position: s.position,
+            duration: s.duration,
+            volume: s.volume,
+            shuffle: s.shuffle,
+            repeat: match s.repeat {
+                RepeatMode::Off => "off",
+                RepeatMode::One => "one",
+                RepeatMode::All => "all",
+            },
+            favorited: s.favorited,
+            rating: s.rating / 20,
+        }
+    }
+}
+
+fn state_name(state: &PlayState) -> &'static str {
+    match state {
+        PlayState::Playing => "playing",
+        PlayState::Paused => "paused",
+        PlayState::Stopped => "stopped",
+    }
+}
+
+/// Render `status` with a `--format` template, or the default one-line
+/// summary. Unknown placeholders are left as they are.
+fn format_status(status: &PlayerStatus, template: Option<&str>) -> String {
+    let state = state_name(&status.state);
+    let Some(template) = template else {
+        if status.track_name.is_empty() {
+            return state.to_string();
+        }
+        return format!(
+            "{state}: {} \u{2013} {} ({}) [{}/{}]",
+            status.artist,
+            status.track_name,
+            status.album,
+            ui::format_time(status.position),
+            ui::format_time(status.duration),
+        );
+    };
+
+    let fields = [
+        ("{state}", state.to_string()),
+        ("{title}", status.track_name.clone()),
+        ("{artist}", status.artist.clone()),
+        ("{album}", status.album.clone()),
+        ("{position}", ui::format_time(status.position)),
+        ("{duration}", ui::format_time(status.duration)),
+        ("{volume}", status.volume.to_string()),
+        ("{rating}", "\u{2605}".repeat((status.rating / 20) as usize)),
+    ];
+    fields
+        .iter()
+        .fold(template.to_string(), |out, (key, value)| out.replace(key, value))
+}
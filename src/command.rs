@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use crate::volume::VolumeChange;
+
+/// A command entered at the `:` prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Session(SessionCommand),
+    /// `:artwork [path]` — save the current artwork (default ~/Pictures)
+    Artwork(Option<PathBuf>),
+    /// `:search <query>` — search titles, artists and albums in the whole library
+    Search(String),
+    /// `:features` — which optional features work here, and why not
+    Features,
+    /// `:queue save [name]` — copy the play queue into a new playlist
+    SaveQueue(Option<String>),
+    /// `:changelog` — release notes
+    Changelog,
+    /// `:volume <0-100|+N|-N>` — set the volume, or step it
+    Volume(VolumeChange),
+}
+
+/// `:session ...` subcommands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionCommand {
+    /// `:session start <name>`
+    Start(String),
+    /// `:session end`
+    End,
+    /// `:session stats [name]` — defaults to the active or latest session
+    Stats(Option<String>),
+    /// `:session play [name]` — replay a session as a playlist
+    Play(Option<String>),
+}
+
+/// Parse a command line (without the leading `:`).
+pub fn parse(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (word, rest) = split_word(input);
+
+    match word {
+        "session" => {
+            let (sub, name) = split_word(rest);
+            let name = (!name.is_empty()).then(|| name.to_string());
+            let cmd = match sub {
+                "start" => SessionCommand::Start(
+                    name.ok_or_else(|| "Usage: session start <name>".to_string())?,
+                ),
+                "end" => SessionCommand::End,
+                "stats" => SessionCommand::Stats(name),
+                "play" => SessionCommand::Play(name),
+                _ => return Err("Usage: session start|end|stats|play".to_string()),
+            };
+            Ok(Command::Session(cmd))
+        }
+        "artwork" => Ok(Command::Artwork(
+            (!rest.is_empty()).then(|| expand_path(rest)),
+        )),
+        "search" if rest.is_empty() => Err("Usage: search <query>".to_string()),
+        "search" => Ok(Command::Search(rest.to_string())),
+        "features" => Ok(Command::Features),
+        "changelog" => Ok(Command::Changelog),
+        "volume" if rest.is_empty() => Err("Usage: volume <0-100|+N|-N>".to_string()),
+        "volume" => VolumeChange::parse(rest).map(Command::Volume),
+        "queue" => match split_word(rest) {
+            ("save", name) => Ok(Command::SaveQueue((!name.is_empty()).then(|| name.to_string()))),
+            _ => Err("Usage: queue save [name]".to_string()),
+        },
+        "" => Err("Empty command".to_string()),
+        other => Err(format!("Unknown command: {other}")),
+    }
+}
+
+/// Split off the first whitespace-delimited word, returning it and the
+/// trimmed remainder.
+fn split_word(s: &str) -> (&str, &str) {
+    match s.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (s, ""),
+    }
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_path(s: &str) -> PathBuf {
+    match (s.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(s),
+    }
+}
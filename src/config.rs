@@ -0,0 +1,240 @@
+use serde::Deserialize;
+use std::io;
+use std::path::PathBuf;
+
+use crate::action::Action;
+use crate::app::StartView;
+use crate::lyrics::Provider;
+
+/// A column in the track table.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Number,
+    Title,
+    Artist,
+    Album,
+    /// Star rating.
+    Rating,
+    Time,
+}
+
+/// An item in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusWidget {
+    /// Play state icon.
+    Transport,
+    /// Shuffle / repeat mode.
+    Mode,
+    /// Volume, or the muted icon.
+    Volume,
+    /// Live level meter (needs `level_meter_device`).
+    Meter,
+    /// Output device (AirPlay), flashing when it changes.
+    Output,
+    /// Local time of day.
+    Clock,
+    /// Elapsed / total time. The bar itself fills as the track plays either way.
+    Progress,
+}
+
+/// What to do with playback when the UI starts or exits.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackPolicy {
+    /// Leave Music playing or paused as it is.
+    #[default]
+    Leave,
+    Resume,
+    Pause,
+}
+
+impl PlaybackPolicy {
+    pub fn action(self) -> Option<Action> {
+        match self {
+            PlaybackPolicy::Leave => None,
+            PlaybackPolicy::Resume => Some(Action::Play),
+            PlaybackPolicy::Pause => Some(Action::Pause),
+        }
+    }
+}
+
+/// User settings from `~/.config/cli-music/config.toml`.
+///
+/// Every field is optional; anything missing falls back to its default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Track table columns, in display order.
+    pub columns: Vec<Column>,
+    /// Status bar widgets, left-aligned and right-aligned, in display order.
+    pub status_left: Vec<StatusWidget>,
+    pub status_right: Vec<StatusWidget>,
+    /// Lyrics providers, tried in order until one has lyrics.
+    pub lyrics_providers: Vec<Provider>,
+    /// API token for the Genius lyrics provider.
+    pub genius_token: Option<String>,
+    /// Draw blurred artwork behind karaoke lyrics.
+    pub karaoke_backdrop: bool,
+    /// Draw blurred artwork behind the Now Playing panel.
+    pub now_playing_backdrop: bool,
+    /// Minutes without input, while music plays, before the screensaver
+    /// starts. Off when unset.
+    pub screensaver_minutes: Option<u64>,
+    /// AVFoundation audio input to meter (e.g. a "BlackHole 2ch" loopback
+    /// device mirroring the output). The level meter is off when unset.
+    pub level_meter_device: Option<String>,
+    /// Speak "Now playing ..." on every track change.
+    pub announce: bool,
+    /// `say` voice for announcements (system default when unset).
+    pub announce_voice: Option<String>,
+    /// View to open at launch (`--view` overrides it).
+    pub start_view: StartView,
+    /// Playback when the UI starts.
+    pub on_start: PlaybackPolicy,
+    /// Playback when the UI exits.
+    pub on_exit: PlaybackPolicy,
+    /// Width of the Now Playing panel in percent; unset picks one by
+    /// terminal width. Ctrl-h / Ctrl-l adjust it and save it here.
+    pub split_percent: Option<u16>,
+    /// Announcement volume, 0–100.
+    pub announce_volume: u8,
+    /// Percentage points +/- change the volume by (Shift: four times that).
+    pub volume_step: u8,
+    /// Load pinned, most used and recently opened playlists in the
+    /// background after startup so opening them is instant.
+    pub prefetch: bool,
+    /// Playlists to prefetch, in addition to the recently opened ones.
+    pub pinned_playlists: Vec<String>,
+    /// How many of the most opened playlists to prefetch.
+    pub prefetch_top: usize,
+    /// Minutes after which a cached track list is refreshed in the
+    /// background when opened (0 keeps it for the whole run).
+    pub cache_ttl_minutes: u64,
+    /// Playlists with at least this many tracks show a preview of the
+    /// shuffled order before X plays them (0 turns the preview off).
+    pub shuffle_preview_min: usize,
+    /// Months since the last play after which a favorite counts as forgotten.
+    pub forgotten_months: u32,
+    /// Address for the HTTP remote-control API (e.g. "0.0.0.0:8723"). The
+    /// server is off when unset.
+    pub remote_listen: Option<String>,
+    /// Token remote clients must send (as `Authorization: Bearer`). Required
+    /// unless `remote_listen` is a loopback address.
+    pub remote_token: Option<String>,
+    /// Address for the WebSocket event stream (e.g. "127.0.0.1:8724"); uses
+    /// `remote_token` when set, which is required unless it's a loopback
+    /// address.
+    pub events_listen: Option<String>,
+    /// Unix socket path for the newline-delimited JSON event stream.
+    pub events_socket: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                Column::Number,
+                Column::Title,
+                Column::Artist,
+                Column::Album,
+                Column::Rating,
+                Column::Time,
+            ],
+            status_left: vec![
+                StatusWidget::Transport,
+                StatusWidget::Mode,
+                StatusWidget::Volume,
+                StatusWidget::Meter,
+                StatusWidget::Output,
+            ],
+            status_right: vec![StatusWidget::Progress],
+            lyrics_providers: vec![
+                Provider::Local,
+                Provider::Music,
+                Provider::Lrclib,
+                Provider::Genius,
+            ],
+            genius_token: None,
+            karaoke_backdrop: true,
+            now_playing_backdrop: false,
+            screensaver_minutes: None,
+            level_meter_device: None,
+            announce: false,
+            announce_voice: None,
+            announce_volume: 70,
+            volume_step: 5,
+            start_view: StartView::Last,
+            on_start: PlaybackPolicy::Leave,
+            on_exit: PlaybackPolicy::Leave,
+            split_percent: None,
+            prefetch: false,
+            pinned_playlists: Vec::new(),
+            prefetch_top: 5,
+            cache_ttl_minutes: 30,
+            shuffle_preview_min: 100,
+            forgotten_months: 6,
+            remote_listen: None,
+            remote_token: None,
+            events_listen: None,
+            events_socket: None,
+        }
+    }
+}
+
+impl Config {
+    /// Config file path: ~/.config/cli-music/config.toml
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("cli-music").join("config.toml"))
+    }
+
+    /// Load from disk. Defaults if the file doesn't exist; a file that
+    /// can't be parsed is an error (with toml's line and reason) so the
+    /// caller can say why its settings were ignored.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Ok(Self::default());
+        };
+        let mut config: Self = toml::from_str(&contents).map_err(|e| {
+            // "TOML parse error at line 3, column 7", then a source excerpt
+            let location = e.to_string().lines().next().unwrap_or_default().to_string();
+            format!("{location}: {}", e.message())
+        })?;
+        if config.columns.is_empty() {
+            config.columns = Self::default().columns;
+        }
+        Ok(config)
+    }
+
+    /// Set one top-level `key = value` in the config file, keeping the rest
+    /// of it (comments included) as is.
+    pub fn save_value(key: &str, value: &str) -> io::Result<()> {
+        let path = Self::path().ok_or_else(|| io::Error::other("No config directory"))?;
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let setting = format!("{key} = {value}");
+        let mut found = false;
+        let mut lines: Vec<String> = contents
+            .lines()
+            .map(|line| {
+                let is_key = line.split_once('=').is_some_and(|(k, _)| k.trim() == key);
+                if is_key && !found {
+                    found = true;
+                    setting.clone()
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        if !found {
+            lines.push(setting);
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, lines.join("\n") + "\n")
+    }
+}
@@ -0,0 +1,195 @@
+use image::DynamicImage;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Worker threads downloading and decoding artwork.
+const WORKERS: usize = 3;
+
+/// Decoded images kept for reuse, in bytes of pixel data (~100 covers at
+/// 300x300 RGB).
+const CACHE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Which queued job a worker picks next: the highest priority, oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Covers likely needed soon, e.g. the up-next track's.
+    Prefetch,
+    /// Images on screen, e.g. the artwork chooser's thumbnails.
+    Visible,
+    /// The playing track's cover.
+    NowPlaying,
+}
+
+type Load = Box<dyn FnOnce() -> Option<DynamicImage> + Send>;
+type Done = Box<dyn FnOnce(Option<DynamicImage>) + Send>;
+
+struct Job {
+    key: String,
+    priority: Priority,
+    load: Load,
+    waiters: Vec<Done>,
+}
+
+#[derive(Default)]
+struct State {
+    queue: Vec<Job>,
+    /// Waiters of the jobs being worked on, by key.
+    running: HashMap<String, Vec<Done>>,
+    /// Decoded images, least recently used first.
+    cache: Vec<(String, DynamicImage)>,
+    cache_bytes: usize,
+}
+
+/// A small pool of workers that load images by priority, share work between
+/// requests for the same key and keep a bounded cache of the results.
+pub struct DecodePool {
+    state: Arc<(Mutex<State>, Condvar)>,
+}
+
+/// The shared pool, started on first use.
+pub fn pool() -> &'static DecodePool {
+    static POOL: OnceLock<DecodePool> = OnceLock::new();
+    POOL.get_or_init(DecodePool::new)
+}
+
+// ---------------------------------------------------------------------------
+// Pool
+// ---------------------------------------------------------------------------
+
+impl DecodePool {
+    fn new() -> Self {
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        for _ in 0..WORKERS {
+            let state = state.clone();
+            thread::spawn(move || work(&state));
+        }
+        Self { state }
+    }
+
+    /// Get the image for `key`, running `load` on a worker unless it's
+    /// cached or already on its way. `done` gets the result; it runs on the
+    /// calling thread for a cache hit, and never if the job is cancelled.
+    pub fn request(
+        &self,
+        key: impl Into<String>,
+        priority: Priority,
+        load: impl FnOnce() -> Option<DynamicImage> + Send + 'static,
+        done: impl FnOnce(Option<DynamicImage>) + Send + 'static,
+    ) {
+        let key = key.into();
+        let (lock, ready) = &*self.state;
+        let Ok(mut state) = lock.lock() else { return };
+        if let Some(img) = state.cached(&key) {
+            drop(state);
+            done(Some(img));
+            return;
+        }
+        if let Some(waiters) = state.running.get_mut(&key) {
+            waiters.push(Box::new(done));
+            return;
+        }
+        if let Some(job) = state.queue.iter_mut().find(|j| j.key == key) {
+            job.priority = job.priority.max(priority);
+            job.waiters.push(Box::new(done));
+            return;
+        }
+        state.queue.push(Job {
+            key,
+            priority,
+            load: Box::new(load),
+            waiters: vec![Box::new(done)],
+        });
+        ready.notify_one();
+    }
+
+    /// Drop queued jobs of `priority` that haven't started, e.g. thumbnails
+    /// of a closed chooser or the cover of a track that's no longer playing.
+    pub fn cancel(&self, priority: Priority) {
+        if let Ok(mut state) = self.state.0.lock() {
+            state.queue.retain(|j| j.priority != priority);
+        }
+    }
+
+    /// Forget the cached image for `key`, so the next request loads it again.
+    pub fn forget(&self, key: &str) {
+        if let Ok(mut state) = self.state.0.lock() {
+            state.uncache(key);
+        }
+    }
+}
+
+impl State {
+    /// A cached image, marked as most recently used.
+    fn cached(&mut self, key: &str) -> Option<DynamicImage> {
+        let i = self.cache.iter().position(|(k, _)| k == key)?;
+        let entry = self.cache.remove(i);
+        let img = entry.1.clone();
+        self.cache.push(entry);
+        Some(img)
+    }
+
+    /// Cache an image, evicting the least recently used ones over budget.
+    fn store(&mut self, key: String, img: DynamicImage) {
+        let size = img.as_bytes().len();
+        if size > CACHE_BYTES {
+            return;
+        }
+        self.uncache(&key);
+        self.cache_bytes += size;
+        self.cache.push((key, img));
+        while self.cache_bytes > CACHE_BYTES {
+            let (_, old) = self.cache.remove(0);
+            self.cache_bytes -= old.as_bytes().len();
+        }
+    }
+
+    fn uncache(&mut self, key: &str) {
+        if let Some(i) = self.cache.iter().position(|(k, _)| k == key) {
+            let (_, img) = self.cache.remove(i);
+            self.cache_bytes -= img.as_bytes().len();
+        }
+    }
+
+    /// Take the next job: highest priority, oldest among equals.
+    fn next_job(&mut self) -> Option<Job> {
+        let best = self.queue.iter().map(|j| j.priority).max()?;
+        let i = self.queue.iter().position(|j| j.priority == best)?;
+        Some(self.queue.remove(i))
+    }
+}
+
+fn work(state: &(Mutex<State>, Condvar)) {
+    let (lock, ready) = state;
+    loop {
+        let job = {
+            let Ok(mut state) = lock.lock() else { return };
+            loop {
+                if let Some(job) = state.next_job() {
+                    state.running.insert(job.key.clone(), job.waiters);
+                    break (job.key, job.load);
+                }
+                state = match ready.wait(state) {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+            }
+        };
+        let (key, load) = job;
+        let img = load();
+        let waiters = {
+            let Ok(mut state) = lock.lock() else { return };
+            if let Some(ref img) = img {
+                state.store(key.clone(), img.clone());
+            }
+            state.running.remove(&key).unwrap_or_default()
+        };
+        for done in waiters {
+            done(img.clone());
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use color_eyre::{eyre::eyre, Result};
+use serde_json::json;
+use std::io::{BufWriter, Write};
+use std::net::TcpListener;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::{HeaderValue, StatusCode};
+
+use crate::bridge::PlayerStatus;
+use crate::cli::StatusJson;
+use crate::remote;
+
+/// Pushes player changes to connected integrations (overlays, home
+/// automation) as one JSON object per message:
+/// `{"event": "track", "status": {...}}`, with `status` shaped like
+/// `cli-music status --json`.
+#[derive(Default)]
+pub struct Hub {
+    subscribers: Mutex<Vec<mpsc::Sender<Arc<str>>>>,
+    /// Last published status, sent to clients as they connect.
+    snapshot: Mutex<Option<Arc<str>>>,
+}
+
+impl Hub {
+    /// Publish what changed between two statuses, if anything did.
+    pub fn publish_changes(&self, old: &PlayerStatus, new: &PlayerStatus) {
+        *self.snapshot.lock().unwrap() = Some(message("snapshot", new).into());
+
+        for event in changes(old, new) {
+            let message: Arc<str> = message(event, new).into();
+            self.subscribers
+                .lock()
+                .unwrap()
+                .retain(|tx| tx.send(message.clone()).is_ok());
+        }
+    }
+
+    /// A stream of messages, starting with the current snapshot.
+    fn subscribe(&self) -> mpsc::Receiver<Arc<str>> {
+        let (tx, rx) = mpsc::channel();
+        if let Some(snapshot) = self.snapshot.lock().unwrap().clone() {
+            let _ = tx.send(snapshot);
+        }
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+}
+
+/// One event as a JSON line (without the newline).
+pub fn message(event: &str, status: &PlayerStatus) -> String {
+    json!({ "event": event, "status": StatusJson::from(status) }).to_string()
+}
+
+/// Event names for what differs between two statuses.
+pub fn changes(old: &PlayerStatus, new: &PlayerStatus) -> Vec<&'static str> {
+    let mut events = Vec::new();
+    if old.track_id != new.track_id || old.track_name != new.track_name {
+        events.push("track");
+    }
+    if old.state != new.state {
+        events.push("state");
+    }
+    if old.volume != new.volume {
+        events.push("volume");
+    }
+    if old.shuffle != new.shuffle || old.repeat != new.repeat {
+        events.push("mode");
+    }
+    if old.favorited != new.favorited || old.rating != new.rating {
+        events.push("rating");
+    }
+    events
+}
+
+// ---------------------------------------------------------------------------
+// Transports
+// ---------------------------------------------------------------------------
+
+/// Serve the stream over WebSocket on `addr`. With a `token`, clients must
+/// send it as `Authorization: Bearer <token>` or, from a browser (which
+/// can't set headers on a WebSocket), as a `Sec-WebSocket-Protocol`. Without
+/// one, only loopback addresses are served, to clients that name a loopback
+/// host and aren't a web page from elsewhere.
+pub fn serve_websocket(addr: &str, token: Option<String>, hub: Arc<Hub>) -> Result<()> {
+    let token = token.filter(|t| !t.is_empty());
+    if token.is_none() && !remote::is_loopback(addr)? {
+        return Err(eyre!("{addr} is reachable from other machines; set remote_token"));
+    }
+    let listener = TcpListener::bind(addr).map_err(|e| eyre!("Can't listen on {addr}: {e}"))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let hub = hub.clone();
+            let token = token.clone();
+            thread::spawn(move || {
+                // tungstenite's callback signature, not ours
+                #[allow(clippy::result_large_err)]
+                let callback = |request: &Request, mut response: Response| {
+                    match authorize(request, token.as_deref()) {
+                        Some(protocol) => {
+                            if let Some(protocol) = protocol {
+                                response.headers_mut().insert("Sec-WebSocket-Protocol", protocol);
+                            }
+                            Ok(response)
+                        }
+                        None => {
+                            let mut denied = ErrorResponse::new(None);
+                            *denied.status_mut() = StatusCode::UNAUTHORIZED;
+                            Err(denied)
+                        }
+                    }
+                };
+                let Ok(mut socket) = tungstenite::accept_hdr(stream, callback) else {
+                    return;
+                };
+                for message in hub.subscribe() {
+                    if socket.send(tungstenite::Message::text(message.to_string())).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Whether a WebSocket handshake may connect: None to refuse, else the
+/// subprotocol to answer with (the token, when it was sent as one; browsers
+/// drop the connection unless it's echoed).
+fn authorize(request: &Request, token: Option<&str>) -> Option<Option<HeaderValue>> {
+    let header = |name: &str| request.headers().get(name).and_then(|v| v.to_str().ok());
+    let Some(token) = token else {
+        // Browsers send an Origin; only pages served from this machine pass
+        let origin_ok = header("Origin").is_none_or(|origin| {
+            origin.split_once("://").is_some_and(|(_, host)| remote::loopback_host(host))
+        });
+        return (origin_ok && header("Host").is_some_and(remote::loopback_host)).then_some(None);
+    };
+    if header("Authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|sent| remote::same_token(sent, token))
+    {
+        return Some(None);
+    }
+    let protocol = header("Sec-WebSocket-Protocol")?
+        .split(',')
+        .map(str::trim)
+        .find(|sent| remote::same_token(sent, token))?;
+    Some(HeaderValue::from_str(protocol).ok())
+}
+
+/// The event socket's file; removed again when dropped.
+pub struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Serve the stream as newline-delimited JSON on a Unix socket at `path`
+/// (e.g. for `socat - UNIX-CONNECT:<path>`). A stale socket from an earlier
+/// run is replaced, but anything else at `path` is left alone. Keep the
+/// returned file until exit.
+pub fn serve_socket(path: &Path, hub: Arc<Hub>) -> Result<SocketFile> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => return Err(eyre!("{} exists and isn't a socket", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(eyre!("Can't check {}: {e}", path.display())),
+    }
+    let listener =
+        UnixListener::bind(path).map_err(|e| eyre!("Can't listen on {}: {e}", path.display()))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let hub = hub.clone();
+            thread::spawn(move || {
+                let mut out = BufWriter::new(stream);
+                for message in hub.subscribe() {
+                    if writeln!(out, "{message}").and_then(|()| out.flush()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    Ok(SocketFile(path.to_path_buf()))
+}
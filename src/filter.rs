@@ -0,0 +1,345 @@
+use crate::library::TrackEntry;
+use crate::mix::Camelot;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A parsed track filter, e.g. `artist:radiohead year:>2000 duration:<4:00`.
+///
+/// Terms separated by spaces must all match; `or` separates alternatives.
+/// A bare word matches the title, artist or album; `-term` negates a term;
+/// values with spaces are quoted (`artist:"pink floyd"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// Alternatives, each a list of terms that must all match.
+    groups: Vec<Vec<Term>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    test: Test,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Test {
+    /// Title, artist or album contains the (lowercased) text.
+    Anywhere(String),
+    Text { field: TextField, exact: bool, value: String },
+    Number { field: NumberField, cmp: Cmp },
+    /// `added:` against the ISO date the track was added.
+    Added(Cmp<String>),
+    /// `key:` as a Camelot code or key name (`8A`, `F#m`).
+    Key(Camelot),
+    Is(Flag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextField {
+    Title,
+    Artist,
+    Album,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberField {
+    Year,
+    /// Seconds.
+    Duration,
+    Plays,
+    Bpm,
+    /// Stars, 0–5.
+    Rating,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flag {
+    Favorite,
+    Disliked,
+    Rated,
+}
+
+/// A comparison against a value: `5`, `>5`, `<=5` or `3..5` (inclusive).
+#[derive(Debug, Clone, PartialEq)]
+enum Cmp<T = f64> {
+    Eq(T),
+    Lt(T),
+    Le(T),
+    Gt(T),
+    Ge(T),
+    Range(T, T),
+}
+
+impl<T: PartialOrd> Cmp<T> {
+    fn test(&self, x: &T) -> bool {
+        match self {
+            Cmp::Eq(v) => x == v,
+            Cmp::Lt(v) => x < v,
+            Cmp::Le(v) => x <= v,
+            Cmp::Gt(v) => x > v,
+            Cmp::Ge(v) => x >= v,
+            Cmp::Range(lo, hi) => lo <= x && x <= hi,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Matching
+// ---------------------------------------------------------------------------
+
+impl Filter {
+    pub fn matches(&self, track: &TrackEntry) -> bool {
+        self.groups
+            .iter()
+            .any(|terms| terms.iter().all(|term| term.test.matches(track) != term.negated))
+    }
+}
+
+impl Test {
+    fn matches(&self, t: &TrackEntry) -> bool {
+        match self {
+            Test::Anywhere(text) => [&t.name, &t.artist, &t.album]
+                .iter()
+                .any(|field| field.to_lowercase().contains(text)),
+            Test::Text { field, exact, value } => {
+                let haystack = match field {
+                    TextField::Title => t.name.to_lowercase(),
+                    TextField::Artist => t.artist.to_lowercase(),
+                    TextField::Album => t.album.to_lowercase(),
+                };
+                if *exact {
+                    haystack == *value
+                } else {
+                    haystack.contains(value)
+                }
+            }
+            Test::Number { field, cmp } => {
+                let x = match field {
+                    // Unknown years and BPMs (0) never match a comparison
+                    NumberField::Year if t.year == 0 => return false,
+                    NumberField::Bpm if t.bpm == 0 => return false,
+                    NumberField::Year => t.year as f64,
+                    NumberField::Duration => t.duration.round(),
+                    NumberField::Plays => t.play_count as f64,
+                    NumberField::Bpm => t.bpm as f64,
+                    NumberField::Rating => (t.rating / 20) as f64,
+                };
+                cmp.test(&x)
+            }
+            Test::Added(cmp) => {
+                let date = t.date_added.get(..10).unwrap_or("");
+                !date.is_empty() && test_date(cmp, date)
+            }
+            Test::Key(key) => t.key == Some(*key),
+            Test::Is(Flag::Favorite) => t.favorited,
+            Test::Is(Flag::Disliked) => t.disliked,
+            Test::Is(Flag::Rated) => t.rating > 0,
+        }
+    }
+}
+
+/// Compare an ISO date with `cmp`, cut to each value's precision: `2020`
+/// is the whole year, so `=2020` matches any day in it and `>2020` starts
+/// in 2021. ISO dates order as strings.
+fn test_date(cmp: &Cmp<String>, date: &str) -> bool {
+    let cut = |value: &str| date.get(..value.len()).unwrap_or(date).to_string();
+    match cmp {
+        Cmp::Range(lo, hi) => *lo <= cut(lo) && cut(hi) <= *hi,
+        Cmp::Eq(v) | Cmp::Lt(v) | Cmp::Le(v) | Cmp::Gt(v) | Cmp::Ge(v) => cmp.test(&cut(v)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// Parse a filter expression. An empty query matches everything.
+pub fn parse(input: &str) -> Result<Filter, String> {
+    let mut groups = vec![Vec::new()];
+    for word in split_words(input)? {
+        if word.eq_ignore_ascii_case("or") {
+            groups.push(Vec::new());
+            continue;
+        }
+        let term = parse_term(&word)?;
+        groups.last_mut().expect("groups is never empty").push(term);
+    }
+    groups.retain(|g| !g.is_empty());
+    if groups.is_empty() {
+        groups.push(Vec::new());
+    }
+    Ok(Filter { groups })
+}
+
+/// Split on whitespace, keeping double-quoted runs together (quotes removed).
+fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unclosed quote".to_string());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn parse_term(word: &str) -> Result<Term, String> {
+    let (negated, word) = match word.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, word),
+    };
+    let test = match word.split_once(':') {
+        None => Test::Anywhere(word.to_lowercase()),
+        Some((field, value)) => parse_field(&field.to_lowercase(), value)?,
+    };
+    Ok(Term { negated, test })
+}
+
+fn parse_field(field: &str, value: &str) -> Result<Test, String> {
+    if value.is_empty() {
+        return Err(format!("{field}: needs a value"));
+    }
+    let text = |field| {
+        let (exact, value) = match value.strip_prefix('=') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        Test::Text { field, exact, value: value.to_lowercase() }
+    };
+    let number = |field, parse: fn(&str) -> Option<f64>| -> Result<Test, String> {
+        let cmp = parse_cmp(value, parse).ok_or_else(|| format!("Bad value for {field:?}: {value}"))?;
+        Ok(Test::Number { field, cmp })
+    };
+    Ok(match field {
+        "title" | "name" => text(TextField::Title),
+        "artist" => text(TextField::Artist),
+        "album" => text(TextField::Album),
+        "key" => Test::Key(Camelot::parse(value).ok_or_else(|| format!("Unknown key: {value}"))?),
+        "year" => number(NumberField::Year, parse_number)?,
+        "duration" | "time" | "length" => number(NumberField::Duration, parse_duration)?,
+        "plays" => number(NumberField::Plays, parse_number)?,
+        "bpm" => number(NumberField::Bpm, parse_number)?,
+        "rating" | "stars" => number(NumberField::Rating, parse_number)?,
+        "added" => {
+            let date = |s: &str| is_date(s).then(|| s.to_string());
+            Test::Added(parse_cmp(value, date).ok_or_else(|| format!("Bad date: {value} (use YYYY-MM-DD)"))?)
+        }
+        "is" => Test::Is(match value.to_lowercase().as_str() {
+            "fav" | "favorite" | "favourite" | "loved" => Flag::Favorite,
+            "disliked" => Flag::Disliked,
+            "rated" => Flag::Rated,
+            other => return Err(format!("Unknown is:{other} (favorite, disliked, rated)")),
+        }),
+        other => return Err(format!("Unknown field: {other}")),
+    })
+}
+
+/// Parse `5`, `>5`, `>=5`, `<5`, `<=5`, `=5` or `3..5` with `parse` for values.
+fn parse_cmp<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Cmp<T>> {
+    if let Some((lo, hi)) = value.split_once("..") {
+        return Some(Cmp::Range(parse(lo)?, parse(hi)?));
+    }
+    // Two-character operators first so `>=` isn't read as `>`
+    if let Some(rest) = value.strip_prefix(">=") {
+        parse(rest).map(Cmp::Ge)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        parse(rest).map(Cmp::Le)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        parse(rest).map(Cmp::Gt)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        parse(rest).map(Cmp::Lt)
+    } else {
+        parse(value.strip_prefix('=').unwrap_or(value)).map(Cmp::Eq)
+    }
+}
+
+fn parse_number(s: &str) -> Option<f64> {
+    s.parse().ok()
+}
+
+/// `4:00`, `1:02:03` or plain seconds.
+fn parse_duration(s: &str) -> Option<f64> {
+    s.split(':').try_fold(0.0, |total, part| Some(total * 60.0 + part.parse::<f64>().ok()?))
+}
+
+/// Whether `s` is YYYY-MM-DD, YYYY-MM or YYYY.
+fn is_date(s: &str) -> bool {
+    matches!(s.len(), 4 | 7 | 10)
+        && s.char_indices().all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn added(date: &str) -> TrackEntry {
+        TrackEntry { date_added: format!("{date}T12:00:00Z"), ..TrackEntry::default() }
+    }
+
+    fn matches(query: &str, date: &str) -> bool {
+        parse(query).unwrap().matches(&added(date))
+    }
+
+    #[test]
+    fn added_year() {
+        assert!(matches("added:2020", "2020-05-01"));
+        assert!(!matches("added:2020", "2021-01-01"));
+        assert!(matches("added:>2020", "2021-01-01"));
+        assert!(!matches("added:>2020", "2020-05-01"));
+        assert!(matches("added:>=2020", "2020-01-01"));
+        assert!(!matches("added:>=2020", "2019-12-31"));
+        assert!(matches("added:<2020", "2019-12-31"));
+        assert!(!matches("added:<2020", "2020-01-01"));
+        assert!(matches("added:<=2020", "2020-12-31"));
+        assert!(!matches("added:<=2020", "2021-01-01"));
+        assert!(matches("added:2019..2020", "2020-12-31"));
+        assert!(!matches("added:2019..2020", "2021-01-01"));
+    }
+
+    #[test]
+    fn added_month() {
+        assert!(matches("added:2020-05", "2020-05-31"));
+        assert!(!matches("added:2020-05", "2020-06-01"));
+        assert!(matches("added:>2020-05", "2020-06-01"));
+        assert!(!matches("added:>2020-05", "2020-05-31"));
+        assert!(matches("added:>=2020-05", "2020-05-01"));
+        assert!(matches("added:<2020-05", "2020-04-30"));
+        assert!(!matches("added:<2020-05", "2020-05-01"));
+        assert!(matches("added:<=2020-05", "2020-05-31"));
+        assert!(matches("added:2020-04..2020-05", "2020-05-15"));
+    }
+
+    #[test]
+    fn added_full_date() {
+        assert!(matches("added:2020-05-01", "2020-05-01"));
+        assert!(!matches("added:2020-05-01", "2020-05-02"));
+        assert!(matches("added:>2020-05-01", "2020-05-02"));
+        assert!(!matches("added:>2020-05-01", "2020-05-01"));
+        assert!(matches("added:>=2020-05-01", "2020-05-01"));
+        assert!(matches("added:<2020-05-01", "2020-04-30"));
+        assert!(matches("added:<=2020-05-01", "2020-05-01"));
+        assert!(!matches("added:<=2020-05-01", "2020-05-02"));
+        assert!(matches("added:2020..2020-05-01", "2020-05-01"));
+    }
+
+    #[test]
+    fn added_needs_a_date() {
+        assert!(parse("added:2020-5").is_err());
+        assert!(parse("added:yesterday").is_err());
+        assert!(!parse("added:2020").unwrap().matches(&TrackEntry::default()));
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One track that started playing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix timestamp (seconds) when the track started.
+    pub played_at: u64,
+    pub id: i32,
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: f64,
+    /// Id of the listening session that was active, if any.
+    #[serde(default)]
+    pub session: Option<u64>,
+}
+
+/// A named listening session ("Friday coding") grouping history entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Unix timestamp the session started at; doubles as its id. A session
+    /// started within the same second as the last one gets the next second,
+    /// so ids stay unique.
+    pub id: u64,
+    pub name: String,
+    pub ended_at: Option<u64>,
+}
+
+/// Summary of what was heard during a session.
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub tracks: usize,
+    pub unique_tracks: usize,
+    pub listened_secs: f64,
+    pub top_artists: Vec<(String, usize)>,
+}
+
+/// Play history plus the sessions it's grouped into.
+#[derive(Debug, Default)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+    pub sessions: Vec<Session>,
+    pub active_session: Option<u64>,
+}
+
+// ---------------------------------------------------------------------------
+// History
+// ---------------------------------------------------------------------------
+
+impl History {
+    /// Load history and sessions from disk. Missing or corrupt files yield
+    /// an empty history; unparseable lines are skipped.
+    pub fn load() -> Self {
+        let entries = history_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|data| {
+                data.lines()
+                    .filter_map(|l| serde_json::from_str(l).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let sessions: Vec<Session> = sessions_path()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            sessions,
+            active_session: None,
+        }
+    }
+
+    /// Record a track that just started playing, tagging it with the active
+    /// session. Appends to the history file; errors are ignored.
+    pub fn record(&mut self, id: i32, name: &str, artist: &str, album: &str, duration: f64) {
+        let entry = HistoryEntry {
+            played_at: now(),
+            id,
+            name: name.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            duration,
+            session: self.active_session,
+        };
+        append_line(&entry);
+        self.entries.push(entry);
+    }
+
+    /// Start a new named session, ending any active one.
+    pub fn start_session(&mut self, name: &str) {
+        self.end_session();
+        let last = self.sessions.iter().map(|s| s.id).max();
+        let id = last.map_or(now(), |last| now().max(last + 1));
+        self.sessions.push(Session {
+            id,
+            name: name.to_string(),
+            ended_at: None,
+        });
+        self.active_session = Some(id);
+        self.save_sessions();
+    }
+
+    /// End the active session, if any. Returns its name.
+    pub fn end_session(&mut self) -> Option<String> {
+        let id = self.active_session.take()?;
+        let session = self.sessions.iter_mut().find(|s| s.id == id)?;
+        session.ended_at = Some(now());
+        let name = session.name.clone();
+        self.save_sessions();
+        Some(name)
+    }
+
+    /// The active session, if one is running.
+    pub fn current_session(&self) -> Option<&Session> {
+        let id = self.active_session?;
+        self.sessions.iter().find(|s| s.id == id)
+    }
+
+    /// Find a session by name (case-insensitive), preferring the most recent
+    /// one. With no name, returns the active or most recent session.
+    pub fn find_session(&self, name: Option<&str>) -> Option<&Session> {
+        match name {
+            Some(name) => self
+                .sessions
+                .iter()
+                .rev()
+                .find(|s| s.name.eq_ignore_ascii_case(name)),
+            None => self.current_session().or(self.sessions.last()),
+        }
+    }
+
+    /// History entries belonging to a session, in play order.
+    pub fn session_entries(&self, session_id: u64) -> Vec<&HistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.session == Some(session_id))
+            .collect()
+    }
+
+    /// Summarize a session: play counts, time listened, and top artists.
+    pub fn session_stats(&self, session_id: u64) -> SessionStats {
+        let entries = self.session_entries(session_id);
+        let end = self
+            .sessions
+            .iter()
+            .find(|s| s.id == session_id)
+            .and_then(|s| s.ended_at)
+            .unwrap_or_else(now);
+
+        let mut artists: HashMap<&str, usize> = HashMap::new();
+        let mut unique: Vec<(&str, &str)> = Vec::new();
+        for e in &entries {
+            *artists.entry(e.artist.as_str()).or_default() += 1;
+            if !unique.contains(&(e.name.as_str(), e.artist.as_str())) {
+                unique.push((e.name.as_str(), e.artist.as_str()));
+            }
+        }
+        let mut top_artists: Vec<(String, usize)> = artists
+            .into_iter()
+            .map(|(a, n)| (a.to_string(), n))
+            .collect();
+        top_artists.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_artists.truncate(5);
+
+        SessionStats {
+            tracks: entries.len(),
+            unique_tracks: unique.len(),
+            listened_secs: listened_secs(&entries, end),
+            top_artists,
+        }
+    }
+
+    fn save_sessions(&self) {
+        let Some(path) = sessions_path() else { return };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&self.sessions) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+/// Time actually spent on `entries` (in play order): each one ran until the
+/// next started, or until `end` for the last, but never past its length.
+/// A track skipped after a few seconds counts only those seconds.
+fn listened_secs(entries: &[&HistoryEntry], end: u64) -> f64 {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let stopped = entries.get(i + 1).map_or(end, |next| next.played_at);
+            (stopped.saturating_sub(e.played_at) as f64).min(e.duration)
+        })
+        .sum()
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+/// History file path: ~/.config/cli-music/history.jsonl (one entry per line)
+fn history_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("cli-music").join("history.jsonl"))
+}
+
+/// Sessions file path: ~/.config/cli-music/sessions.json
+fn sessions_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("cli-music").join("sessions.json"))
+}
+
+fn append_line(entry: &HistoryEntry) {
+    let Some(path) = history_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let Ok(line) = serde_json::to_string(entry) else { return };
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Current Unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// How long ago a Unix timestamp was, e.g. "5m ago" or "3d ago".
+pub fn ago(timestamp: u64) -> String {
+    let secs = now().saturating_sub(timestamp);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86_400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(played_at: u64, duration: f64) -> HistoryEntry {
+        HistoryEntry {
+            played_at,
+            id: 1,
+            name: String::new(),
+            artist: String::new(),
+            album: String::new(),
+            duration,
+            session: Some(0),
+        }
+    }
+
+    #[test]
+    fn listened_time_stops_at_the_next_track() {
+        // Skipped after 3s, then played through, then cut off by the end
+        let entries = [entry(100, 200.0), entry(103, 60.0), entry(163, 300.0)];
+        let refs: Vec<&HistoryEntry> = entries.iter().collect();
+        assert_eq!(listened_secs(&refs, 263), 3.0 + 60.0 + 100.0);
+    }
+
+    #[test]
+    fn listened_time_is_capped_at_the_length() {
+        // A pause (or a gap with nothing playing) doesn't count past the end
+        let entries = [entry(0, 60.0), entry(1000, 30.0)];
+        let refs: Vec<&HistoryEntry> = entries.iter().collect();
+        assert_eq!(listened_secs(&refs, 5000), 90.0);
+        assert_eq!(listened_secs(&[], 5000), 0.0);
+    }
+}
@@ -0,0 +1,183 @@
+use color_eyre::{eyre::eyre, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Apple allows roughly 20 Search API calls per minute per client.
+const RATE_LIMIT: usize = 20;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One item from a Search API response. Fields missing for the entity type
+/// are left empty.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchResult {
+    pub track_name: String,
+    pub artist_name: String,
+    pub collection_name: String,
+    /// 100x100 artwork; other sizes are had by rewriting `100x100bb`.
+    pub artwork_url100: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchResult>,
+}
+
+/// Counters since startup, for `:features`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    /// Requests actually sent.
+    pub requests: u64,
+    /// Lookups answered by an identical request already in flight.
+    pub deduplicated: u64,
+    /// Requests that had to wait for the rate limit.
+    pub throttled: u64,
+    pub failed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    deduplicated: AtomicU64,
+    throttled: AtomicU64,
+    failed: AtomicU64,
+}
+
+type Outcome = std::result::Result<Vec<SearchResult>, String>;
+
+/// A request in flight that identical lookups wait on.
+#[derive(Default)]
+struct Pending {
+    outcome: Mutex<Option<Outcome>>,
+    done: Condvar,
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// Shared Search API client. Every lookup goes through it so artwork and
+/// catalog searches share one rate limit.
+pub struct Client {
+    http: reqwest::blocking::Client,
+    /// Send times of the requests in the current rate window.
+    recent: Mutex<VecDeque<Instant>>,
+    in_flight: Mutex<HashMap<String, Arc<Pending>>>,
+    counters: Counters,
+}
+
+/// The process-wide client.
+pub fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new)
+}
+
+impl Client {
+    fn new() -> Self {
+        Self {
+            http: reqwest::blocking::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            recent: Mutex::new(VecDeque::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Search the catalog for `term`, restricted to `entity` (e.g. "song",
+    /// "album"). Blocks while the rate limit is exhausted; concurrent calls
+    /// with the same arguments share one request.
+    pub fn search(&self, term: &str, entity: &str, limit: u32) -> Result<Vec<SearchResult>> {
+        let url = format!(
+            "https://itunes.apple.com/search?term={}&entity={entity}&limit={limit}",
+            urlencoding::encode(term)
+        );
+
+        let (pending, leader) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&url) {
+                Some(pending) => (pending.clone(), false),
+                None => {
+                    let pending = Arc::new(Pending::default());
+                    in_flight.insert(url.clone(), pending.clone());
+                    (pending, true)
+                }
+            }
+        };
+
+        if !leader {
+            self.counters.deduplicated.fetch_add(1, Ordering::Relaxed);
+            let outcome = pending
+                .done
+                .wait_while(pending.outcome.lock().unwrap(), |o| o.is_none())
+                .unwrap();
+            return outcome.clone().unwrap_or_else(|| Ok(Vec::new())).map_err(|e| eyre!(e));
+        }
+
+        let outcome = self.fetch(&url);
+        if outcome.is_err() {
+            self.counters.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        *pending.outcome.lock().unwrap() = Some(outcome.clone());
+        pending.done.notify_all();
+        self.in_flight.lock().unwrap().remove(&url);
+        outcome.map_err(|e| eyre!(e))
+    }
+
+    fn fetch(&self, url: &str) -> Outcome {
+        self.throttle();
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        let response: SearchResponse = self
+            .http
+            .get(url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| e.to_string())?;
+        Ok(response.results)
+    }
+
+    /// Wait until sending another request stays within the rate limit, then
+    /// record it.
+    fn throttle(&self) {
+        let mut waited = false;
+        loop {
+            let wait = {
+                let mut recent = self.recent.lock().unwrap();
+                while recent.front().is_some_and(|t| t.elapsed() >= RATE_WINDOW) {
+                    recent.pop_front();
+                }
+                if recent.len() < RATE_LIMIT {
+                    recent.push_back(Instant::now());
+                    return;
+                }
+                RATE_WINDOW.saturating_sub(recent[0].elapsed())
+            };
+            if !waited {
+                waited = true;
+                self.counters.throttled.fetch_add(1, Ordering::Relaxed);
+            }
+            thread::sleep(wait);
+        }
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            deduplicated: self.counters.deduplicated.load(Ordering::Relaxed),
+            throttled: self.counters.throttled.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use color_eyre::Result;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::process::Command;
+use std::time::Instant;
+
+/// Helper functions prepended to every script (see `scripts/prelude.js`).
+const PRELUDE: &str = include_str!("scripts/prelude.js");
+
+/// osascript's error number when the user hasn't allowed this terminal to
+/// control Music.
+const NOT_AUTHORIZED: &str = "-1743";
+
+/// The terminal isn't allowed to send Apple Events to Music.
+#[derive(Debug)]
+pub struct NotAuthorized;
+
+impl fmt::Display for NotAuthorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "Automation permission denied (allow your terminal to control Music in System Settings > Privacy & Security > Automation)",
+        )
+    }
+}
+
+impl std::error::Error for NotAuthorized {}
+
+/// Turn osascript's stderr into an error, with a readable message for the
+/// common failures. A missing permission is a `NotAuthorized` that callers
+/// can downcast to.
+pub fn error(stderr: &str) -> color_eyre::Report {
+    if stderr.contains(NOT_AUTHORIZED) {
+        return NotAuthorized.into();
+    }
+    color_eyre::eyre::eyre!("{}", stderr.trim())
+}
+
+// ---------------------------------------------------------------------------
+// Values
+// ---------------------------------------------------------------------------
+
+/// A value substituted into a script template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Ids(Vec<i32>),
+    Strs(Vec<String>),
+    /// JavaScript source inserted verbatim.
+    Code(String),
+}
+
+impl Value {
+    /// The value as a JavaScript literal (or the code itself for `Code`).
+    pub fn to_js(&self) -> String {
+        match self {
+            // JSON string literals are valid JS and escape quotes, backslashes
+            // and control characters
+            Value::Str(s) => serde_json::to_string(s).unwrap_or_else(|_| "\"\"".into()),
+            Value::Int(n) => n.to_string(),
+            Value::Float(x) if x.is_finite() => x.to_string(),
+            Value::Float(_) => "0".into(),
+            Value::Bool(b) => b.to_string(),
+            Value::Ids(ids) => serde_json::to_string(ids).unwrap_or_else(|_| "[]".into()),
+            Value::Strs(strs) => serde_json::to_string(strs).unwrap_or_else(|_| "[]".into()),
+            Value::Code(code) => code.clone(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<u8> for Value {
+    fn from(n: u8) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Float(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&[i32]> for Value {
+    fn from(ids: &[i32]) -> Self {
+        Value::Ids(ids.to_vec())
+    }
+}
+
+impl From<&[String]> for Value {
+    fn from(strs: &[String]) -> Self {
+        Value::Strs(strs.to_vec())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scripts
+// ---------------------------------------------------------------------------
+
+/// A JXA script template with its parameters bound.
+///
+/// Templates reference parameters as `{{name}}`; every placeholder must be
+/// bound before the script is rendered.
+#[derive(Debug, Clone)]
+pub struct Script {
+    template: &'static str,
+    params: Vec<(&'static str, Value)>,
+}
+
+impl Script {
+    pub fn new(template: &'static str) -> Self {
+        Self {
+            template,
+            params: Vec::new(),
+        }
+    }
+
+    /// Bind `{{name}}` to a value, rendered as a JS literal.
+    pub fn arg(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        self.params.push((name, value.into()));
+        self
+    }
+
+    /// Bind `{{name}}` to a snippet of JavaScript source.
+    pub fn code(mut self, name: &'static str, code: &str) -> Self {
+        self.params.push((name, Value::Code(code.to_string())));
+        self
+    }
+
+    /// The template with every placeholder substituted (without the prelude).
+    ///
+    /// Panics on an unbound placeholder: templates are compiled in, so that's
+    /// a bug rather than bad input.
+    pub fn render(&self) -> String {
+        let mut out = String::with_capacity(self.template.len());
+        let mut rest = self.template;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            let value = self
+                .params
+                .iter()
+                .find(|(n, _)| *n == name)
+                .unwrap_or_else(|| panic!("JXA template parameter `{name}` is not bound"));
+            out.push_str(&rest[..start]);
+            out.push_str(&value.1.to_js());
+            rest = &rest[start + 2 + len + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Run the script, returning its trimmed output. A non-zero exit becomes
+    /// an error carrying Music's message.
+    pub fn run(&self) -> Result<String> {
+        let source = format!("{PRELUDE}\n{}", self.render());
+        let started = Instant::now();
+        let output = Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", &source])
+            .output()?;
+        let elapsed = started.elapsed().as_millis();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            crate::log::write(format!("script failed after {elapsed}ms: {}", stderr.trim()));
+            return Err(error(&stderr));
+        }
+        crate::log::write(format!("script ok in {elapsed}ms ({} bytes)", output.stdout.len()));
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Run a script that returns `JSON.stringify(...)` and parse its output.
+    pub fn run_json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_str(&self.run()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(Value::from(r#"say "hi""#).to_js(), r#""say \"hi\"""#);
+        assert_eq!(Value::from(r"C:\Music").to_js(), r#""C:\\Music""#);
+        assert_eq!(Value::from("one\ntwo\r").to_js(), r#""one\ntwo\r""#);
+        assert_eq!(Value::from("tab\there\u{1}").to_js(), r#""tab\there\u0001""#);
+        assert_eq!(Value::from("</script>'").to_js(), r#""</script>'""#);
+    }
+
+    #[test]
+    fn other_values() {
+        assert_eq!(Value::from(42).to_js(), "42");
+        assert_eq!(Value::from(1.5).to_js(), "1.5");
+        assert_eq!(Value::from(f64::NAN).to_js(), "0");
+        assert_eq!(Value::from(true).to_js(), "true");
+        assert_eq!(Value::from(&[1, 2][..]).to_js(), "[1,2]");
+        assert_eq!(Value::from(&["a\"b".to_string()][..]).to_js(), r#"["a\"b"]"#);
+    }
+
+    #[test]
+    fn render_substitutes_placeholders() {
+        let script = Script::new("play({{ id }}, {{name}}); {{body}} {{id}}")
+            .arg("id", 7)
+            .arg("name", "It's \"here\"")
+            .code("body", "delay(1);");
+        assert_eq!(script.render(), r#"play(7, "It's \"here\""); delay(1); 7"#);
+    }
+
+    #[test]
+    fn render_leaves_unclosed_braces() {
+        assert_eq!(Script::new("a {{ b").render(), "a {{ b");
+    }
+
+    #[test]
+    #[should_panic(expected = "`missing` is not bound")]
+    fn render_rejects_unbound_placeholders() {
+        Script::new("x = {{missing}}").arg("other", 1).render();
+    }
+}
@@ -1,6 +1,9 @@
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use serde::Deserialize;
-use std::process::Command;
+use std::path::Path;
+
+use crate::jxa::Script;
+use crate::mix::Camelot;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -8,26 +11,187 @@ use std::process::Command;
 
 #[derive(Debug, Clone)]
 pub struct PlaylistEntry {
-    #[allow(dead_code)]
+    /// Music's id for the playlist. Names aren't unique, so every lookup
+    /// goes through this.
     pub id: i32,
+    /// Music's persistent id. Unlike `id`, it survives Music rebuilding its
+    /// database, so it's what gets saved between runs.
+    pub persistent_id: String,
     pub name: String,
+    /// A plain user playlist, which tracks can be added to (not the
+    /// library, a smart playlist or a special one like Purchased).
+    pub editable: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TrackEntry {
     pub id: i32,
     pub name: String,
     pub artist: String,
     pub album: String,
-    #[allow(dead_code)]
+    /// Release year, 0 if unknown.
+    pub year: u32,
     pub duration: f64,
+    /// ISO-8601 timestamp, empty if unknown.
+    pub date_added: String,
+    pub play_count: u32,
+    /// Beats per minute, 0 if not set.
+    pub bpm: u32,
+    /// Musical key, parsed from the comment or grouping field.
+    pub key: Option<Camelot>,
+    /// Index in the playlist's own order.
+    pub position: usize,
+    pub favorited: bool,
+    pub disliked: bool,
+    /// Star rating as stored by Music: 0–100, 20 per star.
+    pub rating: u8,
+}
+
+/// How the user feels about a track: Music's favorite and dislike flags,
+/// which are mutually exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Opinion {
+    Favorite,
+    Neutral,
+    Dislike,
+}
+
+impl Opinion {
+    /// JXA statements applying this opinion to the track in variable `t`.
+    pub(crate) fn jxa(self) -> &'static str {
+        match self {
+            Opinion::Favorite => "t.favorited = true;",
+            Opinion::Neutral => "t.favorited = false; t.disliked = false;",
+            Opinion::Dislike => "t.disliked = true;",
+        }
+    }
+
+    /// The opinion Music's flags express.
+    pub fn of(favorited: bool, disliked: bool) -> Self {
+        match (favorited, disliked) {
+            (true, _) => Opinion::Favorite,
+            (false, true) => Opinion::Dislike,
+            (false, false) => Opinion::Neutral,
+        }
+    }
+
+    /// (favorited, disliked) flags after applying this opinion.
+    pub fn flags(self) -> (bool, bool) {
+        match self {
+            Opinion::Favorite => (true, false),
+            Opinion::Neutral => (false, false),
+            Opinion::Dislike => (false, true),
+        }
+    }
+}
+
+/// Full metadata for one track, fetched on demand for the detail popup.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrackDetails {
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    pub genre: String,
+    pub year: u32,
+    pub composer: String,
+    /// kbps
+    pub bit_rate: u32,
+    /// Hz
+    pub sample_rate: u32,
+    pub play_count: u32,
+    /// ISO-8601 timestamps, empty if unknown.
+    pub date_added: String,
+    pub played_date: String,
+    /// File kind, e.g. "Apple Music AAC audio file".
+    pub kind: String,
+    /// e.g. "matched", "purchased", "subscription", "local only"
+    pub cloud_status: String,
+}
+
+/// A library-wide track list offered in the library menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmartList {
+    RecentlyAdded,
+    RecentlyPlayed,
+    /// Highly rated or much played tracks not played for a while.
+    ForgottenFavorites,
+}
+
+impl SmartList {
+    pub fn label(self) -> &'static str {
+        match self {
+            SmartList::RecentlyAdded => "Recently Added",
+            SmartList::RecentlyPlayed => "Recently Played",
+            SmartList::ForgottenFavorites => "Forgotten Favorites",
+        }
+    }
+
+    /// The list's tracks, best first: the newest for the recent lists, and
+    /// for forgotten favorites, 4+ star or much played tracks last played
+    /// over `forgotten_months` ago.
+    pub fn fetch(self, forgotten_months: u32) -> Result<Vec<TrackEntry>> {
+        let property = match self {
+            SmartList::RecentlyAdded => "dateAdded",
+            SmartList::RecentlyPlayed => "playedDate",
+            SmartList::ForgottenFavorites => {
+                return fetch_tracks(
+                    Script::new(FORGOTTEN_FAVORITES)
+                        .arg("months", forgotten_months)
+                        .arg("min_plays", FORGOTTEN_MIN_PLAYS)
+                        .arg("limit", RECENT_LIMIT),
+                );
+            }
+        };
+        fetch_tracks(
+            Script::new(RECENT_TRACKS)
+                .code("property", property)
+                .arg("limit", RECENT_LIMIT),
+        )
+    }
+}
+
+/// A playlist's tracks fetched chunk by chunk. When a chunk fails (e.g. an
+/// osascript timeout), the tracks before it are kept and the rest can be
+/// fetched later with `resume_playlist_tracks`.
+#[derive(Debug, Clone)]
+pub struct TrackFetch {
+    pub tracks: Vec<TrackEntry>,
+    /// Tracks in the playlist, as of the last chunk.
+    pub total: usize,
+    /// Why the fetch stopped early, if it did.
+    pub error: Option<String>,
+}
+
+/// Where Music is playing from: its current playlist and track.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct PlayingFrom {
+    pub playlist: i32,
+    pub track: i32,
+}
+
+/// A play queue saved as a playlist.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedQueue {
+    pub name: String,
+    pub count: usize,
 }
 
 // Serde helpers for JSON parsing
 #[derive(Deserialize)]
 struct RawPlaylist {
     id: i32,
+    #[serde(rename = "persistentId")]
+    persistent_id: String,
     name: String,
+    #[serde(default)]
+    editable: bool,
+}
+
+#[derive(Deserialize)]
+struct RawChunk {
+    total: usize,
+    rows: Vec<RawTrack>,
 }
 
 #[derive(Deserialize)]
@@ -36,20 +200,63 @@ struct RawTrack {
     name: String,
     artist: String,
     album: String,
+    #[serde(default)]
+    year: u32,
     duration: f64,
+    #[serde(default, rename = "dateAdded")]
+    date_added: String,
+    #[serde(default, rename = "playCount")]
+    play_count: u32,
+    #[serde(default)]
+    bpm: u32,
+    #[serde(default)]
+    comment: String,
+    #[serde(default)]
+    grouping: String,
+    #[serde(default)]
+    favorited: bool,
+    #[serde(default)]
+    disliked: bool,
+    #[serde(default)]
+    rating: u8,
 }
 
 // ---------------------------------------------------------------------------
-// JS string escaping
+// Scripts
 // ---------------------------------------------------------------------------
 
-fn escape_js(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\0', "")
-}
+const PLAYLISTS: &str = include_str!("scripts/playlists.js");
+const PLAYLIST_TRACKS: &str = include_str!("scripts/playlist_tracks.js");
+const LIBRARY_PLAYLIST_ID: &str = include_str!("scripts/library_playlist_id.js");
+const PLAYING_FROM: &str = include_str!("scripts/playing_from.js");
+const SEARCH_LIBRARY: &str = include_str!("scripts/search_library.js");
+const TRACK_DETAILS: &str = include_str!("scripts/track_details.js");
+const PLAY_TRACK: &str = include_str!("scripts/play_track.js");
+const CREATE_PLAYLIST: &str = include_str!("scripts/create_playlist.js");
+const RENAME_PLAYLIST: &str = include_str!("scripts/rename_playlist.js");
+const DELETE_PLAYLIST: &str = include_str!("scripts/delete_playlist.js");
+const PLAYLIST_ARTWORK: &str = include_str!("scripts/playlist_artwork.js");
+const ADD_TRACKS: &str = include_str!("scripts/add_tracks.js");
+const REMOVE_TRACKS: &str = include_str!("scripts/remove_tracks.js");
+const MOVE_TRACK: &str = include_str!("scripts/move_track.js");
+const SET_BPM: &str = include_str!("scripts/set_bpm.js");
+const SET_OPINION: &str = include_str!("scripts/set_opinion.js");
+const SET_RATING: &str = include_str!("scripts/set_rating.js");
+const PLAY_PLAYLIST: &str = include_str!("scripts/play_playlist.js");
+const PLAY_TRACKS_AS_PLAYLIST: &str = include_str!("scripts/play_tracks_as_playlist.js");
+const SAVE_QUEUE: &str = include_str!("scripts/save_queue.js");
+const RECENT_TRACKS: &str = include_str!("scripts/recent_tracks.js");
+const FORGOTTEN_FAVORITES: &str = include_str!("scripts/forgotten_favorites.js");
+
+/// Tracks fetched per script run when loading a playlist. Small enough that
+/// one chunk finishes well inside osascript's Apple Event timeout.
+const CHUNK_SIZE: usize = 200;
+
+/// Tracks shown in each library menu list.
+const RECENT_LIMIT: u32 = 250;
+
+/// Plays that make an unrated track a forgotten favorite.
+const FORGOTTEN_MIN_PLAYS: u32 = 10;
 
 // ---------------------------------------------------------------------------
 // Public API
@@ -57,112 +264,253 @@ fn escape_js(s: &str) -> String {
 
 /// Fetch all playlists (id + name) from Apple Music.
 pub fn fetch_playlists() -> Result<Vec<PlaylistEntry>> {
-    let script = r#"
-(function() {
-    var app = Application('Music');
-    var pls = app.playlists();
-    var result = [];
-    for (var i = 0; i < pls.length; i++) {
-        result.push({ id: pls[i].id(), name: pls[i].name() });
-    }
-    return JSON.stringify(result);
-})()
-"#;
-
-    let output = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", script])
-        .output()?;
-
-    if !output.status.success() {
-        return Ok(Vec::new());
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let raw: Vec<RawPlaylist> = serde_json::from_str(stdout.trim()).unwrap_or_default();
+    let raw: Vec<RawPlaylist> = Script::new(PLAYLISTS).run_json().unwrap_or_default();
 
     Ok(raw
         .into_iter()
         .map(|p| PlaylistEntry {
             id: p.id,
+            persistent_id: p.persistent_id,
             name: p.name,
+            editable: p.editable,
         })
         .collect())
 }
 
-/// Fetch tracks from a named playlist using batch property access.
-/// Instead of calling .name(), .artist(), etc. on each track individually
-/// (which is one Apple Event per call), we grab all values in bulk arrays.
-/// This reduces thousands of IPC roundtrips to just 5.
-pub fn fetch_playlist_tracks(playlist_name: &str) -> Result<Vec<TrackEntry>> {
-    let escaped = escape_js(playlist_name);
-    let script = format!(
-        r#"
-(function() {{
-    var app = Application('Music');
-    var pl = app.playlists.byName("{}");
-    var t = pl.tracks;
-    var ids = t.id();
-    var names = t.name();
-    var artists = t.artist();
-    var albums = t.album();
-    var durations = t.duration();
-    var dates = t.dateAdded();
-    var result = [];
-    for (var i = 0; i < names.length; i++) {{
-        result.push({{
-            id: ids[i],
-            name: names[i],
-            artist: artists[i],
-            album: albums[i],
-            duration: durations[i],
-            dateAdded: dates[i] ? dates[i].toISOString() : ""
-        }});
-    }}
-    result.sort(function(a, b) {{ return b.dateAdded.localeCompare(a.dateAdded); }});
-    return JSON.stringify(result);
-}})()"#,
-        escaped
-    );
-
-    let output = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", &script])
-        .output()?;
-
-    if !output.status.success() {
-        return Ok(Vec::new());
+/// Fetch the tracks of a playlist, failing if any chunk fails.
+pub fn fetch_playlist_tracks(playlist_id: i32) -> Result<Vec<TrackEntry>> {
+    let fetch = resume_playlist_tracks(playlist_id, Vec::new(), |_, _| {});
+    match fetch.error {
+        None => Ok(fetch.tracks),
+        Some(error) => Err(eyre!(error)),
     }
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let raw: Vec<RawTrack> = serde_json::from_str(stdout.trim()).unwrap_or_default();
+/// Fetch a playlist's tracks after the ones already in `tracks`, chunk by
+/// chunk, stopping at the first failure with what was fetched so far.
+/// `on_chunk` gets each chunk's tracks and the playlist's total as they
+/// arrive, so a caller can show them before the rest has loaded.
+pub fn resume_playlist_tracks(
+    playlist_id: i32,
+    mut tracks: Vec<TrackEntry>,
+    mut on_chunk: impl FnMut(&[TrackEntry], usize),
+) -> TrackFetch {
+    let mut total = tracks.len();
+    loop {
+        let start = tracks.len();
+        let chunk: Result<RawChunk> = Script::new(PLAYLIST_TRACKS)
+            .arg("playlist", playlist_id)
+            .arg("start", start as u32)
+            .arg("end", (start + CHUNK_SIZE) as u32)
+            .run_json();
+        match chunk {
+            Ok(chunk) => {
+                total = chunk.total;
+                let done = chunk.rows.is_empty();
+                tracks.extend(convert_tracks(chunk.rows, start));
+                if !done {
+                    on_chunk(&tracks[start..], total);
+                }
+                if done || tracks.len() >= total {
+                    return TrackFetch { tracks, total, error: None };
+                }
+            }
+            Err(e) => {
+                return TrackFetch {
+                    tracks,
+                    total,
+                    error: Some(e.to_string()),
+                };
+            }
+        }
+    }
+}
 
-    Ok(raw
-        .into_iter()
-        .map(|t| TrackEntry {
+/// Id of the main library playlist (its name is localized, so it can't be
+/// found by name).
+pub fn library_playlist_id() -> Result<i32> {
+    Ok(Script::new(LIBRARY_PLAYLIST_ID).run()?.parse()?)
+}
+
+/// The playlist Music is playing from and the track in it, or None when
+/// stopped or playing from elsewhere (e.g. a radio station).
+pub fn playing_from() -> Result<Option<PlayingFrom>> {
+    Script::new(PLAYING_FROM).run_json()
+}
+
+/// Search the whole library for tracks whose title, artist or album contains
+/// `query` (case-insensitive).
+pub fn search_library(query: &str) -> Result<Vec<TrackEntry>> {
+    fetch_tracks(Script::new(SEARCH_LIBRARY).arg("query", query))
+}
+
+/// Run a script returning `trackRows(...)` (see `scripts/prelude.js`) and
+/// convert its rows.
+fn fetch_tracks(script: Script) -> Result<Vec<TrackEntry>> {
+    let raw: Vec<RawTrack> = script.run_json()?;
+    Ok(convert_tracks(raw, 0))
+}
+
+/// Convert script rows to tracks, numbering positions from `offset`.
+fn convert_tracks(raw: Vec<RawTrack>, offset: usize) -> Vec<TrackEntry> {
+    raw.into_iter()
+        .enumerate()
+        .map(|(i, t)| TrackEntry {
             id: t.id,
             name: t.name,
             artist: t.artist,
             album: t.album,
+            year: t.year,
             duration: t.duration,
+            date_added: t.date_added,
+            play_count: t.play_count,
+            bpm: t.bpm,
+            key: Camelot::parse(&t.grouping).or_else(|| Camelot::parse(&t.comment)),
+            position: offset + i,
+            favorited: t.favorited,
+            disliked: t.disliked,
+            rating: t.rating,
         })
-        .collect())
+        .collect()
+}
+
+/// Fetch full metadata for a track. Properties Music doesn't have for the
+/// track (e.g. bit rate for a cloud-only track) are left empty.
+pub fn fetch_track_details(track_id: i32) -> Result<TrackDetails> {
+    Script::new(TRACK_DETAILS).arg("track", track_id).run_json()
+}
+
+/// Play a track by its Music track id (`id()`, not the persistent id).
+///
+/// Fails with Music's reason when the track can't be played: it's gone from
+/// the cloud, its file is missing, or Music refuses to start it (e.g. DRM).
+pub fn play_track_by_id(track_id: i32) -> Result<()> {
+    Script::new(PLAY_TRACK).arg("track", track_id).run()?;
+    Ok(())
+}
+
+/// Create an empty user playlist.
+pub fn create_playlist(name: &str) -> Result<()> {
+    Script::new(CREATE_PLAYLIST).arg("name", name).run()?;
+    Ok(())
+}
+
+/// Rename a user playlist.
+pub fn rename_playlist(playlist_id: i32, new_name: &str) -> Result<()> {
+    Script::new(RENAME_PLAYLIST)
+        .arg("playlist", playlist_id)
+        .arg("name", new_name)
+        .run()?;
+    Ok(())
+}
+
+/// Delete a user playlist (the tracks stay in the library).
+pub fn delete_playlist(playlist_id: i32) -> Result<()> {
+    Script::new(DELETE_PLAYLIST).arg("playlist", playlist_id).run()?;
+    Ok(())
 }
 
-/// Play a track by its persistent ID.
-pub fn play_track_by_id(track_id: i32) {
-    let script = format!(
-        r#"
-(function() {{
-    var app = Application('Music');
-    var matches = app.tracks.whose({{id: {}}});
-    if (matches.length > 0) {{
-        matches[0].play();
-    }}
-}})()"#,
-        track_id
-    );
+/// Write a playlist's own artwork (the image set on it in Music) to `path`.
+/// Returns false when it has none.
+pub fn export_playlist_artwork(playlist_id: i32, path: &Path) -> Result<bool> {
+    let out = Script::new(PLAYLIST_ARTWORK)
+        .arg("playlist", playlist_id)
+        .arg("path", path.to_string_lossy().as_ref())
+        .run()?;
+    Ok(out == "1")
+}
+
+/// Add library tracks to a user playlist (appended in the given order).
+pub fn add_tracks_to_playlist(playlist_id: i32, track_ids: &[i32]) -> Result<()> {
+    Script::new(ADD_TRACKS)
+        .arg("playlist", playlist_id)
+        .arg("tracks", track_ids)
+        .run()?;
+    Ok(())
+}
 
-    let _ = Command::new("osascript")
-        .args(["-l", "JavaScript", "-e", &script])
-        .output();
+/// Remove tracks from a user playlist (they stay in the library).
+pub fn remove_tracks_from_playlist(playlist_id: i32, track_ids: &[i32]) -> Result<()> {
+    Script::new(REMOVE_TRACKS)
+        .arg("playlist", playlist_id)
+        .arg("tracks", track_ids)
+        .run()?;
+    Ok(())
 }
 
+/// Move a track within a user playlist to just before or after another
+/// track of the same playlist.
+pub fn move_track(playlist_id: i32, track_id: i32, anchor_id: i32, after: bool) -> Result<()> {
+    Script::new(MOVE_TRACK)
+        .arg("playlist", playlist_id)
+        .arg("track", track_id)
+        .arg("anchor", anchor_id)
+        .arg("after", after)
+        .run()?;
+    Ok(())
+}
+
+/// Write a track's BPM field.
+pub fn set_track_bpm(track_id: i32, bpm: u32) -> Result<()> {
+    Script::new(SET_BPM).arg("track", track_id).arg("bpm", bpm).run()?;
+    Ok(())
+}
+
+/// Favorite, dislike or clear the flags on library tracks.
+pub fn set_opinion(track_ids: &[i32], opinion: Opinion) -> Result<()> {
+    Script::new(SET_OPINION)
+        .arg("tracks", track_ids)
+        .code("apply", opinion.jxa())
+        .run()?;
+    Ok(())
+}
+
+/// Set the star rating (0–100) of library tracks.
+pub fn set_rating(track_ids: &[i32], rating: u8) -> Result<()> {
+    Script::new(SET_RATING)
+        .arg("tracks", track_ids)
+        .arg("rating", rating)
+        .run()?;
+    Ok(())
+}
+
+/// Start playing a whole playlist, optionally with shuffle turned on.
+pub fn play_playlist(playlist_id: i32, shuffle: bool) -> Result<()> {
+    Script::new(PLAY_PLAYLIST)
+        .arg("playlist", playlist_id)
+        .arg("shuffle", shuffle)
+        .run()?;
+    Ok(())
+}
+
+/// A playlist made by `play_tracks_as_playlist`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneratedPlaylist {
+    #[serde(rename = "persistentId")]
+    pub persistent_id: String,
+    /// Persistent ids from `owned` that are gone now: replaced by this one,
+    /// or deleted in Music.
+    pub gone: Vec<String>,
+}
+
+/// Create a user playlist holding `track_ids` in order and start playing it.
+/// An earlier one under the same name is replaced only if it's in `owned`
+/// (persistent ids of playlists cli-music made); otherwise the new one gets
+/// a " (2)"-style name. Ids that no longer resolve to a library track are
+/// skipped.
+pub fn play_tracks_as_playlist(name: &str, track_ids: &[i32], owned: &[String]) -> Result<GeneratedPlaylist> {
+    Script::new(PLAY_TRACKS_AS_PLAYLIST)
+        .arg("name", name)
+        .arg("tracks", track_ids)
+        .arg("owned", owned)
+        .run_json()
+}
+
+/// Copy the tracks Music is playing from (its current playlist) into a new
+/// user playlist, in order. Without a name it's called "Queue <date>"; a
+/// taken name gets a " (2)"-style suffix.
+pub fn save_queue(name: Option<&str>) -> Result<SavedQueue> {
+    Script::new(SAVE_QUEUE)
+        .arg("name", name.unwrap_or_default())
+        .run_json()
+}
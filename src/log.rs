@@ -0,0 +1,36 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The log file, once `enable` has opened it. Logging is a no-op before.
+static LOG: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Log file path: ~/.cache/cli-music/cli-music.log
+pub fn path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("cli-music").join("cli-music.log"))
+}
+
+/// Start logging to `path()`, truncating the previous log. Returns the path
+/// on success.
+pub fn enable() -> Option<PathBuf> {
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let file = File::create(&path).ok()?;
+    LOG.set(Mutex::new(file)).ok()?;
+    Some(path)
+}
+
+/// Append a line with a Unix timestamp, if logging is on.
+pub fn write(message: impl AsRef<str>) {
+    let Some(log) = LOG.get() else { return };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64());
+    if let Ok(mut file) = log.lock() {
+        let _ = writeln!(file, "{now:.3} {}", message.as_ref());
+    }
+}
@@ -0,0 +1,452 @@
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::jxa::Script;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A source of lyrics, tried in the configured priority order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// A `.lrc` file next to a local track's audio file.
+    Local,
+    /// Lyrics embedded in the track in Music.app.
+    Music,
+    /// The lrclib.net API (synced lyrics when available).
+    Lrclib,
+    /// genius.com (needs `genius_token` in the config).
+    Genius,
+}
+
+impl Provider {
+    pub fn label(self) -> &'static str {
+        match self {
+            Provider::Local => "local .lrc",
+            Provider::Music => "Music.app",
+            Provider::Lrclib => "LRCLIB",
+            Provider::Genius => "Genius",
+        }
+    }
+}
+
+/// One lyric line, with its start time (seconds) if the lyrics are synced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LyricLine {
+    pub time: Option<f64>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lyrics {
+    pub lines: Vec<LyricLine>,
+    pub synced: bool,
+    pub provider: Provider,
+}
+
+/// The track to look up lyrics for.
+#[derive(Debug, Clone)]
+pub struct LyricsQuery {
+    /// Music's id for the track; the local and Music providers read it.
+    pub track_id: i32,
+    pub name: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: f64,
+}
+
+impl LyricsQuery {
+    /// Key used for the in-memory and on-disk caches.
+    pub fn cache_key(&self) -> String {
+        format!("{} - {}", self.artist, self.name)
+    }
+}
+
+impl Lyrics {
+    /// Index of the line being sung at `position`, for synced lyrics.
+    pub fn current_line(&self, position: f64) -> Option<usize> {
+        if !self.synced {
+            return None;
+        }
+        self.lines
+            .iter()
+            .rposition(|l| l.time.is_some_and(|t| t <= position))
+    }
+
+    /// Start times of the song's sections (verses, choruses, ...) for synced
+    /// lyrics: the first line, every line after a blank one, and bracketed
+    /// labels like "[Chorus]". Empty when there's only one section.
+    pub fn sections(&self) -> Vec<f64> {
+        if !self.synced {
+            return Vec::new();
+        }
+        let mut starts: Vec<f64> = Vec::new();
+        let mut after_blank = true;
+        for line in &self.lines {
+            let Some(time) = line.time else { continue };
+            let text = line.text.trim();
+            if text.is_empty() {
+                after_blank = true;
+                continue;
+            }
+            let label = text.starts_with('[') && text.ends_with(']');
+            if (after_blank || label) && starts.last().is_none_or(|&last| time > last) {
+                starts.push(time);
+            }
+            after_blank = false;
+        }
+        if starts.len() < 2 {
+            starts.clear();
+        }
+        starts
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lookup
+// ---------------------------------------------------------------------------
+
+/// Fetch lyrics for the queried track, trying `providers` in order and
+/// returning the first hit. Results are cached on disk.
+pub fn fetch(
+    query: &LyricsQuery,
+    providers: &[Provider],
+    genius_token: Option<&str>,
+) -> Option<Lyrics> {
+    if let Some(cached) = load_cached(query) {
+        return Some(cached);
+    }
+
+    let lyrics = providers.iter().find_map(|&provider| {
+        let text = match provider {
+            Provider::Local => fetch_local(query.track_id),
+            Provider::Music => fetch_music(query.track_id),
+            Provider::Lrclib => fetch_lrclib(query),
+            Provider::Genius => genius_token.and_then(|token| fetch_genius(query, token)),
+        }?;
+        let lines = parse_lrc(&text);
+        if lines.is_empty() {
+            return None;
+        }
+        Some(Lyrics {
+            synced: lines.iter().any(|l| l.time.is_some()),
+            lines,
+            provider,
+        })
+    })?;
+
+    store_cached(query, &lyrics);
+    Some(lyrics)
+}
+
+/// Parse LRC-formatted text into lines. Text without `[mm:ss.xx]` tags is
+/// returned as unsynced lines; LRC metadata tags (`[ar:...]`) are dropped.
+pub fn parse_lrc(text: &str) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    for raw in text.lines() {
+        let mut rest = raw.trim();
+        let mut times = Vec::new();
+        let mut is_tag = false;
+        while let Some(inner) = rest.strip_prefix('[') {
+            let Some(end) = inner.find(']') else { break };
+            let tag = &inner[..end];
+            if let Some(t) = parse_timestamp(tag) {
+                times.push(t);
+            } else if is_metadata_tag(tag) {
+                is_tag = true;
+            } else {
+                // Literal bracketed text such as "[Chorus]"
+                break;
+            }
+            rest = inner[end + 1..].trim_start();
+        }
+        if is_tag && times.is_empty() {
+            continue;
+        }
+        if times.is_empty() {
+            lines.push(LyricLine { time: None, text: rest.to_string() });
+        }
+        for t in times {
+            lines.push(LyricLine { time: Some(t), text: rest.to_string() });
+        }
+    }
+
+    if lines.iter().any(|l| l.time.is_some()) {
+        lines.retain(|l| l.time.is_some());
+        lines.sort_by(|a, b| a.time.unwrap_or(0.0).total_cmp(&b.time.unwrap_or(0.0)));
+    }
+    // Trim blank lines at either end
+    while lines.first().is_some_and(|l| l.text.is_empty() && l.time.is_none()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.text.is_empty() && l.time.is_none()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Whether `tag` is an LRC metadata tag like `ar:Artist` or `length: 3:20`.
+fn is_metadata_tag(tag: &str) -> bool {
+    tag.split_once(':')
+        .is_some_and(|(key, _)| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Parse an LRC timestamp like `01:23.45` into seconds.
+fn parse_timestamp(s: &str) -> Option<f64> {
+    let (mins, secs) = s.split_once(':')?;
+    let mins: u32 = mins.trim().parse().ok()?;
+    let secs: f64 = secs.trim().parse().ok()?;
+    Some(mins as f64 * 60.0 + secs)
+}
+
+// ---------------------------------------------------------------------------
+// Providers
+// ---------------------------------------------------------------------------
+
+/// Raw shape returned by the track lyrics JXA script.
+#[derive(Deserialize)]
+struct JxaLyrics {
+    #[serde(default)]
+    lyrics: String,
+    #[serde(default)]
+    location: String,
+}
+
+/// The `.lrc` file next to track `track_id`'s audio file. Looked up by id,
+/// not as the current track, which may have changed since the request.
+fn fetch_local(track_id: i32) -> Option<String> {
+    if track_id == 0 {
+        return None;
+    }
+    stored(track_id).ok()?.lrc
+}
+
+fn fetch_music(track_id: i32) -> Option<String> {
+    if track_id == 0 {
+        return None;
+    }
+    let lyrics = stored(track_id).ok()?.music;
+    (!lyrics.trim().is_empty()).then_some(lyrics)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibResponse {
+    synced_lyrics: Option<String>,
+    plain_lyrics: Option<String>,
+}
+
+fn fetch_lrclib(query: &LyricsQuery) -> Option<String> {
+    let url = format!(
+        "https://lrclib.net/api/get?artist_name={}&track_name={}&album_name={}&duration={}",
+        urlencoding::encode(&query.artist),
+        urlencoding::encode(&query.name),
+        urlencoding::encode(&query.album),
+        query.duration.round() as u64,
+    );
+    let resp: LrclibResponse = reqwest::blocking::get(&url).ok()?.json().ok()?;
+    resp.synced_lyrics
+        .filter(|s| !s.trim().is_empty())
+        .or(resp.plain_lyrics)
+        .filter(|s| !s.trim().is_empty())
+}
+
+fn fetch_genius(query: &LyricsQuery, token: &str) -> Option<String> {
+    let client = reqwest::blocking::Client::new();
+    let search = format!(
+        "https://api.genius.com/search?q={}",
+        urlencoding::encode(&format!("{} {}", query.name, query.artist))
+    );
+    let json: serde_json::Value = client
+        .get(&search)
+        .bearer_auth(token)
+        .send()
+        .ok()?
+        .json()
+        .ok()?;
+    let page_url = json["response"]["hits"]
+        .as_array()?
+        .iter()
+        .find_map(|hit| hit["result"]["url"].as_str())?
+        .to_string();
+
+    let html = client.get(&page_url).send().ok()?.text().ok()?;
+    let text = extract_genius_lyrics(&html);
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Pull the lyric text out of a Genius song page. Lyrics live in one or more
+/// `<div data-lyrics-container="true">` elements with `<br>` line breaks.
+fn extract_genius_lyrics(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("data-lyrics-container=\"true\"") {
+        rest = &rest[start..];
+        let Some(open_end) = rest.find('>') else { break };
+        rest = &rest[open_end + 1..];
+
+        // Walk to the matching </div>, tracking nested divs
+        let mut depth = 1;
+        let mut i = 0;
+        while depth > 0 && i < rest.len() {
+            if rest[i..].starts_with("<div") {
+                depth += 1;
+            } else if rest[i..].starts_with("</div") {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            i += rest[i..].chars().next().map_or(1, char::len_utf8);
+        }
+        out.push_str(&strip_html(&rest[..i]));
+        out.push('\n');
+        rest = &rest[i..];
+    }
+    out
+}
+
+fn strip_html(fragment: &str) -> String {
+    let mut out = String::new();
+    let mut rest = fragment;
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let Some(gt) = rest[lt..].find('>') else { break };
+        if rest[lt..lt + gt].starts_with("<br") {
+            out.push('\n');
+        }
+        rest = &rest[lt + gt + 1..];
+    }
+    out.push_str(rest);
+    out.replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+}
+
+// ---------------------------------------------------------------------------
+// Editing
+// ---------------------------------------------------------------------------
+
+const TRACK_LYRICS: &str = include_str!("scripts/track_lyrics.js");
+const SET_LYRICS: &str = include_str!("scripts/set_lyrics.js");
+
+/// A track's own lyrics: Music.app's lyrics field and, for local files, the
+/// `.lrc` file next to the audio file.
+#[derive(Debug, Clone, Default)]
+pub struct StoredLyrics {
+    pub music: String,
+    pub lrc_path: Option<PathBuf>,
+    /// Contents of the `.lrc` file, if there is one.
+    pub lrc: Option<String>,
+}
+
+impl StoredLyrics {
+    /// The text to start editing from: Music's field, else the `.lrc` file.
+    pub fn text(&self) -> &str {
+        if !self.music.trim().is_empty() {
+            return &self.music;
+        }
+        self.lrc.as_deref().unwrap_or("")
+    }
+
+    /// Whether saving would overwrite lyrics the track already has.
+    pub fn exists(&self) -> bool {
+        !self.text().trim().is_empty()
+    }
+}
+
+/// Read a track's stored lyrics.
+pub fn stored(track_id: i32) -> Result<StoredLyrics> {
+    let info: JxaLyrics = Script::new(TRACK_LYRICS).arg("track", track_id).run_json()?;
+    let lrc_path = (!info.location.is_empty()).then(|| PathBuf::from(info.location).with_extension("lrc"));
+    let lrc = lrc_path.as_ref().and_then(|p| std::fs::read_to_string(p).ok());
+    Ok(StoredLyrics { music: info.lyrics, lrc_path, lrc })
+}
+
+/// Write `text` to a track's lyrics field, and to `lrc_path` when given
+/// (an empty `text` removes the file). Updates the disk cache for `query`
+/// and returns the lyrics as they'll now be shown.
+pub fn write(track_id: i32, query: &LyricsQuery, text: &str, lrc_path: Option<&Path>) -> Result<Option<Lyrics>> {
+    Script::new(SET_LYRICS).arg("track", track_id).arg("lyrics", text).run()?;
+    if let Some(path) = lrc_path {
+        if text.trim().is_empty() {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        } else {
+            std::fs::write(path, text)?;
+        }
+    }
+
+    let lines = parse_lrc(text);
+    if lines.is_empty() {
+        if let Some(path) = cache_path(query) {
+            let _ = std::fs::remove_file(path);
+        }
+        return Ok(None);
+    }
+    let lyrics = Lyrics {
+        synced: lines.iter().any(|l| l.time.is_some()),
+        lines,
+        provider: if lrc_path.is_some() { Provider::Local } else { Provider::Music },
+    };
+    store_cached(query, &lyrics);
+    Ok(Some(lyrics))
+}
+
+/// Lyrics as LRC text, with `[mm:ss.xx]` tags when synced: the inverse of
+/// `parse_lrc`, used to seed the editor with fetched lyrics.
+pub fn to_lrc(lyrics: &Lyrics) -> String {
+    let mut out = String::new();
+    for line in &lyrics.lines {
+        if let Some(t) = line.time {
+            let mins = (t / 60.0).floor();
+            out.push_str(&format!("[{:02}:{:05.2}]", mins as u32, t - mins * 60.0));
+        }
+        out.push_str(&line.text);
+        out.push('\n');
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Disk cache
+// ---------------------------------------------------------------------------
+
+/// Cache file path: ~/Library/Caches/cli-music/lyrics/<artist> - <title>.json
+fn cache_path(query: &LyricsQuery) -> Option<PathBuf> {
+    let name = query.cache_key().replace(['/', ':'], "_");
+    dirs::cache_dir().map(|d| d.join("cli-music").join("lyrics").join(format!("{name}.json")))
+}
+
+/// How long cached lyrics are used before the providers are asked again
+/// (they may have gained synced lyrics, or the track its own).
+const CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+fn load_cached(query: &LyricsQuery) -> Option<Lyrics> {
+    let path = cache_path(query)?;
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    if age > CACHE_MAX_AGE {
+        return None;
+    }
+    let data = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn store_cached(query: &LyricsQuery, lyrics: &Lyrics) {
+    let Some(path) = cache_path(query) else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(lyrics) {
+        let _ = std::fs::write(path, json);
+    }
+}
@@ -1,47 +1,233 @@
+mod action;
 mod app;
 mod artwork;
 mod bridge;
+mod browser;
+mod capabilities;
+mod changelog;
+mod cli;
+mod command;
+mod config;
+mod decode;
+mod events;
+mod filter;
+mod history;
+mod itunes;
+mod jxa;
 mod library;
+mod log;
+mod lyrics;
+mod meter;
+mod mix;
+mod remote;
+mod shuffle;
 mod ui;
+mod volume;
 
-use app::{App, LibraryView, Panel, PersistedState};
-use bridge::PlayerStatus;
+use action::Action;
+use app::{
+    App, ArtworkChooser, BpmTap, ConfirmAction, Incomplete, InputAction, LoadProgress, Level, LibraryView, LyricsEditor,
+    MenuItem, Modal,
+    Panel, PersistedState, PickAction, Popup, StartView, StartupOptions, AUDIO_PRESET_ROW, MENU,
+};
+use command::{Command, SessionCommand};
+use decode::Priority;
+use bridge::{PlayState, PlayerStatus};
+use browser::{BrowserColumn, ColumnBrowser};
+use capabilities::Support;
+use library::{Opinion, SmartList, TrackEntry};
+use volume::VolumeChange;
+use clap::Parser;
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use std::sync::mpsc;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Pause between background playlist prefetches, so they don't compete with
+/// the player poll and user-triggered fetches for Music's attention.
+const PREFETCH_DELAY: Duration = Duration::from_secs(2);
+
+/// Playlist that marked tracks are played through.
+const QUEUE_PLAYLIST: &str = "cli-music Queue";
+
+/// How often the startup guide re-checks Music's state on its own.
+const ONBOARDING_RECHECK: Duration = Duration::from_secs(3);
+
+/// Seconds into a section after which `[` restarts it instead of going to
+/// the previous one.
+const SECTION_RESTART: f64 = 3.0;
+
+/// Bounds and step for resizing the Now Playing panel (percent).
+const SPLIT_MIN: u16 = 20;
+const SPLIT_MAX: u16 = 80;
+const SPLIT_STEP: u16 = 5;
 
 enum AppEvent {
     Key(crossterm::event::KeyEvent),
+    /// Text pasted into the terminal (bracketed paste).
+    Paste(String),
     Tick,
     PlayerUpdate(PlayerStatus),
-    TracksLoaded(LibraryView, String, Vec<library::TrackEntry>),
+    /// A playlist's tracks to show, possibly only partly loaded.
+    TracksLoaded(i32, library::TrackFetch),
+    /// A chunk of a playlist's tracks and its total, while the rest loads
+    TracksBatch(i32, Vec<TrackEntry>, usize),
+    /// A fresh fetch of a cached playlist's tracks
+    TracksRefreshed(i32, Vec<TrackEntry>),
+    /// An in-app edit changed a playlist's tracks in Music.
+    PlaylistEdited(i32),
+    SearchLoaded(String, Result<Vec<library::TrackEntry>, String>),
+    /// Tracks loaded ahead of time; cached without changing the view.
+    TracksPrefetched(i32, Vec<library::TrackEntry>),
+    /// Tracks of a library menu list, newest first.
+    ListLoaded(SmartList, Vec<library::TrackEntry>),
+    /// The whole library's tracks, for the column browser (empty on failure).
+    BrowserLoaded(Vec<library::TrackEntry>),
+    PlaylistsLoaded(Vec<library::PlaylistEntry>),
+    /// A playlist made to play tracks in order (see `play_as_playlist`).
+    PlaylistGenerated(library::GeneratedPlaylist),
     ArtworkLoaded(String, Option<image::DynamicImage>),
+    /// A playlist's cover (own artwork or mosaic), by mosaic key.
+    PlaylistCover(String, image::DynamicImage),
+    /// Chooser candidates for a track.
+    ArtworkCandidates(String, Vec<artwork::Candidate>),
+    /// A chooser thumbnail: (track, thumbnail URL, image).
+    ArtworkThumbnail(String, String, Option<image::DynamicImage>),
+    UpNextLoaded(String, Option<bridge::UpNext>),
+    Notify(Level, String),
+    ShowPopup(Popup),
+    /// Music's EQ settings for the audio modal (None if they couldn't be read).
+    AudioSettingsLoaded(Option<bridge::AudioSettings>),
+    /// A playlist's tracks, fetched to preview shuffling it.
+    ShuffleTracks(library::PlaylistEntry, Vec<TrackEntry>),
+    /// Playing a track failed: (track id, reason).
+    PlayFailed(i32, String),
+    LyricsLoaded(i32, String, Option<lyrics::Lyrics>),
+    /// A track's stored lyrics, to open the editor with: (track id, lyrics).
+    LyricsStored(i32, lyrics::StoredLyrics),
+    /// The editor's lyrics were written: (lyrics key, result).
+    LyricsWritten(String, Result<Option<lyrics::Lyrics>, String>),
+    OutputDevice(String),
+    CapabilitiesProbed(capabilities::Capabilities),
+    /// Internet access is back after being off.
+    NetworkRestored,
+    Level(f32),
     TrackSaved,
+    /// Moving a track in a playlist failed: (playlist id, error).
+    ReorderFailed(i32, String),
+    /// A rating change failed: the previous ratings and the marks to restore.
+    RatingFailed(Vec<(i32, u8)>, Vec<i32>, String),
+    /// A favorite/dislike change failed, like `RatingFailed`.
+    OpinionFailed(Vec<(i32, Opinion)>, Vec<i32>, String),
+    /// A remote API request and the channel its reply goes back on.
+    Remote(remote::Request, mpsc::Sender<remote::Reply>),
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let terminal = ratatui::init();
-    let result = run(terminal);
+    let cli = cli::Cli::parse();
+    if let Some(cmd) = cli.command {
+        return cli::run(cmd);
+    }
+    let startup = cli.startup_options();
+
+    let mut terminal = ratatui::init();
+    let _ = crossterm::execute!(std::io::stdout(), event::EnableBracketedPaste);
+    let result = match onboard(&mut terminal) {
+        Ok(true) => run(terminal, startup),
+        other => other.map(|_| ()),
+    };
+    let _ = crossterm::execute!(std::io::stdout(), event::DisableBracketedPaste);
     ratatui::restore();
     result
 }
 
-fn run(mut terminal: ratatui::DefaultTerminal) -> Result<()> {
-    let mut app = App::default();
+/// Until Music is running and scriptable, show a guide instead of an empty
+/// UI. Returns false if the user quit from it.
+fn onboard(terminal: &mut ratatui::DefaultTerminal) -> Result<bool> {
+    let mut access = bridge::check_access();
+    let mut checked = Instant::now();
+    while access != bridge::Access::Ready {
+        terminal.draw(|frame| ui::draw_onboarding(frame, &access))?;
+        if event::poll(Duration::from_millis(200))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(false)
+                    }
+                    KeyCode::Char('o') => bridge::open_music(),
+                    KeyCode::Char('p') => bridge::open_automation_settings(),
+                    KeyCode::Char('r') => {}
+                    _ => continue,
+                }
+                access = bridge::check_access();
+                checked = Instant::now();
+            }
+        } else if checked.elapsed() >= ONBOARDING_RECHECK {
+            access = bridge::check_access();
+            checked = Instant::now();
+        }
+    }
+    Ok(true)
+}
+
+fn run(mut terminal: ratatui::DefaultTerminal, startup: Option<StartupOptions>) -> Result<()> {
+    // Safe mode bypasses everything the user customized: config.toml (and
+    // with it the remote API, event stream, prefetching and level meter),
+    // saved UI state and network access, and logs what happens instead
+    let safe_mode = startup.as_ref().is_some_and(|o| o.safe_mode);
+    let log_path = if safe_mode { log::enable() } else { None };
+    let loaded = if safe_mode { Ok(config::Config::default()) } else { config::Config::load() };
+    let (config, config_error) = match loaded {
+        Ok(config) => (config, None),
+        Err(e) => (config::Config::default(), Some(e)),
+    };
 
     // Load playlists on startup
-    app.playlists = library::fetch_playlists().unwrap_or_default();
+    let mut app = App {
+        announce: config.announce,
+        split_percent: config.split_percent.map(|p| p.clamp(SPLIT_MIN, SPLIT_MAX)),
+        config,
+        safe_mode,
+        playlists: library::fetch_playlists().unwrap_or_default(),
+        history: history::History::load(),
+        ..App::default()
+    };
     if !app.playlists.is_empty() {
         app.playlist_state.select(Some(0));
     }
+    if let Some(e) = config_error {
+        app.push_message(Level::Error, format!("config.toml ignored, using defaults: {e}"));
+    }
 
-    // Restore saved UI state
-    if let Some(state) = PersistedState::load() {
-        state.apply(&mut app);
+    // Open as the flags say, else as configured: the last session's view
+    // (saved UI state) by default
+    let options = startup.unwrap_or_default();
+    let view = options.view.unwrap_or(app.config.start_view);
+    if let Some(mut state) = PersistedState::load().filter(|_| !safe_mode) {
+        // Needed whatever the view, to know which playlists are ours to replace
+        app.generated_playlists = std::mem::take(&mut state.generated_playlists);
+        if view == StartView::Last {
+            state.apply(&mut app);
+        }
+    }
+    options.apply(&mut app, view);
+    if let Some(action) = app.config.on_start.action() {
+        perform(&mut app, action);
+    }
+    app.popup = changelog::check_upgrade();
+    if safe_mode {
+        let log_path = log_path.map_or("unavailable".to_string(), |p| p.display().to_string());
+        app.notify(format!("Safe mode: default settings, network off; log: {log_path}"));
+        app.capabilities.network = Support::Unavailable("Off in safe mode".to_string());
     }
 
     let (tx, rx) = mpsc::channel();
@@ -51,10 +237,14 @@ fn run(mut terminal: ratatui::DefaultTerminal) -> Result<()> {
     thread::spawn(move || {
         loop {
             if event::poll(Duration::from_millis(200)).unwrap_or(false) {
-                if let Ok(Event::Key(key)) = event::read() {
-                    if key.kind == KeyEventKind::Press {
+                match event::read() {
+                    Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
                         let _ = tx_input.send(AppEvent::Key(key));
                     }
+                    Ok(Event::Paste(text)) => {
+                        let _ = tx_input.send(AppEvent::Paste(text));
+                    }
+                    _ => {}
                 }
             } else {
                 let _ = tx_input.send(AppEvent::Tick);
@@ -70,29 +260,114 @@ fn run(mut terminal: ratatui::DefaultTerminal) -> Result<()> {
         thread::sleep(Duration::from_millis(500));
     });
 
+    // Output device polling thread (changes rarely, so poll slowly)
+    let tx_output = tx.clone();
+    thread::spawn(move || loop {
+        if let Some(device) = bridge::fetch_output_device() {
+            let _ = tx_output.send(AppEvent::OutputDevice(device));
+        }
+        thread::sleep(Duration::from_secs(5));
+    });
+
+    // Remote-control API
+    if let Some(addr) = app.config.remote_listen.clone() {
+        let tx_remote = tx.clone();
+        let served = remote::serve(&addr, app.config.remote_token.clone(), move |request, reply| {
+            let _ = tx_remote.send(AppEvent::Remote(request, reply));
+        });
+        if let Err(e) = served {
+            app.push_message(Level::Error, format!("Remote API off: {e}"));
+        }
+    }
+
+    // Event stream for integrations, over WebSocket and/or a Unix socket
+    let mut event_hub = None;
+    // Held until exit, when dropping it removes the socket file
+    let mut _event_socket = None;
+    if app.config.events_listen.is_some() || app.config.events_socket.is_some() {
+        let hub = Arc::new(events::Hub::default());
+        if let Some(addr) = app.config.events_listen.clone() {
+            if let Err(e) = events::serve_websocket(&addr, app.config.remote_token.clone(), hub.clone()) {
+                app.push_message(Level::Error, format!("Event stream off: {e}"));
+            }
+        }
+        if let Some(path) = app.config.events_socket.clone() {
+            match events::serve_socket(Path::new(&path), hub.clone()) {
+                Ok(file) => _event_socket = Some(file),
+                Err(e) => app.push_message(Level::Error, format!("Event socket off: {e}")),
+            }
+        }
+        event_hub = Some(hub);
+    }
+
+    // Capability probes; the network one repeats while it's off
+    let tx_probe = tx.clone();
+    thread::spawn(move || {
+        let _ = tx_probe.send(AppEvent::CapabilitiesProbed(capabilities::probe(!safe_mode)));
+    });
+
+    // Playlist prefetch thread: one playlist at a time, spaced out
+    if app.config.prefetch {
+        let ids = app.prefetch_candidates();
+        let tx_prefetch = tx.clone();
+        thread::spawn(move || {
+            for id in ids {
+                thread::sleep(PREFETCH_DELAY);
+                if let Ok(tracks) = library::fetch_playlist_tracks(id) {
+                    let _ = tx_prefetch.send(AppEvent::TracksPrefetched(id, tracks));
+                }
+            }
+        });
+    }
+
+    // Level meter capture thread (only with a configured capture device)
+    if let Some(device) = app.config.level_meter_device.clone() {
+        app.level_meter = Some(meter::LevelMeter::default());
+        let tx_level = tx.clone();
+        thread::spawn(move || {
+            let tx_capture = tx_level.clone();
+            let result = meter::capture_levels(&device, move |level| {
+                let _ = tx_capture.send(AppEvent::Level(level));
+            });
+            let (level, msg) = match result {
+                Ok(()) => (Level::Info, format!("Level meter stopped (capture device \"{device}\")")),
+                Err(e) => (Level::Error, format!("Level meter unavailable: ffmpeg: {e}")),
+            };
+            let _ = tx_level.send(AppEvent::Notify(level, msg));
+        });
+    }
+
     loop {
-        app.clear_expired_notification();
+        app.clear_expired_toasts();
+        app.remember_position();
+        request_playlist_cover(&mut app, &tx);
+        // Every frame, not just on Ticks, which stop while keys keep coming
+        app.interpolate_position();
         terminal.draw(|frame| ui::draw(frame, &mut app))?;
 
         match rx.recv()? {
             AppEvent::Key(key) => handle_key(&mut app, key, &tx),
-            AppEvent::Tick => {}
+            AppEvent::Paste(text) => handle_paste(&mut app, &text),
+            AppEvent::Tick => {
+                app.tick = app.tick.wrapping_add(1);
+                app.check_up_next();
+                app.check_idle();
+            }
             AppEvent::TrackSaved => {
-                app.notify("\u{2713} Saved to Library!");
-                app.track_cache.remove("Library");
+                app.push_message(Level::Success, "Saved to Library!");
+                let library_ids: Vec<i32> = app
+                    .playlists
+                    .iter()
+                    .filter(|p| p.name == "Library")
+                    .map(|p| p.id)
+                    .collect();
+                app.track_cache.retain(|id, _| !library_ids.contains(id));
+                app.search_cache.clear();
                 // If currently viewing the Library playlist, refresh in place
-                if app.view == LibraryView::Tracks {
-                    let is_library = app.playlist_state.selected()
-                        .and_then(|i| app.playlists.get(i))
-                        .is_some_and(|p| p.name == "Library");
-                    if is_library {
-                        app.loading = true;
-                        let tx_bg = tx.clone();
-                        thread::spawn(move || {
-                            let tracks = library::fetch_playlist_tracks("Library").unwrap_or_default();
-                            let _ = tx_bg.send(AppEvent::TracksLoaded(LibraryView::Tracks, "Library".to_string(), tracks));
-                        });
-                    }
+                let open_library = app.open_playlist().map(|p| p.id).filter(|id| library_ids.contains(id));
+                if let Some(id) = open_library {
+                    app.loading = true;
+                    load_playlist(&mut app, &tx, id, Vec::new());
                 }
             }
             AppEvent::PlayerUpdate(status) => {
@@ -100,43 +375,373 @@ fn run(mut terminal: ratatui::DefaultTerminal) -> Result<()> {
                     status.track_name != app.artwork_track && !status.track_name.is_empty();
 
                 if track_changed {
+                    app.history.record(
+                        status.track_id,
+                        &status.track_name,
+                        &status.artist,
+                        &status.album,
+                        status.duration,
+                    );
+
+                    // Skip the very first track so launching doesn't talk over it
+                    if app.announce && !app.artwork_track.is_empty() {
+                        let (name, artist) = (status.track_name.clone(), status.artist.clone());
+                        let voice = app.config.announce_voice.clone();
+                        let volume = app.config.announce_volume;
+                        let playing = status.state == PlayState::Playing;
+                        thread::spawn(move || {
+                            bridge::announce_track(&name, &artist, voice.as_deref(), volume, playing);
+                        });
+                    }
+
                     app.artwork_track = status.track_name.clone();
                     app.artwork = None;
+                    app.backdrop = None;
 
+                    // Artwork comes from the iTunes Search API
+                    if app.capabilities.network.usable() {
+                        // The previous track's cover isn't wanted anymore
+                        decode::pool().cancel(Priority::NowPlaying);
+                        let track_name = status.track_name.clone();
+                        let tx_art = tx.clone();
+                        let done = move |img| {
+                            let _ = tx_art.send(AppEvent::ArtworkLoaded(track_name, img));
+                        };
+                        request_cover(&status.track_name, &status.artist, &status.album, Priority::NowPlaying, done);
+                    }
+
+                    app.lyrics = None;
+                    app.lyrics_for.clear();
+                    app.lyrics_loading = false;
+                    app.lyrics_cursor = None;
+
+                    app.up_next = None;
+                    app.up_next_shown = false;
                     let track_name = status.track_name.clone();
-                    let artist = status.artist.clone();
-                    let tx_art = tx.clone();
+                    let tx_next = tx.clone();
                     thread::spawn(move || {
-                        let img = artwork::fetch_artwork_url(&track_name, &artist)
-                            .and_then(|url| artwork::download_image(&url));
-                        let _ = tx_art.send(AppEvent::ArtworkLoaded(track_name, img));
+                        let next = bridge::fetch_up_next();
+                        let _ = tx_next.send(AppEvent::UpNextLoaded(track_name, next));
                     });
                 }
 
+                // A track that plays after all is no longer an error
+                app.play_errors.remove(&status.track_id);
+                if let Some(hub) = &event_hub {
+                    hub.publish_changes(&app.player, &status);
+                }
                 app.update_player_status(status);
+                if app.show_lyrics || app.karaoke {
+                    request_lyrics(&mut app, &tx);
+                }
+            }
+            AppEvent::TracksBatch(id, batch, total) => {
+                // Only the latest load streams into the list; others just
+                // end up in the cache
+                if let Some(progress) = app.load_progress.as_mut().filter(|p| p.playlist_id == id) {
+                    progress.loaded += batch.len();
+                    progress.total = total;
+                    if app.loading {
+                        // First chunk: show it without waiting for the rest
+                        app.loading = false;
+                        app.incomplete = None;
+                        app.open_search = None;
+                        app.open_list = None;
+                        app.set_tracks(batch);
+                        app.view = LibraryView::Tracks;
+                    } else if app.view == LibraryView::Tracks && app.open_progress().is_some() {
+                        app.append_tracks(batch);
+                    }
+                }
+            }
+            AppEvent::TracksLoaded(id, fetch) => {
+                let streamed = app.load_progress.as_ref().is_some_and(|p| p.playlist_id == id);
+                if streamed {
+                    app.load_progress = None;
+                }
+                // With chunks already shown (or the user gone elsewhere),
+                // only the cache and the incomplete marker need updating
+                let shown = streamed && !app.loading;
+                app.loading = false;
+                app.incomplete = None;
+                match fetch.error {
+                    None => {
+                        app.track_cache.insert(id, (Instant::now(), fetch.tracks.clone()));
+                    }
+                    Some(ref error) => {
+                        let msg = if fetch.tracks.is_empty() {
+                            format!("Couldn't load tracks ({error}); press r to retry")
+                        } else {
+                            format!(
+                                "Loaded {} of {} tracks ({error}); press r to load the rest",
+                                fetch.tracks.len(),
+                                fetch.total
+                            )
+                        };
+                        app.push_message(Level::Error, msg);
+                        app.incomplete = Some(Incomplete {
+                            playlist_id: id,
+                            fetched: fetch.tracks.clone(),
+                            total: fetch.total,
+                        });
+                    }
+                }
+                if !shown {
+                    app.open_search = None;
+                    app.open_list = None;
+                    app.set_tracks(fetch.tracks);
+                    app.view = LibraryView::Tracks;
+                }
+            }
+            AppEvent::ListLoaded(list, tracks) => {
+                app.loading = false;
+                app.open_search = None;
+                app.open_list = Some(list);
+                app.set_tracks(tracks);
+                app.view = LibraryView::Tracks;
             }
-            AppEvent::TracksLoaded(view, cache_key, tracks) => {
+            AppEvent::ShuffleTracks(playlist, tracks) => {
+                app.track_cache.entry(playlist.id).or_insert((Instant::now(), tracks.clone()));
+                preview_shuffle(&mut app, tx.clone(), playlist, tracks);
+            }
+            AppEvent::TracksPrefetched(id, tracks) => {
+                app.track_cache.entry(id).or_insert((Instant::now(), tracks));
+            }
+            AppEvent::TracksRefreshed(id, tracks) => app.refresh_tracks(id, tracks),
+            AppEvent::PlaylistEdited(id) => {
+                // Whatever Music made of the edit is the truth now
+                app.track_cache.remove(&id);
+                if app.view == LibraryView::Tracks && app.open_playlist().is_some_and(|p| p.id == id) {
+                    refresh_playlist(&tx, id);
+                }
+            }
+            AppEvent::BrowserLoaded(tracks) => {
                 app.loading = false;
-                if !cache_key.is_empty() {
-                    app.track_cache.insert(cache_key, tracks.clone());
+                if !tracks.is_empty() {
+                    app.browser = Some(ColumnBrowser::new(tracks));
+                    app.show_browser_tracks();
                 }
-                app.tracks = tracks;
-                app.track_state.select(if app.tracks.is_empty() {
-                    None
-                } else {
-                    Some(0)
-                });
-                app.view = view;
+            }
+            // Failures aren't cached, so the next search tries again
+            AppEvent::SearchLoaded(query, Err(e)) => {
+                if app.open_search.as_ref() == Some(&query) {
+                    app.loading = false;
+                }
+                app.push_message(Level::Error, format!("Search failed: {e}"));
+            }
+            AppEvent::SearchLoaded(query, Ok(tracks)) => {
+                app.search_cache.insert(query.clone(), (Instant::now(), tracks.clone()));
+                if app.open_search.as_ref() == Some(&query) {
+                    // A background refresh keeps the selection on the same track
+                    app.loading = false;
+                    let selected = app.selected_track().map(|t| t.id);
+                    app.set_tracks(tracks);
+                    app.view = LibraryView::Tracks;
+                    if let Some(pos) = selected.and_then(|id| app.tracks.iter().position(|t| t.id == id)) {
+                        app.track_state.select(Some(pos));
+                    }
+                }
+            }
+            AppEvent::PlaylistsLoaded(playlists) => app.set_playlists(playlists),
+            AppEvent::PlaylistGenerated(generated) => {
+                app.generated_playlists.retain(|id| !generated.gone.contains(id));
+                app.generated_playlists.push(generated.persistent_id);
             }
             AppEvent::ArtworkLoaded(track, img) => {
                 if track == app.artwork_track {
+                    app.backdrop = img.as_ref().map(artwork::blurred_backdrop);
                     app.artwork = img;
                 }
             }
+            AppEvent::ArtworkCandidates(track, candidates) => {
+                if let Some(chooser) = app.artwork_chooser.as_mut().filter(|c| c.track_name == track) {
+                    if candidates.is_empty() {
+                        app.artwork_chooser = None;
+                        app.notify("No artwork found");
+                    } else {
+                        for candidate in &candidates {
+                            let (track, url) = (track.clone(), candidate.thumb_url.clone());
+                            let tx_thumb = tx.clone();
+                            decode::pool().request(
+                                url.clone(),
+                                Priority::Visible,
+                                {
+                                    let url = url.clone();
+                                    move || artwork::download_image(&url)
+                                },
+                                move |img| {
+                                    let _ = tx_thumb.send(AppEvent::ArtworkThumbnail(track, url, img));
+                                },
+                            );
+                        }
+                        chooser.candidates = Some(candidates);
+                    }
+                }
+            }
+            AppEvent::ArtworkThumbnail(track, url, img) => {
+                let candidates = app
+                    .artwork_chooser
+                    .as_mut()
+                    .filter(|c| c.track_name == track)
+                    .and_then(|c| c.candidates.as_mut());
+                if let Some(candidate) = candidates.and_then(|cs| cs.iter_mut().find(|c| c.thumb_url == url)) {
+                    candidate.thumbnail = img;
+                }
+            }
+            AppEvent::OutputDevice(device) => {
+                if app.capabilities.airplay.usable() {
+                    app.set_output_device(device);
+                }
+            }
+            AppEvent::Remote(request, reply) => {
+                let result = match request {
+                    remote::Request::Action(action) => action
+                        .perform(&app.player)
+                        .map(|()| serde_json::json!({ "ok": true }))
+                        .map_err(|e| e.to_string()),
+                    remote::Request::Status => {
+                        serde_json::to_value(cli::StatusJson::from(&app.player)).map_err(|e| e.to_string())
+                    }
+                    remote::Request::Queue => Ok(serde_json::json!({
+                        "up_next": app.up_next.as_ref().map(|n| serde_json::json!({
+                            "title": n.name,
+                            "artist": n.artist,
+                        })),
+                    })),
+                };
+                let _ = reply.send(result);
+            }
+            AppEvent::CapabilitiesProbed(capabilities) => {
+                if !capabilities.airplay.usable() {
+                    app.output_device.clear();
+                }
+                if capabilities.list().iter().any(|(_, s)| !s.usable()) {
+                    app.notify("Some features are unavailable (:features for details)");
+                }
+                if !capabilities.network.usable() && !app.safe_mode {
+                    recheck_network(tx.clone());
+                }
+                app.capabilities = capabilities;
+            }
+            AppEvent::NetworkRestored => {
+                app.capabilities.network = Support::Available;
+                app.notify("Back online: artwork and online lyrics are on again");
+                // What was looked up offline is worth another try
+                app.lyrics_cache.retain(|_, lyrics| lyrics.is_some());
+                app.playlist_cover_requested.clear();
+                if app.lyrics.is_none() {
+                    app.lyrics_for.clear();
+                    if app.show_lyrics || app.karaoke {
+                        request_lyrics(&mut app, &tx);
+                    }
+                }
+                if app.artwork.is_none() && !app.player.track_name.is_empty() {
+                    let track_name = app.player.track_name.clone();
+                    let tx_art = tx.clone();
+                    let done = move |img| {
+                        let _ = tx_art.send(AppEvent::ArtworkLoaded(track_name, img));
+                    };
+                    request_cover(&app.player.track_name, &app.player.artist, &app.player.album, Priority::NowPlaying, done);
+                }
+            }
+            AppEvent::Level(level) => {
+                if let Some(ref mut meter) = app.level_meter {
+                    meter.update(level);
+                }
+            }
+            AppEvent::Notify(level, msg) => app.push_message(level, msg),
+            AppEvent::ShowPopup(popup) => app.popup = Some(popup),
+            AppEvent::AudioSettingsLoaded(None) => {
+                if matches!(app.modal, Some(Modal::Audio { .. })) {
+                    app.modal = None;
+                }
+            }
+            AppEvent::AudioSettingsLoaded(Some(loaded)) => {
+                if let Some(Modal::Audio { settings, selected }) = app.modal.as_mut() {
+                    // Start on the current preset
+                    *selected = loaded.presets.iter().position(|p| *p == loaded.preset).map_or(0, |i| i + AUDIO_PRESET_ROW);
+                    *settings = Some(loaded);
+                }
+            }
+            AppEvent::PlayFailed(id, reason) => {
+                app.push_message(Level::Error, format!("Can't play: {reason}"));
+                app.play_errors.insert(id, reason);
+            }
+            // The optimistic swaps no longer match Music; take its order
+            AppEvent::ReorderFailed(playlist, err) => {
+                app.push_message(Level::Error, format!("Couldn't move track: {err}"));
+                refresh_playlist(&tx, playlist);
+            }
+            // Undo the optimistic rating
+            AppEvent::RatingFailed(previous, marks, err) => {
+                for (id, rating) in previous {
+                    app.apply_rating(&[id], rating);
+                }
+                app.restore_marks(&marks);
+                app.push_message(Level::Error, format!("Rating failed: {err}"));
+            }
+            AppEvent::OpinionFailed(previous, marks, err) => {
+                for (id, opinion) in previous {
+                    app.apply_opinion(&[id], opinion);
+                }
+                app.restore_marks(&marks);
+                app.push_message(Level::Error, err);
+            }
+            AppEvent::LyricsLoaded(track_id, key, lyrics) => {
+                app.lyrics_cache.insert(key.clone(), lyrics.clone());
+                if track_id == app.player.track_id && key == app.lyrics_key() {
+                    app.lyrics = lyrics;
+                    app.lyrics_for = key;
+                    app.lyrics_loading = false;
+                }
+            }
+            AppEvent::LyricsStored(track_id, stored) => {
+                if track_id == app.player.track_id && app.lyrics_editor.is_none() {
+                    open_lyrics_editor(&mut app, stored);
+                }
+            }
+            AppEvent::LyricsWritten(key, Ok(lyrics)) => {
+                app.lyrics_cache.insert(key.clone(), lyrics.clone());
+                if key == app.lyrics_key() {
+                    app.lyrics = lyrics;
+                    app.lyrics_for = key.clone();
+                    app.lyrics_cursor = None;
+                }
+                if let Some(editor) = app.lyrics_editor.take_if(|e| e.query.cache_key() == key) {
+                    app.push_message(Level::Success, format!("Saved lyrics for {}", editor.query.name));
+                }
+            }
+            AppEvent::LyricsWritten(key, Err(e)) => {
+                // Keep the editor open so the text isn't lost
+                if let Some(editor) = app.lyrics_editor.as_mut().filter(|e| e.query.cache_key() == key) {
+                    editor.saving = false;
+                }
+                app.push_message(Level::Error, format!("Couldn't save lyrics: {e}"));
+            }
+            AppEvent::PlaylistCover(key, img) => {
+                if key == app.playlist_cover_requested {
+                    app.playlist_cover = Some((key, img));
+                }
+            }
+            AppEvent::UpNextLoaded(track, next) => {
+                if track == app.artwork_track {
+                    // Warm the next cover so the track change shows it at once
+                    if let Some(n) = next.as_ref().filter(|_| app.capabilities.network.usable()) {
+                        request_cover(&n.name, &n.artist, &n.album, Priority::Prefetch, |_| {});
+                    }
+                    app.up_next = next;
+                }
+            }
         }
 
         if app.should_quit {
-            PersistedState::from_app(&app).save();
+            app.history.end_session();
+            if !app.safe_mode {
+                PersistedState::from_app(&app).save();
+            }
+            if let Some(action) = app.config.on_exit.action() {
+                perform(&mut app, action);
+            }
             break;
         }
     }
@@ -145,6 +750,50 @@ fn run(mut terminal: ratatui::DefaultTerminal) -> Result<()> {
 }
 
 fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<AppEvent>) {
+    // Ctrl+C quits from anywhere, even inside prompts
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.should_quit = true;
+        return;
+    }
+
+    // Any key wakes the screensaver, and does nothing else
+    app.last_input = Instant::now();
+    if app.screensaver {
+        app.screensaver = false;
+        return;
+    }
+
+    // Any key dismisses an open popup
+    if app.popup.is_some() {
+        app.popup = None;
+        return;
+    }
+
+    // Command prompt intercepts all keys until Enter/Esc
+    if app.command_mode {
+        match key.code {
+            KeyCode::Enter => {
+                app.command_mode = false;
+                let input = std::mem::take(&mut app.command_input);
+                match command::parse(&input) {
+                    Ok(cmd) => run_command(app, cmd, tx),
+                    Err(e) => app.push_message(Level::Error, e),
+                }
+            }
+            KeyCode::Esc => {
+                app.command_mode = false;
+                app.command_input.clear();
+            }
+            KeyCode::Backspace if app.command_input.is_empty() => app.command_mode = false,
+            KeyCode::Backspace => {
+                app.command_input.pop();
+            }
+            KeyCode::Char(c) => app.command_input.push(c),
+            _ => {}
+        }
+        return;
+    }
+
     // Search mode intercepts all keys — live filtering
     if app.search_mode {
         match key.code {
@@ -167,84 +816,467 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<
         return;
     }
 
-    // Shift+arrow: skip tracks via Apple Music native next/prev
+    if app.modal.is_some() {
+        handle_modal_key(app, key, tx);
+        return;
+    }
+
+    // Lyrics editor intercepts all keys until saved or cancelled
+    if let Some(ref mut editor) = app.lyrics_editor {
+        if editor.saving {
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('s') if ctrl => {
+                if !editor.modified() {
+                    app.lyrics_editor = None;
+                    app.notify("Lyrics unchanged");
+                } else if editor.stored.exists() {
+                    let name = editor.query.name.clone();
+                    app.modal = Some(Modal::Confirm {
+                        message: format!("Overwrite the existing lyrics of \"{name}\"?"),
+                        action: ConfirmAction::WriteLyrics,
+                    });
+                } else {
+                    save_lyrics(app, tx);
+                }
+            }
+            KeyCode::Esc => {
+                if editor.modified() {
+                    app.notify("Lyrics edit discarded");
+                }
+                app.lyrics_editor = None;
+            }
+            KeyCode::Char(c) if !ctrl => editor.insert(c),
+            KeyCode::Tab => editor.insert(' '),
+            KeyCode::Enter => editor.insert('\n'),
+            KeyCode::Backspace => editor.backspace(),
+            KeyCode::Delete => editor.delete(),
+            KeyCode::Left => editor.move_left(),
+            KeyCode::Right => editor.move_right(),
+            KeyCode::Up => editor.move_lines(-1),
+            KeyCode::Down => editor.move_lines(1),
+            KeyCode::PageUp => editor.move_lines(-10),
+            KeyCode::PageDown => editor.move_lines(10),
+            KeyCode::Home => editor.home(),
+            KeyCode::End => editor.end(),
+            _ => {}
+        }
+        return;
+    }
+
+    // BPM tap tool intercepts all keys until saved or cancelled
+    if let Some(ref mut tap) = app.bpm_tap {
+        match key.code {
+            KeyCode::Char('b') | KeyCode::Char(' ') => tap.tap(),
+            KeyCode::Char('r') => tap.taps.clear(),
+            KeyCode::Enter => {
+                if let Some(bpm) = tap.bpm() {
+                    let bpm = bpm.round() as u32;
+                    let (id, name) = (tap.target_id, tap.target_name.clone());
+                    let tx_bpm = tx.clone();
+                    thread::spawn(move || {
+                        let (level, msg) = match library::set_track_bpm(id, bpm) {
+                            Ok(()) => (Level::Success, format!("{name}: {bpm} BPM")),
+                            Err(e) => (Level::Error, format!("Couldn't save BPM: {e}")),
+                        };
+                        let _ = tx_bpm.send(AppEvent::Notify(level, msg));
+                    });
+                    app.bpm_tap = None;
+                }
+            }
+            KeyCode::Esc => app.bpm_tap = None,
+            _ => {}
+        }
+        return;
+    }
+
+    // Artwork chooser intercepts all keys until a cover is picked or cancelled
+    if let Some(ref mut chooser) = app.artwork_chooser {
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('k') => chooser.move_by(-1),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('j') => chooser.move_by(1),
+            KeyCode::Enter => {
+                if let Some(candidate) = chooser.selected_candidate() {
+                    let url = candidate.url.clone();
+                    let (track, artist, album) =
+                        (chooser.track_name.clone(), chooser.artist.clone(), chooser.album.clone());
+                    app.artwork_chooser = None;
+                    let pool = decode::pool();
+                    pool.cancel(Priority::Visible);
+                    app.playlist_cover_requested.clear();
+                    let key = artwork::cover_key(&track, &artist, &album);
+                    pool.forget(&key);
+                    let tx_art = tx.clone();
+                    pool.request(
+                        key,
+                        Priority::NowPlaying,
+                        move || {
+                            if let Err(e) = artwork::save_choice(&artist, &album, &url) {
+                                let _ = tx_art.send(AppEvent::Notify(Level::Error, format!("Couldn't save artwork choice: {e}")));
+                            }
+                            artwork::download_image(&url)
+                        },
+                        {
+                            let tx_art = tx.clone();
+                            move |img| {
+                                let _ = tx_art.send(AppEvent::ArtworkLoaded(track, img));
+                            }
+                        },
+                    );
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('w') | KeyCode::Char('q') => {
+                // Thumbnails still queued would only be thrown away; a cancelled
+                // playlist cover is asked for again
+                decode::pool().cancel(Priority::Visible);
+                app.playlist_cover_requested.clear();
+                app.artwork_chooser = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // Rating mode: a digit rates, anything else cancels
+    if app.rating_mode {
+        app.rating_mode = false;
+        if let KeyCode::Char(c @ '0'..='5') = key.code {
+            set_rating(app, c as u8 - b'0', tx);
+        }
+        return;
+    }
+
+    // Lyrics search intercepts all keys — jumps to matches as you type
+    if app.lyrics_search_mode {
+        match key.code {
+            KeyCode::Enter => app.lyrics_search_mode = false,
+            KeyCode::Esc => {
+                app.lyrics_search_mode = false;
+                app.lyrics_query.clear();
+            }
+            KeyCode::Backspace => {
+                app.lyrics_query.pop();
+                app.find_lyric(true, true);
+            }
+            KeyCode::Char(c) => {
+                app.lyrics_query.push(c);
+                app.find_lyric(true, true);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    // Shift+arrow: skip tracks via Apple Music native next/prev (after
+    // the prompts and dialogs above, which take arrows themselves)
     if key.modifiers.contains(KeyModifiers::SHIFT) {
         match key.code {
             KeyCode::Left => {
-                let _ = bridge::previous_track();
+                perform(app, Action::Previous);
                 return;
             }
             KeyCode::Right => {
-                let _ = bridge::next_track();
+                perform(app, Action::Next);
                 return;
             }
             _ => {}
         }
     }
 
-    // Library navigation keys (only when Library panel is active)
-    if app.active_panel == Panel::Library {
+    // Ctrl-h / Ctrl-l: move the panel divider (before h/l navigation sees them)
+    if key.modifiers.contains(KeyModifiers::CONTROL) && matches!(key.code, KeyCode::Char('h' | 'l')) {
+        resize_split(app, key.code == KeyCode::Char('l'));
+        return;
+    }
+
+    // Alt-1..9: jump to a tab (before digits become a count prefix)
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        if let KeyCode::Char(c @ '1'..='9') = key.code {
+            app.switch_tab(c as usize - '1' as usize);
+            return;
+        }
+    }
+
+    // Karaoke is full-screen: only exit and the global playback keys apply
+    let karaoke = app.karaoke;
+    if karaoke && matches!(key.code, KeyCode::Esc | KeyCode::Char('z')) {
+        app.karaoke = false;
+        return;
+    }
+    // So is the mini-player, which shows no panels
+    let panels_hidden = karaoke || app.mini_player;
+
+    // Lyrics navigation (Now Playing panel with lyrics shown)
+    if !panels_hidden && app.active_panel == Panel::NowPlaying && app.show_lyrics {
         match key.code {
-            KeyCode::Char('J') | KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.select_next_by(5);
+            KeyCode::Char('j') | KeyCode::Down => {
+                app.move_lyrics_cursor(1);
                 return;
             }
-            KeyCode::Char('K') | KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.select_previous_by(5);
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.move_lyrics_cursor(-1);
                 return;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                app.select_next();
+            KeyCode::Char('/') => {
+                app.lyrics_search_mode = true;
+                app.lyrics_query.clear();
                 return;
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                app.select_previous();
+            KeyCode::Char('n') if !app.lyrics_query.is_empty() => {
+                app.find_lyric(true, false);
                 return;
             }
-            // Right arrow / Enter / l: drill into playlist or play track
-            KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => {
-                match app.view {
-                    LibraryView::Playlists => {
-                        if let Some(playlist) = app.selected_playlist() {
-                            let name = playlist.name.clone();
-                            if let Some(cached) = app.track_cache.get(&name) {
-                                app.tracks = cached.clone();
-                                app.track_state.select(if app.tracks.is_empty() {
-                                    None
-                                } else {
-                                    Some(0)
-                                });
-                                app.view = LibraryView::Tracks;
-                            } else {
-                                app.loading = true;
-                                let tx_bg = tx.clone();
-                                std::thread::spawn(move || {
-                                    let tracks = library::fetch_playlist_tracks(&name).unwrap_or_default();
-                                    let _ = tx_bg.send(AppEvent::TracksLoaded(LibraryView::Tracks, name, tracks));
-                                });
-                            }
-                        }
-                    }
-                    LibraryView::Tracks => {
-                        if let Some(track) = app.selected_track() {
-                            let id = track.id;
-                            std::thread::spawn(move || {
-                                library::play_track_by_id(id);
-                            });
-                        }
-                    }
-                }
+            KeyCode::Char('N') if !app.lyrics_query.is_empty() => {
+                app.find_lyric(false, false);
                 return;
             }
-            // Left arrow / h / Esc: go back to playlists
-            KeyCode::Left | KeyCode::Esc | KeyCode::Char('h') => {
-                match app.view {
-                    LibraryView::Tracks => {
-                        app.view = LibraryView::Playlists;
-                        app.tracks.clear();
-                        app.track_state.select(None);
-                    }
-                    LibraryView::Playlists => {}
+            // Edit the playing track's lyrics
+            KeyCode::Char('e') => {
+                let track_id = app.player.track_id;
+                if track_id == 0 {
+                    app.notify("Nothing playing");
+                    return;
+                }
+                let tx_lyrics = tx.clone();
+                thread::spawn(move || {
+                    let _ = match lyrics::stored(track_id) {
+                        Ok(stored) => tx_lyrics.send(AppEvent::LyricsStored(track_id, stored)),
+                        Err(e) => tx_lyrics.send(AppEvent::Notify(Level::Error, format!("Couldn't read lyrics: {e}"))),
+                    };
+                });
+                return;
+            }
+            // Seek to the picked line (synced lyrics) and resume following playback
+            KeyCode::Enter => {
+                let time = app
+                    .lyrics_cursor
+                    .zip(app.lyrics.as_ref())
+                    .and_then(|(i, l)| l.lines.get(i))
+                    .and_then(|l| l.time);
+                if let Some(time) = time {
+                    perform(app, Action::Seek(time));
+                    app.lyrics_cursor = None;
+                }
+                return;
+            }
+            KeyCode::Esc if app.lyrics_cursor.is_some() || !app.lyrics_query.is_empty() => {
+                app.lyrics_cursor = None;
+                app.lyrics_query.clear();
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // Library navigation keys (only when Library panel is active)
+    if !panels_hidden && app.active_panel == Panel::Library {
+        // Vim-style count prefix, e.g. `10j`
+        if let KeyCode::Char(c @ '0'..='9') = key.code {
+            if c != '0' || app.pending_count.is_some() {
+                let digit = c as usize - '0' as usize;
+                let count = app.pending_count.unwrap_or(0);
+                app.pending_count = Some(count.saturating_mul(10).saturating_add(digit));
+                app.pending_g = false;
+                return;
+            }
+        }
+        let count = app.pending_count.take();
+        let pending_g = std::mem::take(&mut app.pending_g);
+        let half_page = (app.library_height as usize / 2).max(1);
+
+        // Column browser: the artist and album columns take the navigation
+        // keys; in the tracks column only going back is different
+        if let Some(browser) = app.browser.as_mut() {
+            let in_list = browser.focus != BrowserColumn::Tracks;
+            let changed = match key.code {
+                KeyCode::Char('j') | KeyCode::Down if in_list => browser.move_by(count.unwrap_or(1) as isize),
+                KeyCode::Char('k') | KeyCode::Up if in_list => browser.move_by(-(count.unwrap_or(1) as isize)),
+                KeyCode::Char('g') if in_list && pending_g => browser.select(0),
+                KeyCode::Char('g') if in_list => {
+                    app.pending_g = true;
+                    return;
+                }
+                KeyCode::Char('G') if in_list => browser.select(usize::MAX),
+                KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') if in_list => {
+                    browser.focus_right();
+                    return;
+                }
+                KeyCode::Left | KeyCode::Char('h') if browser.focus != BrowserColumn::Artists => {
+                    browser.focus_left();
+                    return;
+                }
+                KeyCode::Esc if browser.focus == BrowserColumn::Tracks && !app.marked.is_empty() => {
+                    app.clear_marks();
+                    return;
+                }
+                KeyCode::Esc if browser.focus == BrowserColumn::Tracks => {
+                    browser.focus_left();
+                    return;
+                }
+                KeyCode::Left | KeyCode::Char('h') | KeyCode::Esc | KeyCode::Char('C') => {
+                    toggle_browser(app, tx);
+                    return;
+                }
+                _ if in_list => return,
+                _ => false,
+            };
+            if changed {
+                app.show_browser_tracks();
+            }
+            if in_list {
+                return;
+            }
+        }
+
+        match key.code {
+            // In playlist order, J/K move the selected track instead of jumping
+            KeyCode::Char('J') if app.can_reorder() => {
+                move_selected_track(app, 1, tx);
+                return;
+            }
+            KeyCode::Char('K') if app.can_reorder() => {
+                move_selected_track(app, -1, tx);
+                return;
+            }
+            KeyCode::Char('J') | KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                app.select_next_by(5 * count.unwrap_or(1));
+                return;
+            }
+            KeyCode::Char('K') | KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                app.select_previous_by(5 * count.unwrap_or(1));
+                return;
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.select_next_by(half_page * count.unwrap_or(1));
+                return;
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.select_previous_by(half_page * count.unwrap_or(1));
+                return;
+            }
+            // Without a count j/k wrap around; with one they stop at the ends
+            KeyCode::Char('j') | KeyCode::Down => {
+                match count {
+                    Some(n) => app.select_next_by(n),
+                    None => app.select_next(),
+                }
+                return;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                match count {
+                    Some(n) => app.select_previous_by(n),
+                    None => app.select_previous(),
+                }
+                return;
+            }
+            // `gg` / `G` jump to the top / bottom, or to row N with a count
+            KeyCode::Char('g') if pending_g => {
+                app.select_index(count.map_or(0, |n| n.saturating_sub(1)));
+                return;
+            }
+            KeyCode::Char('g') => {
+                app.pending_g = true;
+                app.pending_count = count;
+                return;
+            }
+            KeyCode::Char('G') => {
+                app.select_index(count.map_or(usize::MAX, |n| n.saturating_sub(1)));
+                return;
+            }
+            // Right arrow / Enter / l: drill into playlist or play track
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => {
+                match app.view {
+                    LibraryView::Menu => {
+                        let item = app.menu_state.selected().and_then(|i| MENU.get(i).copied());
+                        match item {
+                            Some(MenuItem::Playlists) => app.view = LibraryView::Playlists,
+                            Some(MenuItem::Smart(list)) => load_list(app, tx, list),
+                            Some(MenuItem::Browser) => toggle_browser(app, tx),
+                            Some(MenuItem::History) => {
+                                app.view = LibraryView::History;
+                                app.select_history_start();
+                            }
+                            None => {}
+                        }
+                    }
+                    LibraryView::Playlists => {
+                        if let Some(playlist) = app.selected_playlist() {
+                            let id = playlist.id;
+                            app.note_recent_playlist(id);
+                            if let Some((_, cached)) = app.track_cache.get(&id) {
+                                app.open_search = None;
+                                app.open_list = None;
+                                app.set_tracks(cached.clone());
+                                app.view = LibraryView::Tracks;
+                                // Stale: show it anyway, refresh behind it
+                                if app.cache_is_stale(id) {
+                                    let tx_bg = tx.clone();
+                                    thread::spawn(move || {
+                                        if let Ok(tracks) = library::fetch_playlist_tracks(id) {
+                                            let _ = tx_bg.send(AppEvent::TracksRefreshed(id, tracks));
+                                        }
+                                    });
+                                }
+                            } else {
+                                app.loading = true;
+                                load_playlist(app, tx, id, Vec::new());
+                            }
+                        }
+                    }
+                    // With tracks marked, queue them up in list order
+                    LibraryView::Tracks if app.marked_count() > 0 => {
+                        let ids: Vec<i32> = app.target_tracks().iter().map(|t| t.id).collect();
+                        app.clear_marks();
+                        app.notify(format!("\u{25b6} Playing {} marked tracks", ids.len()));
+                        play_as_playlist(app, tx, QUEUE_PLAYLIST.to_string(), ids, "Couldn't play".to_string());
+                    }
+                    LibraryView::History => {
+                        let id = app
+                            .history_state
+                            .selected()
+                            .and_then(|i| app.history_rows().get(i).map(|e| e.id));
+                        if let Some(id) = id {
+                            let tx_play = tx.clone();
+                            thread::spawn(move || {
+                                if let Err(e) = library::play_track_by_id(id) {
+                                    let _ = tx_play.send(AppEvent::PlayFailed(id, e.to_string()));
+                                }
+                            });
+                        }
+                    }
+                    LibraryView::Tracks => {
+                        if let Some(track) = app.selected_track() {
+                            let id = track.id;
+                            let tx_play = tx.clone();
+                            std::thread::spawn(move || {
+                                if let Err(e) = library::play_track_by_id(id) {
+                                    let _ = tx_play.send(AppEvent::PlayFailed(id, e.to_string()));
+                                }
+                            });
+                        }
+                    }
+                }
+                return;
+            }
+            // Esc drops marks before it goes back
+            KeyCode::Esc if app.view == LibraryView::Tracks && app.clear_marks() => return,
+            // Left arrow / h / Esc: go back up, to the playlists or the menu
+            KeyCode::Left | KeyCode::Esc | KeyCode::Char('h') => {
+                match app.view {
+                    LibraryView::Tracks => {
+                        app.view = if app.open_list.take().is_some() {
+                            LibraryView::Menu
+                        } else {
+                            LibraryView::Playlists
+                        };
+                        app.open_search = None;
+                        app.tracks.clear();
+                        app.track_state.select(None);
+                    }
+                    LibraryView::Playlists | LibraryView::History => app.view = LibraryView::Menu,
+                    LibraryView::Menu => {}
                 }
                 return;
             }
@@ -252,6 +1284,147 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<
                 app.enter_search();
                 return;
             }
+            KeyCode::Char('C') => {
+                toggle_browser(app, tx);
+                return;
+            }
+            KeyCode::Char('H') if app.view == LibraryView::History => {
+                app.view = LibraryView::Playlists;
+                return;
+            }
+            KeyCode::Char('H') => {
+                app.browser = None;
+                app.view = LibraryView::History;
+                app.select_history_start();
+                return;
+            }
+            // c / r / d: create, rename, delete playlists
+            KeyCode::Char('c') if app.view == LibraryView::Playlists => {
+                app.modal = Some(Modal::Input {
+                    title: " New playlist ".to_string(),
+                    value: String::new(),
+                    action: InputAction::CreatePlaylist,
+                });
+                return;
+            }
+            KeyCode::Char('r') if app.view == LibraryView::Playlists => {
+                if let Some(playlist) = app.selected_playlist() {
+                    app.modal = Some(Modal::Input {
+                        title: " Rename playlist ".to_string(),
+                        value: playlist.name.clone(),
+                        action: InputAction::RenamePlaylist(playlist.clone()),
+                    });
+                }
+                return;
+            }
+            // r in a partly loaded playlist: fetch the tracks that are missing
+            KeyCode::Char('r') if app.open_incomplete().is_some() => {
+                let Some(incomplete) = app.incomplete.take() else { return };
+                load_playlist(app, tx, incomplete.playlist_id, incomplete.fetched);
+                return;
+            }
+            // R: fetch what the panel shows again, e.g. after editing in Music
+            KeyCode::Char('R') => {
+                refresh_view(app, tx);
+                return;
+            }
+            KeyCode::Char('d') if app.view == LibraryView::Playlists => {
+                if let Some(playlist) = app.selected_playlist() {
+                    app.modal = Some(Modal::Confirm {
+                        message: format!("Delete playlist \"{}\"?", playlist.name),
+                        action: ConfirmAction::DeletePlaylist(playlist.clone()),
+                    });
+                }
+                return;
+            }
+            // x in a library menu list: queue up the whole list in order
+            KeyCode::Char('x') if app.view == LibraryView::Tracks && app.open_list.is_some() => {
+                let ids: Vec<i32> = app.tracks.iter().map(|t| t.id).collect();
+                let label = app.open_list.map_or("", |l| l.label());
+                app.notify(format!("\u{25b6} Playing {label} ({} tracks)", ids.len()));
+                play_as_playlist(app, tx, QUEUE_PLAYLIST.to_string(), ids, "Couldn't play".to_string());
+                return;
+            }
+            // x / X: play the selected (or open) playlist in order / shuffled
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                if let Some(playlist) = app.selected_playlist() {
+                    let (id, name) = (playlist.id, playlist.name.clone());
+                    let shuffle = key.code == KeyCode::Char('X');
+                    if shuffle && app.config.shuffle_preview_min > 0 {
+                        let playlist = playlist.clone();
+                        shuffle_playlist(app, tx, playlist);
+                        return;
+                    }
+                    let tx_play = tx.clone();
+                    thread::spawn(move || {
+                        let (level, msg) = match library::play_playlist(id, shuffle) {
+                            Ok(()) if shuffle => (Level::Info, format!("\u{2921} Shuffling {name}")),
+                            Ok(()) => (Level::Info, format!("\u{25b6} Playing {name}")),
+                            Err(e) => (Level::Error, format!("Couldn't play {name}: {e}")),
+                        };
+                        let _ = tx_play.send(AppEvent::Notify(level, msg));
+                    });
+                }
+                return;
+            }
+            // Space / V: mark the selected track / start or finish a range
+            KeyCode::Char(' ') if app.view == LibraryView::Tracks => {
+                app.toggle_mark();
+                return;
+            }
+            KeyCode::Char('V') if app.view == LibraryView::Tracks => {
+                app.toggle_visual();
+                return;
+            }
+            // a: add the marked (or selected) tracks to a playlist picked from a list
+            KeyCode::Char('a') if app.view == LibraryView::Tracks => {
+                let targets = app.target_tracks();
+                if let Some(label) = targets_label(&targets) {
+                    let ids = targets.iter().map(|t| t.id).collect();
+                    app.modal = Some(Modal::PickPlaylist {
+                        title: format!(" Add {label} to "),
+                        filter: String::new(),
+                        selected: 0,
+                        action: PickAction::AddTracks(ids),
+                    });
+                }
+                return;
+            }
+            // Delete / Backspace: remove the marked (or selected) tracks from the open playlist
+            KeyCode::Delete | KeyCode::Backspace if app.view == LibraryView::Tracks => {
+                let playlist = app.open_playlist().cloned();
+                let targets = app.target_tracks();
+                if let (Some(playlist), Some(label)) = (playlist, targets_label(&targets)) {
+                    let track_ids = targets.iter().map(|t| t.id).collect();
+                    app.modal = Some(Modal::Confirm {
+                        message: format!("Remove {label} from {}?", playlist.name),
+                        action: ConfirmAction::RemoveTracks { playlist, track_ids },
+                    });
+                }
+                return;
+            }
+            // D: build a BPM/key-ordered mix starting from the selected track
+            KeyCode::Char('D') if app.view == LibraryView::Tracks => {
+                let Some(seed) = app.selected_track() else { return };
+                if seed.bpm == 0 {
+                    app.notify("Selected track has no BPM (tap one with b)");
+                    return;
+                }
+                let mix = mix::build_mix(seed, &app.tracks);
+                let name = format!("Mix: {}", seed.name);
+                let ids: Vec<i32> = mix.iter().map(|t| t.id).collect();
+                app.notify(format!("Building {name} ({} tracks)", ids.len()));
+                play_as_playlist(app, tx, name, ids, "Mix failed".to_string());
+                return;
+            }
+            KeyCode::Char('o') if app.view == LibraryView::Tracks => {
+                app.cycle_sort_key();
+                return;
+            }
+            KeyCode::Char('O') if app.view == LibraryView::Tracks => {
+                app.toggle_sort_direction();
+                return;
+            }
             _ => {}
         }
     }
@@ -259,21 +1432,31 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<
     // Global keys
     match key.code {
         KeyCode::Char('q') => app.should_quit = true,
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.should_quit = true;
-        }
         KeyCode::Char(' ') => {
-            let _ = bridge::toggle_playback();
+            perform(app, Action::TogglePlayback);
         }
         KeyCode::Char('+') | KeyCode::Char('=') => {
-            let step = if key.modifiers.contains(KeyModifiers::SHIFT) { 20 } else { 5 };
-            let _ = bridge::set_volume(app.player.volume.saturating_add(step).min(100));
+            let step = volume_step(app, key.modifiers);
+            change_volume(app, VolumeChange::By(step));
         }
         KeyCode::Char('-') => {
-            let step = if key.modifiers.contains(KeyModifiers::SHIFT) { 20 } else { 5 };
-            let _ = bridge::set_volume(app.player.volume.saturating_sub(step).max(0));
+            let step = volume_step(app, key.modifiers);
+            change_volume(app, VolumeChange::By(-step));
+        }
+        KeyCode::Char('M') => {
+            if app.muted_volume.is_none() && app.player.volume.is_muted() {
+                app.notify("Volume is already at 0");
+                return;
+            }
+            let previous = (app.player.volume, app.muted_volume);
+            let volume = app.toggle_mute();
+            if let Err(e) = Action::SetVolume(volume).perform(&app.player) {
+                (app.player.volume, app.muted_volume) = previous;
+                app.push_message(Level::Error, format!("Mute failed: {e}"));
+            }
         }
-        KeyCode::Char('s') => {
+        // The library (and its filter) isn't shown in karaoke or the mini-player
+        KeyCode::Char('s') if !panels_hidden => {
             if app.search_query.is_empty() {
                 app.enter_search();
             } else {
@@ -281,27 +1464,131 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<
             }
         }
         KeyCode::Char('m') => {
-            bridge::cycle_play_mode(&app.player);
+            perform(app, Action::CycleMode);
         }
         KeyCode::Char('n') => {
             app.mini_player = !app.mini_player;
         }
-        KeyCode::Char('f') => {
-            if !app.player.track_name.is_empty() {
-                let tx_save = tx.clone();
-                thread::spawn(move || {
-                    bridge::add_to_library();
-                    let _ = tx_save.send(AppEvent::TrackSaved);
-                });
+        KeyCode::Char('Z') => {
+            app.zoomed = !app.zoomed;
+        }
+        // Library tabs
+        KeyCode::Char('t') => app.new_tab(),
+        KeyCode::Char('T') => {
+            app.close_tab();
+        }
+        KeyCode::Char('{') => app.cycle_tab(false),
+        KeyCode::Char('}') => app.cycle_tab(true),
+        KeyCode::Char('A') => {
+            app.announce = !app.announce;
+            app.notify(if app.announce { "Announcements on" } else { "Announcements off" });
+        }
+        // Audio settings: EQ on/off and preset, crossfade
+        KeyCode::Char('E') => {
+            app.modal = Some(Modal::Audio { settings: None, selected: 0 });
+            let tx_audio = tx.clone();
+            thread::spawn(move || {
+                let settings = bridge::fetch_audio_settings();
+                if let Err(ref e) = settings {
+                    let _ = tx_audio.send(AppEvent::Notify(Level::Error, format!("Couldn't read audio settings: {e}")));
+                }
+                let _ = tx_audio.send(AppEvent::AudioSettingsLoaded(settings.ok()));
+            });
+        }
+        // BPM tap for the selected library track, or the playing one
+        KeyCode::Char('b') => {
+            let selected = app.selected_track().filter(|_| {
+                app.active_panel == Panel::Library && app.view == LibraryView::Tracks
+            });
+            let target = match selected {
+                Some(t) => Some((t.id, t.name.clone())),
+                None if app.player.track_id != 0 => {
+                    Some((app.player.track_id, app.player.track_name.clone()))
+                }
+                None => None,
+            };
+            if let Some((id, name)) = target {
+                app.bpm_tap = Some(BpmTap::new(id, name));
+            }
+        }
+        // Choose among the covers of the playing track's releases
+        KeyCode::Char('w') if !app.player.track_name.is_empty() => {
+            if let Some(reason) = app.capabilities.network.reason() {
+                app.push_message(Level::Error, format!("Artwork unavailable: {reason}"));
+                return;
             }
+            let (track, artist) = (app.player.track_name.clone(), app.player.artist.clone());
+            app.artwork_chooser = Some(ArtworkChooser::new(track.clone(), artist.clone(), app.player.album.clone()));
+            let tx_art = tx.clone();
+            thread::spawn(move || {
+                let candidates = artwork::fetch_candidates(&track, &artist);
+                let _ = tx_art.send(AppEvent::ArtworkCandidates(track, candidates));
+            });
+        }
+        // Turning lyrics off always works
+        KeyCode::Char('y') | KeyCode::Char('z')
+            if (key.code == KeyCode::Char('z') || !app.show_lyrics) && app.lyrics_unavailable().is_some() =>
+        {
+            let reason = app.lyrics_unavailable().unwrap_or_default();
+            app.push_message(Level::Error, format!("Lyrics unavailable: {reason}"));
+        }
+        KeyCode::Char('z') => {
+            app.karaoke = true;
+            request_lyrics(app, tx);
+        }
+        KeyCode::Char('y') => {
+            app.show_lyrics = !app.show_lyrics;
+            if app.show_lyrics {
+                request_lyrics(app, tx);
+            }
+        }
+        KeyCode::Char('f') if !app.player.track_name.is_empty() => {
+            let tx_save = tx.clone();
+            thread::spawn(move || {
+                let event = match bridge::add_to_library() {
+                    Ok(()) => AppEvent::TrackSaved,
+                    Err(e) => AppEvent::Notify(Level::Error, format!("Couldn't save to library: {e}")),
+                };
+                let _ = tx_save.send(event);
+            });
+        }
+        // F / U / !: favorite, unfavorite, dislike
+        KeyCode::Char('F') => set_opinion(app, Opinion::Favorite, tx),
+        KeyCode::Char('U') => set_opinion(app, Opinion::Neutral, tx),
+        KeyCode::Char('!') => set_opinion(app, Opinion::Dislike, tx),
+        KeyCode::Char('*') => app.rating_mode = true,
+        // Full metadata for the selected (or playing) track
+        KeyCode::Char('i') => {
+            let (_, ids) = edit_targets(app);
+            let Some(&id) = ids.first() else { return };
+            let play_error = app.play_errors.get(&id).cloned();
+            let tx_info = tx.clone();
+            thread::spawn(move || {
+                let event = match library::fetch_track_details(id) {
+                    Ok(details) => {
+                        let mut popup = track_details_popup(&details);
+                        if let Some(reason) = play_error {
+                            popup.lines.push(String::new());
+                            popup.lines.push(format!("\u{26a0} Last play failed: {reason}"));
+                        }
+                        AppEvent::ShowPopup(popup)
+                    }
+                    Err(e) => AppEvent::Notify(Level::Error, format!("Couldn't load track info: {e}")),
+                };
+                let _ = tx_info.send(event);
+            });
         }
         KeyCode::Left | KeyCode::Char('<') | KeyCode::Char(',') => {
-            let new_pos = (app.player.position - 5.0).max(0.0);
-            bridge::seek_to(new_pos);
+            perform(app, Action::Seek(app.player.position - 5.0));
         }
         KeyCode::Right | KeyCode::Char('>') | KeyCode::Char('.') => {
-            let new_pos = (app.player.position + 5.0).min(app.player.duration);
-            bridge::seek_to(new_pos);
+            perform(app, Action::Seek(app.player.position + 5.0));
+        }
+        KeyCode::Char('[') => seek_section(app, false),
+        KeyCode::Char(']') => seek_section(app, true),
+        KeyCode::Char(':') => {
+            app.command_mode = true;
+            app.command_input.clear();
         }
         KeyCode::Char('1') => app.active_panel = Panel::NowPlaying,
         KeyCode::Char('2') => app.active_panel = Panel::Library,
@@ -314,3 +1601,926 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<
         _ => {}
     }
 }
+
+fn handle_modal_key(app: &mut App, key: crossterm::event::KeyEvent, tx: &mpsc::Sender<AppEvent>) {
+    let pick_count = match &app.modal {
+        Some(Modal::PickPlaylist { filter, .. }) => app.picker_matches(filter).len(),
+        _ => 0,
+    };
+    let Some(modal) = app.modal.as_mut() else { return };
+    match modal {
+        Modal::Input { value, .. } => match key.code {
+            KeyCode::Enter => {
+                if let Some(Modal::Input { value, action, .. }) = app.modal.take() {
+                    let value = value.trim().to_string();
+                    if !value.is_empty() {
+                        run_input_action(action, value, tx);
+                    }
+                }
+            }
+            KeyCode::Esc => app.modal = None,
+            KeyCode::Backspace => {
+                value.pop();
+            }
+            KeyCode::Char(c) => value.push(c),
+            _ => {}
+        },
+        Modal::PickPlaylist { filter, selected, .. } => match key.code {
+            KeyCode::Enter => {
+                if let Some(Modal::PickPlaylist { filter, selected, action, .. }) = app.modal.take() {
+                    let playlist = app.picker_matches(&filter).get(selected).map(|&p| p.clone());
+                    if let Some(playlist) = playlist {
+                        run_pick_action(app, action, playlist, tx);
+                    }
+                }
+            }
+            KeyCode::Esc => app.modal = None,
+            KeyCode::Down => *selected = (*selected + 1).min(pick_count.saturating_sub(1)),
+            KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                filter.pop();
+                *selected = 0;
+            }
+            KeyCode::Char(c) => {
+                filter.push(c);
+                *selected = 0;
+            }
+            _ => {}
+        },
+        Modal::Confirm { .. } => match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                if let Some(Modal::Confirm { action, .. }) = app.modal.take() {
+                    run_confirm_action(app, action, tx);
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => app.modal = None,
+            _ => {}
+        },
+        Modal::ShufflePreview { tracks, .. } => match key.code {
+            KeyCode::Char('r') => shuffle::shuffle(tracks),
+            KeyCode::Enter | KeyCode::Char('x') | KeyCode::Char('X') => {
+                if let Some(Modal::ShufflePreview { playlist, tracks }) = app.modal.take() {
+                    let ids: Vec<i32> = tracks.iter().map(|t| t.id).collect();
+                    app.notify(format!("\u{2921} Shuffling {} ({} tracks)", playlist.name, ids.len()));
+                    let failed = format!("Couldn't play {}", playlist.name);
+                    play_as_playlist(app, tx, QUEUE_PLAYLIST.to_string(), ids, failed);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.modal = None,
+            _ => {}
+        },
+        Modal::Audio { settings, selected } => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('E') => app.modal = None,
+            KeyCode::Down | KeyCode::Char('j') => {
+                let rows = settings.as_ref().map_or(0, |s| s.presets.len() + AUDIO_PRESET_ROW);
+                *selected = (*selected + 1).min(rows.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            // Crossfade length, on the crossfade row
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') if *selected == 1 => {
+                let Some(crossfade) = settings.as_mut().and_then(|s| s.crossfade.as_mut()) else { return };
+                let seconds = if matches!(key.code, KeyCode::Left | KeyCode::Char('h')) {
+                    crossfade.seconds.saturating_sub(1)
+                } else {
+                    crossfade.seconds + 1
+                };
+                crossfade.seconds = seconds.clamp(bridge::Crossfade::MIN_SECONDS, bridge::Crossfade::MAX_SECONDS);
+                crossfade.enabled = true;
+                set_crossfade(*crossfade, tx);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if *selected == 1 => {
+                let Some(settings) = settings.as_mut() else { return };
+                let Some(crossfade) = settings.crossfade.as_mut() else {
+                    app.push_message(Level::Error, "Crossfade needs Accessibility permission for this terminal");
+                    return;
+                };
+                crossfade.enabled = !crossfade.enabled;
+                set_crossfade(*crossfade, tx);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(settings) = settings.as_mut() else { return };
+                // Row 0 toggles the EQ; picking a preset also turns it on
+                match selected.checked_sub(AUDIO_PRESET_ROW).and_then(|i| settings.presets.get(i)) {
+                    None => settings.eq_enabled = !settings.eq_enabled,
+                    Some(preset) => {
+                        settings.preset = preset.clone();
+                        settings.eq_enabled = true;
+                    }
+                }
+                let (enabled, preset) = (settings.eq_enabled, settings.preset.clone());
+                let tx_eq = tx.clone();
+                thread::spawn(move || {
+                    if let Err(e) = bridge::set_eq(enabled, &preset) {
+                        let _ = tx_eq.send(AppEvent::Notify(Level::Error, format!("Couldn't change the EQ: {e}")));
+                    }
+                });
+            }
+            _ => {}
+        },
+    }
+}
+
+/// Play `ids` in order through a playlist named `name` that cli-music owns,
+/// in the background. Errors are shown as "<failed>: <reason>".
+fn play_as_playlist(app: &App, tx: &mpsc::Sender<AppEvent>, name: String, ids: Vec<i32>, failed: String) {
+    let owned = app.generated_playlists.clone();
+    let tx = tx.clone();
+    thread::spawn(move || match library::play_tracks_as_playlist(&name, &ids, &owned) {
+        Ok(generated) => {
+            let _ = tx.send(AppEvent::PlaylistGenerated(generated));
+        }
+        Err(e) => {
+            let _ = tx.send(AppEvent::Notify(Level::Error, format!("{failed}: {e}")));
+        }
+    });
+}
+
+/// Apply a crossfade change in the background; it drives Music's settings
+/// window, which takes a moment.
+fn set_crossfade(crossfade: bridge::Crossfade, tx: &mpsc::Sender<AppEvent>) {
+    let tx = tx.clone();
+    thread::spawn(move || {
+        if let Err(e) = bridge::set_crossfade(crossfade) {
+            let _ = tx.send(AppEvent::Notify(Level::Error, format!("Couldn't change crossfade: {e}")));
+        }
+    });
+}
+
+fn run_input_action(action: InputAction, value: String, tx: &mpsc::Sender<AppEvent>) {
+    match action {
+        InputAction::CreatePlaylist => {
+            edit_playlists(tx, move || {
+                library::create_playlist(&value).map(|()| format!("Created {value}"))
+            });
+        }
+        InputAction::RenamePlaylist(playlist) => {
+            edit_playlists(tx, move || {
+                library::rename_playlist(playlist.id, &value).map(|()| format!("Renamed to {value}"))
+            });
+        }
+    }
+}
+
+fn run_pick_action(
+    app: &mut App,
+    action: PickAction,
+    playlist: library::PlaylistEntry,
+    tx: &mpsc::Sender<AppEvent>,
+) {
+    match action {
+        PickAction::AddTracks(ids) => {
+            app.clear_marks();
+            app.track_cache.remove(&playlist.id);
+            let tx_add = tx.clone();
+            thread::spawn(move || {
+                let name = &playlist.name;
+                let (level, msg) = match library::add_tracks_to_playlist(playlist.id, &ids) {
+                    Ok(()) if ids.len() == 1 => (Level::Success, format!("Added to {name}")),
+                    Ok(()) => (Level::Success, format!("Added {} tracks to {name}", ids.len())),
+                    Err(e) => (Level::Error, format!("Couldn't add to {name}: {e}")),
+                };
+                let _ = tx_add.send(AppEvent::Notify(level, msg));
+                let _ = tx_add.send(AppEvent::PlaylistEdited(playlist.id));
+            });
+        }
+    }
+}
+
+fn run_confirm_action(app: &mut App, action: ConfirmAction, tx: &mpsc::Sender<AppEvent>) {
+    match action {
+        ConfirmAction::RemoveTracks { playlist, track_ids } => {
+            // Optimistically drop the rows; the cache entry is stale either way
+            app.track_cache.remove(&playlist.id);
+            app.clear_marks();
+            app.tracks.retain(|t| !track_ids.contains(&t.id));
+            let len = app.tracks.len();
+            app.track_state
+                .select(app.track_state.selected().filter(|_| len > 0).map(|i| i.min(len - 1)));
+
+            let tx_remove = tx.clone();
+            thread::spawn(move || {
+                let name = &playlist.name;
+                let (level, msg) = match library::remove_tracks_from_playlist(playlist.id, &track_ids) {
+                    Ok(()) if track_ids.len() == 1 => (Level::Success, format!("Removed from {name}")),
+                    Ok(()) => (Level::Success, format!("Removed {} tracks from {name}", track_ids.len())),
+                    Err(e) => (Level::Error, format!("Couldn't remove from {name}: {e}")),
+                };
+                let _ = tx_remove.send(AppEvent::Notify(level, msg));
+                // Also brings back the rows if the removal failed
+                let _ = tx_remove.send(AppEvent::PlaylistEdited(playlist.id));
+            });
+        }
+        ConfirmAction::DeletePlaylist(playlist) => {
+            app.track_cache.remove(&playlist.id);
+            edit_playlists(tx, move || {
+                library::delete_playlist(playlist.id).map(|()| format!("Deleted {}", playlist.name))
+            });
+        }
+        ConfirmAction::WriteLyrics => save_lyrics(app, tx),
+    }
+}
+
+/// Move the selected track one row down (`delta` 1) or up (-1) in the open
+/// playlist. The list updates immediately and is rolled back if Music refuses
+/// the move (e.g. for smart or library playlists).
+fn move_selected_track(app: &mut App, delta: isize, tx: &mpsc::Sender<AppEvent>) {
+    let Some(i) = app.track_state.selected() else { return };
+    let Some(j) = i.checked_add_signed(delta).filter(|&j| j < app.tracks.len()) else { return };
+    let Some(playlist) = app.open_playlist().map(|p| p.id) else { return };
+    let (track, neighbor) = (&app.tracks[i], &app.tracks[j]);
+    let (id, neighbor_id) = (track.id, neighbor.id);
+    let after = track.position < neighbor.position;
+
+    app.swap_positions(playlist, id, neighbor_id);
+    let track_move = TrackMove { playlist, id, neighbor_id, after, tx: tx.clone() };
+    let _ = reorder_queue().lock().map(|queue| queue.send(track_move));
+}
+
+/// A J/K move on its way to Music.
+struct TrackMove {
+    playlist: i32,
+    id: i32,
+    neighbor_id: i32,
+    after: bool,
+    tx: mpsc::Sender<AppEvent>,
+}
+
+/// Moves run one at a time, in the order they were made, on a single worker:
+/// each one is relative to the list the previous ones left. When one fails,
+/// the moves already queued for that playlist are dropped, since they were
+/// made on top of it, and the playlist is fetched again.
+fn reorder_queue() -> &'static Mutex<mpsc::Sender<TrackMove>> {
+    static QUEUE: OnceLock<Mutex<mpsc::Sender<TrackMove>>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (queue, moves) = mpsc::channel::<TrackMove>();
+        thread::spawn(move || {
+            let mut pending: VecDeque<TrackMove> = VecDeque::new();
+            loop {
+                let Some(m) = pending.pop_front().or_else(|| moves.recv().ok()) else { return };
+                if let Err(e) = library::move_track(m.playlist, m.id, m.neighbor_id, m.after) {
+                    pending.extend(moves.try_iter());
+                    pending.retain(|later| later.playlist != m.playlist);
+                    let _ = m.tx.send(AppEvent::ReorderFailed(m.playlist, e.to_string()));
+                }
+            }
+        });
+        Mutex::new(queue)
+    })
+}
+
+/// Tracks a favorite or rating edit applies to: the marked (or selected)
+/// tracks when the track list is focused, otherwise the playing track.
+/// The flag says which of the two it is.
+fn edit_targets(app: &App) -> (bool, Vec<i32>) {
+    let in_tracks = app.active_panel == Panel::Library && app.view == LibraryView::Tracks;
+    let ids = if in_tracks {
+        app.target_tracks().iter().map(|t| t.id).collect()
+    } else if !app.player.track_name.is_empty() {
+        vec![app.player.track_id]
+    } else {
+        Vec::new()
+    };
+    (in_tracks, ids)
+}
+
+/// Set a 0–5 star rating on the marked (or selected) tracks when the track
+/// list is focused, otherwise the playing track.
+fn set_rating(app: &mut App, stars: u8, tx: &mpsc::Sender<AppEvent>) {
+    let (in_tracks, ids) = edit_targets(app);
+    if ids.is_empty() {
+        return;
+    }
+
+    let rating = stars * 20;
+    let previous = app.ratings_of(&ids);
+    let marks = if app.clear_marks() { ids.clone() } else { Vec::new() };
+    app.apply_rating(&ids, rating);
+    let tx_rating = tx.clone();
+    thread::spawn(move || {
+        let result = if in_tracks {
+            library::set_rating(&ids, rating)
+        } else {
+            bridge::set_current_rating(rating)
+        };
+        let event = match result {
+            Ok(()) if stars == 0 => AppEvent::Notify(Level::Success, "Rating cleared".to_string()),
+            Ok(()) => AppEvent::Notify(Level::Success, format!("Rated {}", "\u{2605}".repeat(stars as usize))),
+            Err(e) => AppEvent::RatingFailed(previous, marks, e.to_string()),
+        };
+        let _ = tx_rating.send(event);
+    });
+}
+
+/// Favorite, unfavorite or dislike the marked (or selected) tracks when the
+/// track list is focused, otherwise the playing track.
+fn set_opinion(app: &mut App, opinion: Opinion, tx: &mpsc::Sender<AppEvent>) {
+    let (in_tracks, ids) = edit_targets(app);
+    if ids.is_empty() {
+        return;
+    }
+
+    let previous = app.opinions_of(&ids);
+    let marks = if app.clear_marks() { ids.clone() } else { Vec::new() };
+    app.apply_opinion(&ids, opinion);
+    let verb = match opinion {
+        Opinion::Favorite => "Favorited",
+        Opinion::Neutral => "Cleared",
+        Opinion::Dislike => "Disliked",
+    };
+    let tx_opinion = tx.clone();
+    thread::spawn(move || {
+        let result = if in_tracks {
+            library::set_opinion(&ids, opinion)
+        } else {
+            bridge::set_current_opinion(opinion)
+        };
+        let event = match result {
+            Ok(()) if ids.len() == 1 => AppEvent::Notify(Level::Success, verb.to_string()),
+            Ok(()) => AppEvent::Notify(Level::Success, format!("{verb} {} tracks", ids.len())),
+            Err(e) => AppEvent::OpinionFailed(previous, marks, format!("{verb} failed: {e}")),
+        };
+        let _ = tx_opinion.send(event);
+    });
+}
+
+/// Run a player action, showing a toast if it fails.
+fn perform(app: &mut App, action: Action) {
+    if let Err(e) = action.perform(&app.player) {
+        app.push_message(Level::Error, format!("{} failed: {e}", action.label()));
+    }
+}
+
+/// The +/- step: `volume_step`, or four of them with Shift.
+fn volume_step(app: &App, modifiers: KeyModifiers) -> i32 {
+    let step = i32::from(app.config.volume_step);
+    if modifiers.contains(KeyModifiers::SHIFT) { step * 4 } else { step }
+}
+
+/// Set the volume, counting from the last one set here rather than the last
+/// poll, so quick repeated steps add up.
+fn change_volume(app: &mut App, change: VolumeChange) {
+    let volume = change.apply(app.player.volume);
+    if volume == app.player.volume {
+        return;
+    }
+    match Action::SetVolume(volume).perform(&app.player) {
+        Ok(()) => {
+            app.player.volume = volume;
+            app.muted_volume = None;
+            app.volume_set_at = Some(Instant::now());
+        }
+        Err(e) => app.push_message(Level::Error, format!("Volume failed: {e}")),
+    }
+}
+
+/// How a batch action names its tracks: the title of a single track, or a count.
+fn targets_label(targets: &[&library::TrackEntry]) -> Option<String> {
+    match targets {
+        [] => None,
+        [track] => Some(format!("\"{}\"", track.name)),
+        _ => Some(format!("{} tracks", targets.len())),
+    }
+}
+
+/// Run a playlist edit in the background, then report the outcome and
+/// reload the playlist list.
+fn edit_playlists<F>(tx: &mpsc::Sender<AppEvent>, edit: F)
+where
+    F: FnOnce() -> Result<String> + Send + 'static,
+{
+    let tx_edit = tx.clone();
+    thread::spawn(move || {
+        let (level, msg) = match edit() {
+            Ok(msg) => (Level::Success, msg),
+            Err(e) => (Level::Error, format!("Playlist edit failed: {e}")),
+        };
+        let _ = tx_edit.send(AppEvent::Notify(level, msg));
+        if let Ok(playlists) = library::fetch_playlists() {
+            let _ = tx_edit.send(AppEvent::PlaylistsLoaded(playlists));
+        }
+    });
+}
+
+/// Look up and decode a track's cover on the decode pool; covers are
+/// cached per album.
+fn request_cover(
+    track_name: &str,
+    artist: &str,
+    album: &str,
+    priority: Priority,
+    done: impl FnOnce(Option<image::DynamicImage>) + Send + 'static,
+) {
+    let key = artwork::cover_key(track_name, artist, album);
+    let (track_name, artist, album) = (track_name.to_string(), artist.to_string(), album.to_string());
+    let load = move || {
+        artwork::artwork_url(&track_name, &artist, &album).and_then(|url| artwork::download_image(&url))
+    };
+    decode::pool().request(key, priority, load, done);
+}
+
+/// Make sure the open playlist's cover is loaded or on its way: its own
+/// artwork, else a mosaic of its first tracks' covers in playlist order.
+fn request_playlist_cover(app: &mut App, tx: &mpsc::Sender<AppEvent>) {
+    let Some(id) = app.open_playlist().map(|p| p.id) else { return };
+    let tracks = app.track_cache.get(&id).map_or(&app.tracks, |(_, tracks)| tracks);
+    let sources = artwork::mosaic_sources(tracks);
+    let key = artwork::mosaic_key(id, &sources);
+    if key == app.playlist_cover_requested {
+        return;
+    }
+    app.playlist_cover_requested = key.clone();
+    let network = app.capabilities.network.usable();
+    let tx_cover = tx.clone();
+    let load_key = key.clone();
+    let load = move || artwork::playlist_cover(id, &load_key, &sources, network);
+    decode::pool().request(key.clone(), Priority::Visible, load, move |img| {
+        if let Some(img) = img {
+            let _ = tx_cover.send(AppEvent::PlaylistCover(key, img));
+        }
+    });
+}
+
+/// Fetch the tracks of a library menu list in the background.
+fn load_list(app: &mut App, tx: &mpsc::Sender<AppEvent>, list: SmartList) {
+    app.loading = true;
+    let months = app.config.forgotten_months;
+    let tx_bg = tx.clone();
+    thread::spawn(move || match list.fetch(months) {
+        Ok(tracks) => {
+            let _ = tx_bg.send(AppEvent::ListLoaded(list, tracks));
+        }
+        Err(e) => {
+            let msg = format!("Couldn't load {}: {e}", list.label());
+            let _ = tx_bg.send(AppEvent::Notify(Level::Error, msg));
+            let _ = tx_bg.send(AppEvent::ListLoaded(list, Vec::new()));
+        }
+    });
+}
+
+/// Probe internet access every `NETWORK_RECHECK` until it's back, so one
+/// offline launch doesn't keep artwork and online lyrics off all session.
+fn recheck_network(tx: mpsc::Sender<AppEvent>) {
+    thread::spawn(move || loop {
+        thread::sleep(capabilities::NETWORK_RECHECK);
+        if capabilities::probe_network() == Support::Available {
+            let _ = tx.send(AppEvent::NetworkRestored);
+            return;
+        }
+    });
+}
+
+/// Fetch a playlist's tracks again in the background; the open list is
+/// updated in place when they arrive.
+fn refresh_playlist(tx: &mpsc::Sender<AppEvent>, id: i32) {
+    let tx_bg = tx.clone();
+    thread::spawn(move || match library::fetch_playlist_tracks(id) {
+        Ok(tracks) => {
+            let _ = tx_bg.send(AppEvent::TracksRefreshed(id, tracks));
+        }
+        Err(e) => {
+            let msg = format!("Couldn't refresh tracks: {e}");
+            let _ = tx_bg.send(AppEvent::Notify(Level::Error, msg));
+        }
+    });
+}
+
+/// Re-fetch whatever the library panel shows, bypassing the caches.
+fn refresh_view(app: &mut App, tx: &mpsc::Sender<AppEvent>) {
+    if app.loading || app.load_progress.is_some() {
+        app.notify("Still loading");
+        return;
+    }
+    if app.browser.is_some() {
+        // Reopening reloads the whole library
+        app.browser = None;
+        toggle_browser(app, tx);
+        return;
+    }
+    match app.view {
+        LibraryView::Tracks => {
+            if let Some(query) = app.open_search.clone() {
+                app.search_cache.remove(&query);
+                search_library(app, &query, tx);
+            } else if let Some(list) = app.open_list {
+                load_list(app, tx, list);
+            } else if let Some(id) = app.open_playlist().map(|p| p.id) {
+                app.notify("Refreshing...");
+                refresh_playlist(tx, id);
+            }
+        }
+        LibraryView::Menu | LibraryView::Playlists => {
+            // Every cached track list may be out of date too
+            app.track_cache.clear();
+            app.notify("Refreshing playlists...");
+            let tx_bg = tx.clone();
+            thread::spawn(move || {
+                if let Ok(playlists) = library::fetch_playlists() {
+                    let _ = tx_bg.send(AppEvent::PlaylistsLoaded(playlists));
+                }
+            });
+        }
+        // Kept locally, so always current
+        LibraryView::History => {}
+    }
+}
+
+/// Fetch a playlist's tracks after `fetched` in the background, streaming
+/// each chunk into the open list as it arrives.
+fn load_playlist(app: &mut App, tx: &mpsc::Sender<AppEvent>, id: i32, fetched: Vec<TrackEntry>) {
+    app.load_progress = Some(LoadProgress {
+        playlist_id: id,
+        loaded: fetched.len(),
+        total: 0,
+    });
+    let tx_bg = tx.clone();
+    thread::spawn(move || {
+        let fetch = library::resume_playlist_tracks(id, fetched, |batch, total| {
+            let _ = tx_bg.send(AppEvent::TracksBatch(id, batch.to_vec(), total));
+        });
+        let _ = tx_bg.send(AppEvent::TracksLoaded(id, fetch));
+    });
+}
+
+/// Widen (or narrow) the Now Playing panel one step and save the new width
+/// to the config file.
+fn resize_split(app: &mut App, wider: bool) {
+    let current = app.split_percent.unwrap_or_else(|| ui::default_split(app.terminal_width));
+    let percent = if wider { current + SPLIT_STEP } else { current.saturating_sub(SPLIT_STEP) };
+    let percent = percent.clamp(SPLIT_MIN, SPLIT_MAX);
+    app.split_percent = Some(percent);
+    app.zoomed = false;
+    if app.safe_mode {
+        return;
+    }
+    app.config.split_percent = Some(percent);
+    if let Err(e) = config::Config::save_value("split_percent", &percent.to_string()) {
+        app.push_message(Level::Error, format!("Couldn't save the panel width: {e}"));
+    }
+}
+
+/// X on a playlist: preview a shuffled order first when it's long enough,
+/// fetching its tracks unless they're cached.
+fn shuffle_playlist(app: &mut App, tx: &mpsc::Sender<AppEvent>, playlist: library::PlaylistEntry) {
+    if let Some((_, tracks)) = app.track_cache.get(&playlist.id) {
+        let tracks = tracks.clone();
+        preview_shuffle(app, tx.clone(), playlist, tracks);
+        return;
+    }
+    app.notify(format!("Loading {} to shuffle\u{2026}", playlist.name));
+    let tx_bg = tx.clone();
+    thread::spawn(move || match library::fetch_playlist_tracks(playlist.id) {
+        Ok(tracks) => {
+            let _ = tx_bg.send(AppEvent::ShuffleTracks(playlist, tracks));
+        }
+        Err(e) => {
+            let msg = format!("Couldn't load {}: {e}", playlist.name);
+            let _ = tx_bg.send(AppEvent::Notify(Level::Error, msg));
+        }
+    });
+}
+
+/// Open the shuffle preview for a long playlist; shorter ones are shuffled
+/// by Music straight away.
+fn preview_shuffle(app: &mut App, tx: mpsc::Sender<AppEvent>, playlist: library::PlaylistEntry, mut tracks: Vec<TrackEntry>) {
+    if tracks.len() >= app.config.shuffle_preview_min {
+        shuffle::shuffle(&mut tracks);
+        app.modal = Some(Modal::ShufflePreview { playlist, tracks });
+        return;
+    }
+    thread::spawn(move || {
+        let (level, msg) = match library::play_playlist(playlist.id, true) {
+            Ok(()) => (Level::Info, format!("\u{2921} Shuffling {}", playlist.name)),
+            Err(e) => (Level::Error, format!("Couldn't play {}: {e}", playlist.name)),
+        };
+        let _ = tx.send(AppEvent::Notify(level, msg));
+    });
+}
+
+/// Open the Artists | Albums | Tracks column browser over the whole
+/// library, or close it and go back to the playlist list.
+fn toggle_browser(app: &mut App, tx: &mpsc::Sender<AppEvent>) {
+    if app.browser.take().is_some() {
+        app.view = LibraryView::Playlists;
+        app.tracks.clear();
+        app.track_state.select(None);
+        return;
+    }
+    app.loading = true;
+    let tx_bg = tx.clone();
+    thread::spawn(move || {
+        let tracks = library::library_playlist_id().and_then(|id| {
+            let tracks = library::fetch_playlist_tracks(id)?;
+            let _ = tx_bg.send(AppEvent::TracksPrefetched(id, tracks.clone()));
+            Ok(tracks)
+        });
+        let tracks = tracks.unwrap_or_else(|e| {
+            let _ = tx_bg.send(AppEvent::Notify(Level::Error, format!("Couldn't load the library: {e}")));
+            Vec::new()
+        });
+        let _ = tx_bg.send(AppEvent::BrowserLoaded(tracks));
+    });
+}
+
+/// Jump to the next or previous lyric section. Going back more than
+/// `SECTION_RESTART` seconds into a section restarts it, like previous-track.
+fn seek_section(app: &mut App, forward: bool) {
+    let sections = app.sections();
+    if sections.is_empty() {
+        app.notify("No sections for this track (they come from synced lyrics; press y to load them)");
+        return;
+    }
+    let position = app.player.position;
+    let target = if forward {
+        match sections.iter().find(|&&t| t > position + 0.5) {
+            Some(&t) => t,
+            None => {
+                app.notify("Already in the last section");
+                return;
+            }
+        }
+    } else {
+        sections
+            .iter()
+            .rev()
+            .find(|&&t| t < position - SECTION_RESTART)
+            .copied()
+            .unwrap_or(0.0)
+    };
+    perform(app, Action::Seek(target));
+}
+
+/// Load lyrics for the playing track if they aren't loaded or loading yet:
+/// from the in-memory cache, otherwise via the providers on a background thread.
+fn request_lyrics(app: &mut App, tx: &mpsc::Sender<AppEvent>) {
+    let key = app.lyrics_key();
+    if app.player.track_name.is_empty() || app.lyrics_loading || app.lyrics_for == key {
+        return;
+    }
+    if let Some(cached) = app.lyrics_cache.get(&key) {
+        app.lyrics = cached.clone();
+        app.lyrics_for = key;
+        return;
+    }
+
+    app.lyrics_loading = true;
+    let query = lyrics::LyricsQuery {
+        track_id: app.player.track_id,
+        name: app.player.track_name.clone(),
+        artist: app.player.artist.clone(),
+        album: app.player.album.clone(),
+        duration: app.player.duration,
+    };
+    let providers = app.usable_lyrics_providers();
+    let token = app.config.genius_token.clone();
+    let tx_lyrics = tx.clone();
+    thread::spawn(move || {
+        let found = lyrics::fetch(&query, &providers, token.as_deref());
+        let _ = tx_lyrics.send(AppEvent::LyricsLoaded(query.track_id, key, found));
+    });
+}
+
+/// Open the lyrics editor on the playing track's stored lyrics, or on the
+/// lyrics being shown (e.g. from LRCLIB) when it has none of its own.
+fn open_lyrics_editor(app: &mut App, stored: lyrics::StoredLyrics) {
+    let key = app.lyrics_key();
+    let shown = app.lyrics.as_ref().filter(|_| app.lyrics_for == key);
+    let text = match shown {
+        Some(l) if !stored.exists() => lyrics::to_lrc(l),
+        _ => stored.text().to_string(),
+    };
+    // The .lrc file is the local provider's; only write it if that's in use
+    let write_lrc = stored.lrc_path.is_some()
+        && (stored.lrc.is_some() || app.config.lyrics_providers.contains(&lyrics::Provider::Local));
+    let query = lyrics::LyricsQuery {
+        track_id: app.player.track_id,
+        name: app.player.track_name.clone(),
+        artist: app.player.artist.clone(),
+        album: app.player.album.clone(),
+        duration: app.player.duration,
+    };
+    app.lyrics_editor = Some(LyricsEditor::new(app.player.track_id, query, &text, stored, write_lrc));
+}
+
+/// Write the lyrics editor's text back to the track in the background.
+fn save_lyrics(app: &mut App, tx: &mpsc::Sender<AppEvent>) {
+    let Some(editor) = app.lyrics_editor.as_mut() else { return };
+    editor.saving = true;
+    let (id, query, text) = (editor.track_id, editor.query.clone(), editor.text());
+    let lrc_path = editor.stored.lrc_path.clone().filter(|_| editor.write_lrc);
+    let tx_lyrics = tx.clone();
+    thread::spawn(move || {
+        let result = lyrics::write(id, &query, &text, lrc_path.as_deref()).map_err(|e| e.to_string());
+        let _ = tx_lyrics.send(AppEvent::LyricsWritten(query.cache_key(), result));
+    });
+}
+
+/// Route pasted text to whatever is taking text input.
+fn handle_paste(app: &mut App, text: &str) {
+    app.last_input = Instant::now();
+    if let (Some(editor), None) = (app.lyrics_editor.as_mut(), &app.modal) {
+        if !editor.saving {
+            editor.insert_str(text);
+        }
+        return;
+    }
+    // Single-line inputs take the first line
+    let line = text.lines().next().unwrap_or("");
+    if app.command_mode {
+        app.command_input.push_str(line);
+    } else if app.search_mode {
+        app.search_query.push_str(line);
+        app.apply_search_filter();
+    } else if let Some(Modal::Input { value, .. }) = app.modal.as_mut() {
+        value.push_str(line);
+    }
+}
+
+/// Show library search results in the Tracks view. Cached results are shown
+/// straight away; stale ones are refreshed in the background.
+fn search_library(app: &mut App, query: &str, tx: &mpsc::Sender<AppEvent>) {
+    let query = app::normalize_query(query);
+    app.open_search = Some(query.clone());
+    app.open_list = None;
+    app.browser = None;
+    match app.search_cache.get(&query) {
+        Some((fetched_at, tracks)) => {
+            let fresh = fetched_at.elapsed() < app::SEARCH_CACHE_TTL;
+            app.set_tracks(tracks.clone());
+            app.view = LibraryView::Tracks;
+            app.active_panel = Panel::Library;
+            if fresh {
+                return;
+            }
+        }
+        None => app.loading = true,
+    }
+
+    let tx_search = tx.clone();
+    thread::spawn(move || {
+        let tracks = library::search_library(&query).map_err(|e| e.to_string());
+        let _ = tx_search.send(AppEvent::SearchLoaded(query, tracks));
+    });
+}
+
+fn run_command(app: &mut App, cmd: Command, tx: &mpsc::Sender<AppEvent>) {
+    match cmd {
+        Command::Artwork(path) => {
+            if let Some(reason) = app.capabilities.network.reason() {
+                app.push_message(Level::Error, format!("Artwork unavailable: {reason}"));
+                return;
+            }
+            if app.player.track_name.is_empty() {
+                app.notify("Nothing playing");
+                return;
+            }
+            let Some(path) =
+                path.or_else(|| artwork::default_export_path(&app.player.artist, &app.player.album))
+            else {
+                app.notify("No export directory");
+                return;
+            };
+            let track_name = app.player.track_name.clone();
+            let artist = app.player.artist.clone();
+            let album = app.player.album.clone();
+            let tx_export = tx.clone();
+            thread::spawn(move || {
+                let (level, msg) = match artwork::export_artwork(&track_name, &artist, &album, Some(&path)) {
+                    Ok(()) => (Level::Success, format!("Artwork saved to {}", path.display())),
+                    Err(e) => (Level::Error, format!("Artwork export failed: {e}")),
+                };
+                let _ = tx_export.send(AppEvent::Notify(level, msg));
+            });
+        }
+        Command::Search(query) => search_library(app, &query, tx),
+        Command::Volume(change) => change_volume(app, change),
+        Command::Features => {
+            let mut lines: Vec<String> = app
+                .capabilities
+                .list()
+                .into_iter()
+                .map(|(name, support)| match support {
+                    Support::Available => format!("\u{2713} {name}"),
+                    Support::Unknown => format!("? {name} (not checked)"),
+                    Support::Unavailable(reason) => format!("\u{2717} {name}: {reason}"),
+                })
+                .collect();
+            let m = itunes::client().metrics();
+            lines.push(String::new());
+            lines.push(format!(
+                "iTunes API: {} requests, {} deduplicated, {} throttled, {} failed",
+                m.requests, m.deduplicated, m.throttled, m.failed
+            ));
+            app.popup = Some(Popup {
+                title: " Features ".to_string(),
+                lines,
+            });
+        }
+        Command::Changelog => app.popup = Some(changelog::full()),
+        Command::SaveQueue(name) => {
+            let tx_save = tx.clone();
+            thread::spawn(move || {
+                let (level, msg) = match library::save_queue(name.as_deref()) {
+                    Ok(saved) => (Level::Success, format!("Saved {} tracks to {}", saved.count, saved.name)),
+                    Err(e) => (Level::Error, format!("Couldn't save the queue: {e}")),
+                };
+                let _ = tx_save.send(AppEvent::Notify(level, msg));
+                if let Ok(playlists) = library::fetch_playlists() {
+                    let _ = tx_save.send(AppEvent::PlaylistsLoaded(playlists));
+                }
+            });
+        }
+        Command::Session(SessionCommand::Start(name)) => {
+            app.history.start_session(&name);
+            app.notify(format!("Session started: {name}"));
+        }
+        Command::Session(SessionCommand::End) => match app.history.end_session() {
+            Some(name) => app.notify(format!("Session ended: {name}")),
+            None => app.notify("No active session"),
+        },
+        Command::Session(SessionCommand::Stats(name)) => {
+            let Some(session) = app.history.find_session(name.as_deref()) else {
+                app.notify("No such session");
+                return;
+            };
+            let stats = app.history.session_stats(session.id);
+            let mut lines = vec![
+                format!("Tracks played:  {} ({} unique)", stats.tracks, stats.unique_tracks),
+                format!("Time listened:  {}", format_duration(stats.listened_secs)),
+            ];
+            if !stats.top_artists.is_empty() {
+                lines.push(String::new());
+                lines.push("Top artists:".to_string());
+                for (artist, plays) in &stats.top_artists {
+                    lines.push(format!("  {artist} ({plays})"));
+                }
+            }
+            app.popup = Some(Popup {
+                title: format!(" Session: {} ", session.name),
+                lines,
+            });
+        }
+        Command::Session(SessionCommand::Play(name)) => {
+            let Some(session) = app.history.find_session(name.as_deref()) else {
+                app.notify("No such session");
+                return;
+            };
+            let ids: Vec<i32> = app
+                .history
+                .session_entries(session.id)
+                .iter()
+                .map(|e| e.id)
+                .filter(|&id| id != 0)
+                .collect();
+            if ids.is_empty() {
+                app.notify("Nothing was played in that session");
+                return;
+            }
+            let playlist = format!("Session: {}", session.name);
+            app.notify(format!("Replaying {playlist}"));
+            let failed = format!("Couldn't replay {playlist}");
+            play_as_playlist(app, tx, playlist, ids, failed);
+        }
+    }
+}
+
+/// Popup listing a track's metadata, skipping fields Music left empty.
+fn track_details_popup(details: &library::TrackDetails) -> Popup {
+    let date = |iso: &str| iso.get(..10).unwrap_or_default().to_string();
+    // Zero means Music doesn't know
+    let known = |n: u32, text: String| if n > 0 { text } else { String::new() };
+    let fields = [
+        ("Artist", details.artist.clone()),
+        ("Album", details.album.clone()),
+        ("Genre", details.genre.clone()),
+        ("Year", known(details.year, details.year.to_string())),
+        ("Composer", details.composer.clone()),
+        ("Bit rate", known(details.bit_rate, format!("{} kbps", details.bit_rate))),
+        (
+            "Sample rate",
+            known(details.sample_rate, format!("{:.1} kHz", details.sample_rate as f64 / 1000.0)),
+        ),
+        ("Plays", details.play_count.to_string()),
+        ("Added", date(&details.date_added)),
+        ("Last played", date(&details.played_date)),
+        ("Kind", details.kind.clone()),
+        ("Cloud status", details.cloud_status.replace('_', " ")),
+    ];
+    Popup {
+        title: format!(" {} ", details.name),
+        lines: fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(label, value)| format!("{:<13}{value}", format!("{label}:")))
+            .collect(),
+    }
+}
+
+/// Format seconds as "1h 23m" / "4m".
+fn format_duration(secs: f64) -> String {
+    let mins = (secs / 60.0).round() as u64;
+    if mins >= 60 {
+        format!("{}h {}m", mins / 60, mins % 60)
+    } else {
+        format!("{mins}m")
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Samples per second requested from the capture device. Plenty for a
+/// level meter and keeps the pipe tiny.
+const SAMPLE_RATE: usize = 8000;
+
+/// One level reading per this many samples (~50ms).
+const CHUNK_SAMPLES: usize = SAMPLE_RATE / 20;
+
+/// Levels at or below this are drawn as silence.
+const FLOOR_DB: f32 = -60.0;
+
+/// How long the peak marker holds before falling back to the live level.
+const PEAK_HOLD: Duration = Duration::from_millis(1200);
+
+/// Live output level with peak hold, both 0.0–1.0.
+#[derive(Debug, Clone)]
+pub struct LevelMeter {
+    pub level: f32,
+    pub peak: f32,
+    peak_at: Instant,
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self {
+            level: 0.0,
+            peak: 0.0,
+            peak_at: Instant::now(),
+        }
+    }
+}
+
+impl LevelMeter {
+    /// Record a new reading, raising the peak or letting it fall once the
+    /// hold time has passed.
+    pub fn update(&mut self, level: f32) {
+        self.level = level;
+        if level >= self.peak || self.peak_at.elapsed() > PEAK_HOLD {
+            self.peak = level;
+            self.peak_at = Instant::now();
+        }
+    }
+
+    /// Render as a `width`-cell bar with a `|` peak marker.
+    pub fn render(&self, width: usize) -> String {
+        let filled = (self.level * width as f32).round() as usize;
+        let peak = ((self.peak * width as f32).round() as usize).min(width);
+        (0..width)
+            .map(|i| {
+                if i < filled {
+                    '\u{25ae}'
+                } else if peak > 0 && i == peak - 1 {
+                    '|'
+                } else {
+                    '\u{25af}'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Capture audio from `device` with ffmpeg and call `on_level` with a
+/// 0.0–1.0 level roughly 20 times a second. Blocks until capture ends.
+///
+/// macOS doesn't expose the system output for capture, so `device` is an
+/// AVFoundation input — typically a loopback device such as "BlackHole 2ch"
+/// that the output is mirrored to.
+pub fn capture_levels(device: &str, mut on_level: impl FnMut(f32)) -> std::io::Result<()> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "quiet", "-f", "avfoundation", "-i"])
+        .arg(format!(":{device}"))
+        .args(["-ac", "1", "-ar", &SAMPLE_RATE.to_string(), "-f", "s16le", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+
+    let mut buf = vec![0u8; CHUNK_SAMPLES * 2];
+    while stdout.read_exact(&mut buf).is_ok() {
+        let sum: f64 = buf
+            .chunks_exact(2)
+            .map(|b| {
+                let s = i16::from_le_bytes([b[0], b[1]]) as f64 / i16::MAX as f64;
+                s * s
+            })
+            .sum();
+        let rms = (sum / CHUNK_SAMPLES as f64).sqrt() as f32;
+        let db = 20.0 * rms.max(1e-6).log10();
+        on_level(((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0));
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::library::TrackEntry;
+
+/// A musical key on the Camelot wheel: 1–12 plus minor (A) or major (B).
+/// Neighbouring numbers and the same number in the other mode mix well.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camelot {
+    pub number: u8,
+    pub major: bool,
+}
+
+impl Camelot {
+    /// Parse a key from a track's comment or grouping field.
+    ///
+    /// Accepts a Camelot code anywhere in the text ("8A - Energy 6") or a field
+    /// holding only a musical key ("F#m", "Bb major", "C").
+    pub fn parse(field: &str) -> Option<Self> {
+        let field = field.trim();
+        field
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find_map(Self::parse_code)
+            .or_else(|| Self::parse_musical(field))
+    }
+
+    /// Parse "8A" / "12b".
+    fn parse_code(token: &str) -> Option<Self> {
+        let letter = token.chars().last()?;
+        let number: u8 = token[..token.len() - 1].parse().ok()?;
+        let major = match letter.to_ascii_uppercase() {
+            'A' => false,
+            'B' => true,
+            _ => return None,
+        };
+        (1..=12).contains(&number).then_some(Self { number, major })
+    }
+
+    /// Parse "F#m", "Bb major", "Ebmin", "C".
+    fn parse_musical(field: &str) -> Option<Self> {
+        let mut chars = field.chars();
+        let mut pitch: i32 = match chars.next()?.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+        let mut rest = chars.as_str();
+        if let Some(r) = rest.strip_prefix(['#', '\u{266f}']) {
+            pitch += 1;
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix(['b', '\u{266d}']) {
+            pitch -= 1;
+            rest = r;
+        }
+        let major = match rest.trim().to_lowercase().as_str() {
+            "" | "maj" | "major" => true,
+            "m" | "min" | "minor" => false,
+            _ => return None,
+        };
+        // A minor key sits at the same wheel position as its relative major
+        let pitch = (pitch + if major { 0 } else { 3 }).rem_euclid(12);
+        let number = ((pitch * 7) % 12 + 7) % 12 + 1;
+        Some(Self {
+            number: number as u8,
+            major,
+        })
+    }
+
+    /// Steps between two keys on the wheel (0 = same key).
+    pub fn distance(self, other: Self) -> u32 {
+        let diff = (self.number as i32 - other.number as i32).rem_euclid(12);
+        let around = diff.min(12 - diff) as u32;
+        around + u32::from(self.major != other.major)
+    }
+}
+
+/// Tempo difference in percent, treating half/double time as a match.
+fn bpm_distance(a: u32, b: u32) -> f64 {
+    let (a, b) = (a as f64, b as f64);
+    let diff = (a - b).abs().min((a * 2.0 - b).abs()).min((a - b * 2.0).abs());
+    diff / a.max(b) * 100.0
+}
+
+/// Cost of following `from` with `to`: tempo change plus key clash.
+fn transition_cost(from: &TrackEntry, to: &TrackEntry) -> f64 {
+    let key_cost = match (from.key, to.key) {
+        (Some(a), Some(b)) => a.distance(b) as f64 * 4.0,
+        // Unknown keys get a middling penalty so known-compatible keys win
+        _ => 4.0,
+    };
+    bpm_distance(from.bpm, to.bpm) + key_cost
+}
+
+/// Order `pool` into a mix starting at `seed`, greedily picking the track
+/// that flows best from the previous one. Tracks without a BPM are left out.
+pub fn build_mix(seed: &TrackEntry, pool: &[TrackEntry]) -> Vec<TrackEntry> {
+    let mut remaining: Vec<&TrackEntry> = pool
+        .iter()
+        .filter(|t| t.bpm > 0 && t.id != seed.id)
+        .collect();
+    let mut mix = vec![seed.clone()];
+
+    while !remaining.is_empty() {
+        let last = &mix[mix.len() - 1];
+        let (best, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (i, transition_cost(last, t)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("remaining is not empty");
+        mix.push(remaining.swap_remove(best).clone());
+    }
+    mix
+}
@@ -0,0 +1,209 @@
+use color_eyre::{eyre::eyre, Result};
+use serde_json::json;
+use std::net::ToSocketAddrs;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::action::Action;
+use crate::volume::Volume;
+
+/// How long a request waits for the UI loop to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What a remote client asked for. Actions are performed by the UI loop,
+/// exactly as if the matching key had been pressed.
+#[derive(Debug, Clone, Copy)]
+pub enum Request {
+    Action(Action),
+    Status,
+    Queue,
+}
+
+/// JSON body on success, error message otherwise.
+pub type Reply = std::result::Result<serde_json::Value, String>;
+
+/// Start the remote-control server on `addr` (e.g. "0.0.0.0:8723").
+/// `forward` hands each request to the UI loop along with the channel its
+/// reply goes back on. With a `token`, clients must send it as
+/// `Authorization: Bearer <token>`; without one, only loopback addresses
+/// are served, and only to requests that name a loopback host and don't
+/// come from a web page (see `local_client`).
+pub fn serve<F>(addr: &str, token: Option<String>, forward: F) -> Result<()>
+where
+    F: Fn(Request, mpsc::Sender<Reply>) + Send + 'static,
+{
+    let token = token.filter(|t| !t.is_empty());
+    if token.is_none() && !is_loopback(addr)? {
+        return Err(eyre!("{addr} is reachable from other machines; set remote_token"));
+    }
+    let server = Server::http(addr).map_err(|e| eyre!("Can't listen on {addr}: {e}"))?;
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            handle(request, token.as_deref(), &forward);
+        }
+    });
+    Ok(())
+}
+
+fn handle<F>(request: tiny_http::Request, token: Option<&str>, forward: &F)
+where
+    F: Fn(Request, mpsc::Sender<Reply>),
+{
+    let authorized = match token {
+        Some(token) => bearer_token(request.headers()).is_some_and(|sent| same_token(sent, token)),
+        None => local_client(request.headers()),
+    };
+    if !authorized {
+        respond(request, 401, json!({ "error": "unauthorized" }));
+        return;
+    }
+
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let param = |name: &str| {
+        query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            (key == name).then(|| urlencoding::decode(value).map(|v| v.into_owned()).ok())?
+        })
+    };
+
+    let post = *request.method() == Method::Post;
+    let route = match (path, post) {
+        ("/status", false) => Some(Request::Status),
+        ("/queue", false) => Some(Request::Queue),
+        ("/play", true) => Some(Request::Action(Action::Play)),
+        ("/pause", true) => Some(Request::Action(Action::Pause)),
+        ("/toggle", true) => Some(Request::Action(Action::TogglePlayback)),
+        ("/next", true) => Some(Request::Action(Action::Next)),
+        ("/prev", true) => Some(Request::Action(Action::Previous)),
+        ("/volume", true) => {
+            let level = param("level").and_then(|l| l.parse::<i32>().ok());
+            let delta = param("delta").and_then(|d| d.parse::<i32>().ok());
+            match (level, delta) {
+                (Some(level), _) => Some(Request::Action(Action::SetVolume(Volume::new(level)))),
+                (None, Some(delta)) => Some(Request::Action(Action::ChangeVolume(delta))),
+                (None, None) => {
+                    let error = "volume needs ?level=0-100 or ?delta=N";
+                    respond(request, 400, json!({ "error": error }));
+                    return;
+                }
+            }
+        }
+        _ => None,
+    };
+    let Some(route) = route else {
+        let known = ["/status", "/queue", "/play", "/pause", "/toggle", "/next", "/prev", "/volume"];
+        let (code, error) = if known.contains(&path) {
+            (405, "wrong method (GET for /status and /queue, POST otherwise)")
+        } else {
+            (404, "not found")
+        };
+        respond(request, code, json!({ "error": error }));
+        return;
+    };
+
+    let (reply_tx, reply_rx) = mpsc::channel();
+    forward(route, reply_tx);
+    match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+        Ok(Ok(body)) => respond(request, 200, body),
+        Ok(Err(error)) => respond(request, 500, json!({ "error": error })),
+        Err(_) => respond(request, 503, json!({ "error": "cli-music is busy" })),
+    }
+}
+
+/// The token sent as `Authorization: Bearer <token>`, if any.
+pub fn bearer_token(headers: &[Header]) -> Option<&str> {
+    headers
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+}
+
+/// Whether a request without a token may be served: it names a loopback
+/// `Host` and carries no `Origin`. Browsers send `Origin` with cross-site
+/// requests, and a DNS-rebound page still sends its own host name, so
+/// neither a web page nor a rebinding attack gets through.
+pub fn local_client(headers: &[Header]) -> bool {
+    let header = |name: &'static str| headers.iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str());
+    header("Origin").is_none() && header("Host").is_some_and(loopback_host)
+}
+
+/// Whether a `Host` header value (with or without a port) names this
+/// machine: `localhost` or a loopback IP.
+pub fn loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        // [::1]:8723
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether every address `addr` resolves to is a loopback one.
+pub fn is_loopback(addr: &str) -> Result<bool> {
+    let mut addrs = addr.to_socket_addrs().map_err(|e| eyre!("Bad address {addr}: {e}"))?.peekable();
+    if addrs.peek().is_none() {
+        return Err(eyre!("Bad address {addr}"));
+    }
+    Ok(addrs.all(|a| a.ip().is_loopback()))
+}
+
+/// Compare a sent token with ours in time that doesn't depend on where
+/// they differ.
+pub fn same_token(sent: &str, token: &str) -> bool {
+    let (sent, token) = (sent.as_bytes(), token.as_bytes());
+    sent.len() == token.len() && sent.iter().zip(token).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn respond(request: tiny_http::Request, code: u16, body: serde_json::Value) {
+    let content_type = Header::from_bytes("Content-Type", "application/json").ok();
+    let mut response = Response::from_string(body.to_string()).with_status_code(code);
+    if let Some(header) = content_type {
+        response = response.with_header(header);
+    }
+    let _ = request.respond(response);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<Header> {
+        pairs.iter().map(|(k, v)| Header::from_bytes(*k, *v).unwrap()).collect()
+    }
+
+    #[test]
+    fn loopback_hosts() {
+        assert!(loopback_host("localhost"));
+        assert!(loopback_host("LOCALHOST:8723"));
+        assert!(loopback_host("127.0.0.1:8723"));
+        assert!(loopback_host("127.1.2.3"));
+        assert!(loopback_host("[::1]:8723"));
+        assert!(!loopback_host("evil.example:8723"));
+        assert!(!loopback_host("192.168.1.2:8723"));
+        assert!(!loopback_host("localhost.evil.example"));
+        assert!(!loopback_host(""));
+    }
+
+    #[test]
+    fn local_clients() {
+        assert!(local_client(&headers(&[("Host", "127.0.0.1:8723")])));
+        assert!(!local_client(&headers(&[])));
+        assert!(!local_client(&headers(&[("Host", "rebound.example:8723")])));
+        let from_page = headers(&[("Host", "127.0.0.1:8723"), ("Origin", "https://evil.example")]);
+        assert!(!local_client(&from_page));
+    }
+
+    #[test]
+    fn tokens() {
+        assert!(same_token("secret", "secret"));
+        assert!(!same_token("secreT", "secret"));
+        assert!(!same_token("secret2", "secret"));
+        let sent = headers(&[("Authorization", "Bearer secret")]);
+        assert_eq!(bearer_token(&sent), Some("secret"));
+        assert_eq!(bearer_token(&headers(&[("Authorization", "Basic secret")])), None);
+    }
+}
@@ -0,0 +1,6 @@
+(function() {
+    var pl = app.userPlaylists.byId({{playlist}});
+    eachLibraryTrack({{tracks}}, function(t) {
+        app.duplicate(t, {to: pl});
+    });
+})()
@@ -0,0 +1,8 @@
+(function() {
+    var current = app.currentEQPreset();
+    return JSON.stringify({
+        eqEnabled: app.eqEnabled(),
+        preset: current ? current.name() : "",
+        presets: app.eqPresets.name()
+    });
+})()
@@ -0,0 +1 @@
+app.make({new: 'playlist', withProperties: {name: {{name}}}});
@@ -0,0 +1,13 @@
+// Crossfade isn't in Music's scripting dictionary, so read it from the
+// Playback pane of Music's settings through System Events. Needs
+// Accessibility permission for the terminal.
+{{helpers}}
+
+(function() {
+    var pane = openPlaybackSettings();
+    try {
+        return JSON.stringify(readCrossfade(pane));
+    } finally {
+        closeSettings(pane);
+    }
+})()
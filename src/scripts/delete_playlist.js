@@ -0,0 +1 @@
+app.userPlaylists.byId({{playlist}}).delete();
@@ -0,0 +1,4 @@
+(function() {
+    var t = currentTrack();
+    {{edit}}
+})()
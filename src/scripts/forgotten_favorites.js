@@ -0,0 +1,24 @@
+(function() {
+    var tracks = app.libraryPlaylists[0].tracks;
+    var played = tracks.playedDate();
+    var rows = trackRows(tracks);
+    var month = 30 * 24 * 3600 * 1000;
+    var now = Date.now();
+    var cutoff = now - {{months}} * month;
+    var picks = [];
+    for (var i = 0; i < rows.length; i++) {
+        if (!played[i] || played[i].getTime() > cutoff) {
+            continue;
+        }
+        var stars = rows[i].rating / 20;
+        if (stars < 4 && rows[i].playCount < {{min_plays}}) {
+            continue;
+        }
+        // Loved and often played counts most; being long forgotten adds a little
+        var monthsAgo = Math.min((now - played[i].getTime()) / month, 36);
+        var score = stars * 2 + Math.log(1 + rows[i].playCount) * 2 + monthsAgo / 6;
+        picks.push({ index: i, score: score });
+    }
+    picks.sort(function(a, b) { return b.score - a.score; });
+    return JSON.stringify(picks.slice(0, {{limit}}).map(function(p) { return rows[p.index]; }));
+})()
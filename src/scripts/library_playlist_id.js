@@ -0,0 +1 @@
+app.libraryPlaylists[0].id()
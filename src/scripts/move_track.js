@@ -0,0 +1,6 @@
+(function() {
+    var pl = app.userPlaylists.byId({{playlist}});
+    var track = pl.tracks.whose({id: {{track}}})[0];
+    var anchor = pl.tracks.whose({id: {{anchor}}})[0];
+    app.move(track, {to: {{after}} ? anchor.after : anchor.before});
+})()
@@ -0,0 +1,8 @@
+(function() {
+    var devs = app.currentAirPlayDevices();
+    var names = [];
+    for (var i = 0; i < devs.length; i++) {
+        names.push(devs[i].name());
+    }
+    return JSON.stringify(names);
+})()
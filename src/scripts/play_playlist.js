@@ -0,0 +1,2 @@
+app.shuffleEnabled = {{shuffle}};
+app.playlists.byId({{playlist}}).play();
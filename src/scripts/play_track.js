@@ -0,0 +1,29 @@
+(function() {
+    var t = trackById({{track}});
+    var status = '';
+    try { status = t.cloudStatus(); } catch (e) {}
+    if (['removed', 'no longer available', 'ineligible', 'error'].indexOf(status) >= 0) {
+        throw new Error('Not available (' + status + ')');
+    }
+    // Only local file tracks have a location to lose
+    var location = null;
+    try { location = t.location(); } catch (e) {}
+    if (t.class() === 'fileTrack' && location === null) {
+        throw new Error('File is missing');
+    }
+    t.play();
+    // Streaming tracks can buffer for a while: wait up to 5s for Music to
+    // show the track, and only fail if it stopped or moved on to another
+    var state = '', current = null;
+    for (var i = 0; i < 20; i++) {
+        delay(0.25);
+        state = app.playerState();
+        try { current = app.currentTrack.id(); } catch (e) { current = null; }
+        if (current === {{track}} && state !== 'stopped') {
+            return;
+        }
+    }
+    if (state === 'stopped' || (current !== null && current !== {{track}})) {
+        throw new Error('Music did not start the track');
+    }
+})()
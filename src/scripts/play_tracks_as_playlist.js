@@ -0,0 +1,32 @@
+(function() {
+    var base = {{name}};
+    // Replace only playlists cli-music made itself ({{owned}}, by persistent
+    // id) under this name; a user's playlist of the same name is kept, and
+    // the new one gets a free " (2)"-style name instead
+    var owned = {{owned}};
+    var gone = [];
+    for (var i = 0; i < owned.length; i++) {
+        var matches = app.userPlaylists.whose({persistentID: owned[i]})();
+        if (matches.length === 0) {
+            gone.push(owned[i]);
+            continue;
+        }
+        var old = matches[0].name();
+        if (old === base || old.indexOf(base + ' (') === 0) {
+            matches[0].delete();
+            gone.push(owned[i]);
+        }
+    }
+    var name = base;
+    for (var n = 2; app.userPlaylists.whose({name: name})().length > 0; n++) {
+        name = base + ' (' + n + ')';
+    }
+    var pl = app.make({new: 'playlist', withProperties: {name: name}});
+    eachLibraryTrack({{tracks}}, function(t) {
+        app.duplicate(t, {to: pl});
+    });
+    // The order is the point; Music's shuffle would undo it
+    app.shuffleEnabled = false;
+    pl.play();
+    return JSON.stringify({ persistentId: pl.persistentID(), gone: gone });
+})()
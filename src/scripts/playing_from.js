@@ -0,0 +1,10 @@
+(function() {
+    if (app.playerState() === 'stopped') {
+        return JSON.stringify(null);
+    }
+    try {
+        return JSON.stringify({ playlist: app.currentPlaylist().id(), track: app.currentTrack().id() });
+    } catch (e) {
+        return JSON.stringify(null);
+    }
+})()
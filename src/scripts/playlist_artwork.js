@@ -0,0 +1,28 @@
+// Write the playlist's own artwork to {{path}}. Prints "1" when it has
+// some, nothing when it doesn't (or Music doesn't expose playlist artwork).
+(function() {
+    var matches = app.playlists.whose({id: {{playlist}}});
+    if (matches.length === 0) {
+        throw new Error('Playlist not found');
+    }
+    var artworks;
+    try {
+        artworks = matches[0].artworks;
+        if (artworks.length === 0) {
+            return '';
+        }
+    } catch (e) {
+        return '';
+    }
+    var data = artworks[0].rawData();
+    var sa = Application.currentApplication();
+    sa.includeStandardAdditions = true;
+    var file = sa.openForAccess(Path({{path}}), {writePermission: true});
+    try {
+        sa.setEof(file, {to: 0});
+        sa.write(data, {to: file});
+    } finally {
+        sa.closeAccess(file);
+    }
+    return '1';
+})()
@@ -0,0 +1,9 @@
+(function() {
+    var tracks = app.playlists.byId({{playlist}}).tracks;
+    // Indexes are 1-based, so this is tracks start+1 ... end
+    var chunk = tracks.whose({_and: [
+        {index: {_greaterThan: {{start}}}},
+        {index: {_lessThanEquals: {{end}}}}
+    ]});
+    return JSON.stringify({ total: tracks.length, rows: trackRows(chunk) });
+})()
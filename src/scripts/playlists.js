@@ -0,0 +1,23 @@
+(function() {
+    var pls = app.playlists();
+    var result = [];
+    for (var i = 0; i < pls.length; i++) {
+        // Only plain user playlists take tracks: not the library, smart
+        // playlists or special ones (Purchased, Genius, ...)
+        var editable = false;
+        if (pls[i].class() === 'userPlaylist') {
+            try {
+                editable = !pls[i].smart() && pls[i].specialKind() === 'none';
+            } catch (e) {
+                editable = false;
+            }
+        }
+        result.push({
+            id: pls[i].id(),
+            persistentId: pls[i].persistentID(),
+            name: pls[i].name(),
+            editable: editable
+        });
+    }
+    return JSON.stringify(result);
+})()
@@ -0,0 +1,33 @@
+(function() {
+    var state = app.playerState();
+    var result = {
+        state:    state,
+        position: 0,
+        volume:   app.soundVolume(),
+        shuffle:  app.shuffleEnabled(),
+        repeat:   app.songRepeat(),
+        id:       0,
+        name:     '',
+        artist:   '',
+        album:    '',
+        duration: 0,
+        favorited: false,
+        disliked: false,
+        rating: 0
+    };
+    if (state !== 'stopped') {
+        result.position = app.playerPosition();
+        var t = app.currentTrack;
+        result.id       = t.id();
+        result.name     = t.name();
+        result.artist   = t.artist();
+        result.album    = t.album();
+        result.duration = t.duration();
+        try {
+            result.favorited = t.favorited();
+            result.disliked  = t.disliked();
+            result.rating    = t.rating();
+        } catch (e) {}
+    }
+    return JSON.stringify(result);
+})()
@@ -0,0 +1,73 @@
+// Shared helpers, prepended to every script run through `jxa::Script`.
+var app = Application('Music');
+
+// The track whose `id()` is `id`, or an error if there is none.
+// That's Music's numeric database id, not the hex persistent id.
+function trackById(id) {
+    var matches = app.tracks.whose({id: id});
+    if (matches.length === 0) {
+        throw new Error('Track not found');
+    }
+    return matches[0];
+}
+
+// Call `each` with every library track whose id is in `ids`, in order.
+// Ids that no longer resolve are skipped.
+function eachLibraryTrack(ids, each) {
+    var lib = app.libraryPlaylists[0];
+    for (var i = 0; i < ids.length; i++) {
+        var matches = lib.tracks.whose({id: ids[i]});
+        if (matches.length > 0) {
+            each(matches[0]);
+        }
+    }
+}
+
+// The playing track, or an error if nothing is playing.
+function currentTrack() {
+    if (app.playerState() === 'stopped') {
+        throw new Error('Nothing playing');
+    }
+    return app.currentTrack;
+}
+
+// Table rows for a track collection, read with batch property access.
+// Instead of calling .name(), .artist(), etc. on each track individually
+// (which is one Apple Event per call), we grab all values in bulk arrays.
+// This reduces thousands of IPC roundtrips to a dozen.
+function trackRows(t) {
+    var ids = t.id();
+    var names = t.name();
+    var artists = t.artist();
+    var albums = t.album();
+    var years = t.year();
+    var durations = t.duration();
+    var dates = t.dateAdded();
+    var plays = t.playedCount();
+    var bpms = t.bpm();
+    var comments = t.comment();
+    var groupings = t.grouping();
+    var favs = t.favorited();
+    var dislikes = t.disliked();
+    var ratings = t.rating();
+    var result = [];
+    for (var i = 0; i < names.length; i++) {
+        result.push({
+            id: ids[i],
+            name: names[i],
+            artist: artists[i],
+            album: albums[i],
+            year: years[i],
+            duration: durations[i],
+            dateAdded: dates[i] ? dates[i].toISOString() : "",
+            playCount: plays[i],
+            bpm: bpms[i],
+            comment: comments[i],
+            grouping: groupings[i],
+            favorited: favs[i],
+            disliked: dislikes[i],
+            rating: ratings[i]
+        });
+    }
+    return result;
+}
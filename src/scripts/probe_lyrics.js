@@ -0,0 +1,8 @@
+(function() {
+    var tracks = app.libraryPlaylists[0].tracks;
+    if (tracks.length === 0) {
+        return 'empty';
+    }
+    tracks[0].lyrics();
+    return 'ok';
+})()
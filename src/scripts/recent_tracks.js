@@ -0,0 +1,13 @@
+(function() {
+    var tracks = app.libraryPlaylists[0].tracks;
+    var dates = tracks.{{property}}();
+    var rows = trackRows(tracks);
+    var order = [];
+    for (var i = 0; i < rows.length; i++) {
+        if (dates[i]) {
+            order.push(i);
+        }
+    }
+    order.sort(function(a, b) { return dates[b] - dates[a]; });
+    return JSON.stringify(order.slice(0, {{limit}}).map(function(i) { return rows[i]; }));
+})()
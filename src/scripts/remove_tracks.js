@@ -0,0 +1,10 @@
+(function() {
+    var pl = app.userPlaylists.byId({{playlist}});
+    var ids = {{tracks}};
+    for (var i = 0; i < ids.length; i++) {
+        var matches = pl.tracks.whose({id: ids[i]})();
+        for (var j = matches.length - 1; j >= 0; j--) {
+            matches[j].delete();
+        }
+    }
+})()
@@ -0,0 +1 @@
+app.userPlaylists.byId({{playlist}}).name = {{name}};
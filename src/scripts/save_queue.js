@@ -0,0 +1,29 @@
+(function() {
+    var source;
+    try {
+        source = app.currentPlaylist();
+        source.name();
+    } catch (e) {
+        throw new Error('Nothing is playing');
+    }
+    var tracks = source.tracks();
+    if (tracks.length === 0) {
+        throw new Error('The queue is empty');
+    }
+    var name = {{name}};
+    if (name === '') {
+        var d = new Date();
+        var pad = function(n) { return (n < 10 ? '0' : '') + n; };
+        name = 'Queue ' + d.getFullYear() + '-' + pad(d.getMonth() + 1) + '-' + pad(d.getDate());
+    }
+    // Keep earlier snapshots: "Queue 2024-06-01 (2)", "(3)", ...
+    var base = name;
+    for (var n = 2; app.userPlaylists.whose({name: name})().length > 0; n++) {
+        name = base + ' (' + n + ')';
+    }
+    var pl = app.make({new: 'playlist', withProperties: {name: name}});
+    for (var i = 0; i < tracks.length; i++) {
+        app.duplicate(tracks[i], {to: pl});
+    }
+    return JSON.stringify({ name: name, count: tracks.length });
+})()
@@ -0,0 +1,8 @@
+(function() {
+    var q = {{query}};
+    return JSON.stringify(trackRows(app.libraryPlaylists[0].tracks.whose({_or: [
+        {name: {_contains: q}},
+        {artist: {_contains: q}},
+        {album: {_contains: q}}
+    ]})));
+})()
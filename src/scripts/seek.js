@@ -0,0 +1,3 @@
+if (app.playerState() !== 'stopped') {
+    app.playerPosition = {{position}};
+}
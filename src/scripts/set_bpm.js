@@ -0,0 +1 @@
+trackById({{track}}).bpm = {{bpm}};
@@ -0,0 +1,19 @@
+// Turn crossfade on or off and set its length in seconds, through the
+// Playback pane of Music's settings (see crossfade.js).
+{{helpers}}
+
+(function() {
+    var pane = openPlaybackSettings();
+    try {
+        var current = readCrossfade(pane);
+        var box = pane.checkboxes.whose({name: {_beginsWith: 'Crossfade'}})[0];
+        if (current.enabled !== {{enabled}}) {
+            box.click();
+        }
+        if ({{enabled}}) {
+            pane.sliders[0].value = {{seconds}};
+        }
+    } finally {
+        closeSettings(pane);
+    }
+})()
@@ -0,0 +1,5 @@
+app.eqEnabled = {{enabled}};
+var preset = {{preset}};
+if (preset) {
+    app.currentEQPreset = app.eqPresets.byName(preset);
+}
@@ -0,0 +1 @@
+trackById({{track}}).lyrics = {{lyrics}};
@@ -0,0 +1,3 @@
+eachLibraryTrack({{tracks}}, function(t) {
+    {{apply}}
+});
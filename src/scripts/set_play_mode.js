@@ -0,0 +1,2 @@
+app.shuffleEnabled = {{shuffle}};
+app.songRepeat = {{repeat}};
@@ -0,0 +1,3 @@
+eachLibraryTrack({{tracks}}, function(t) {
+    t.rating = {{rating}};
+});
@@ -0,0 +1,37 @@
+// Helpers for the crossfade scripts: open Music's settings on the Playback
+// pane and read the crossfade controls there.
+function openPlaybackSettings() {
+    app.activate();
+    var events = Application('System Events');
+    var music = events.processes.byName('Music');
+    events.keystroke(',', {using: 'command down'});
+    var dialog = null;
+    for (var i = 0; i < 20 && !dialog; i++) {
+        delay(0.1);
+        var windows = music.windows.whose({subrole: 'AXDialog'});
+        if (windows.length > 0) {
+            dialog = windows[0];
+        }
+    }
+    if (!dialog) {
+        throw new Error("Music's settings didn't open");
+    }
+    dialog.toolbars[0].buttons.byName('Playback').click();
+    delay(0.2);
+    return dialog;
+}
+
+function readCrossfade(pane) {
+    var boxes = pane.checkboxes.whose({name: {_beginsWith: 'Crossfade'}});
+    if (boxes.length === 0) {
+        throw new Error('No crossfade setting in Music > Settings > Playback');
+    }
+    return {
+        enabled: boxes[0].value() === 1,
+        seconds: Math.round(pane.sliders[0].value())
+    };
+}
+
+function closeSettings(pane) {
+    pane.buttons.whose({subrole: 'AXCloseButton'})[0].click();
+}
@@ -0,0 +1,21 @@
+(function() {
+    var t = trackById({{track}});
+    var result = {};
+    function get(key, read) {
+        try { result[key] = read(); } catch (e) {}
+    }
+    get('name', function() { return t.name(); });
+    get('artist', function() { return t.artist(); });
+    get('album', function() { return t.album(); });
+    get('genre', function() { return t.genre(); });
+    get('year', function() { return t.year(); });
+    get('composer', function() { return t.composer(); });
+    get('bitRate', function() { return t.bitRate(); });
+    get('sampleRate', function() { return t.sampleRate(); });
+    get('playCount', function() { return t.playedCount(); });
+    get('dateAdded', function() { return t.dateAdded().toISOString(); });
+    get('playedDate', function() { return t.playedDate().toISOString(); });
+    get('kind', function() { return t.kind(); });
+    get('cloudStatus', function() { return t.cloudStatus(); });
+    return JSON.stringify(result);
+})()
@@ -0,0 +1,7 @@
+(function() {
+    var t = trackById({{track}});
+    var result = { lyrics: '', location: '' };
+    try { result.lyrics = t.lyrics() || ''; } catch (e) {}
+    try { result.location = t.location().toString(); } catch (e) {}
+    return JSON.stringify(result);
+})()
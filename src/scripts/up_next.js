@@ -0,0 +1,21 @@
+(function() {
+    if (app.playerState() === 'stopped' || app.shuffleEnabled()) {
+        return JSON.stringify(null);
+    }
+    try {
+        var tracks = app.currentPlaylist().tracks;
+        var count = tracks.length;
+        // index() is 1-based, so it is already the 0-based index of the next track
+        var next = app.currentTrack.index();
+        if (next >= count) {
+            if (app.songRepeat() !== 'all' || count === 0) {
+                return JSON.stringify(null);
+            }
+            next = 0;
+        }
+        var t = tracks[next];
+        return JSON.stringify({ name: t.name(), artist: t.artist(), album: t.album() });
+    } catch (e) {
+        return JSON.stringify(null);
+    }
+})()
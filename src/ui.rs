@@ -1,16 +1,62 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Layout, Rect},
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Padding, Paragraph},
+    widgets::{
+        Block, Borders, Cell, Clear, HighlightSpacing, List, ListItem, Padding, Paragraph, Row,
+        Scrollbar, ScrollbarOrientation, ScrollbarState, Table,
+    },
     Frame,
 };
 
-use crate::app::{App, LibraryView, Panel};
-use crate::bridge::{PlayState, RepeatMode};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::app::{App, ArtworkChooser, BpmTap, Level, LibraryView, LyricsEditor, Modal, Panel, Popup, AUDIO_PRESET_ROW, MENU};
+use crate::bridge::{Access, PlayState, RepeatMode};
+use crate::browser::BrowserColumn;
+use crate::config::{Column, StatusWidget};
+
+/// Blank columns between the end of a scrolling title and its repeat.
+const MARQUEE_GAP: usize = 4;
+
+/// Marker drawn in front of the selected row.
+const HIGHLIGHT_SYMBOL: &str = " \u{25b6} ";
+
+/// Cells in the status bar level meter.
+const LEVEL_METER_WIDTH: usize = 10;
+
+/// Blank columns between track table columns.
+const COLUMN_SPACING: u16 = 1;
+
+/// Ticks per one-cell step of the screensaver's drift (ticks are ~200ms).
+const SCREENSAVER_STEP: u64 = 3;
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
+    if app.screensaver {
+        draw_screensaver(frame, frame.area(), app);
+        return;
+    }
+
+    if app.karaoke {
+        let area = frame.area();
+        draw_karaoke(frame, area, app);
+        let bottom = Rect { y: area.bottom().saturating_sub(1), height: area.height.min(1), ..area };
+        draw_overlays(frame, bottom, app);
+        draw_toasts(frame, app);
+        return;
+    }
+
+    if app.mini_player {
+        let area = frame.area();
+        draw_mini_player(frame, area, app);
+        // The hint row doubles as the command line
+        let bottom = Rect { y: area.bottom().saturating_sub(1), height: area.height.min(1), ..area };
+        draw_overlays(frame, bottom, app);
+        return;
+    }
+
     let width = frame.area().width;
+    app.terminal_width = width;
     // Compact mode: hide now-playing panel when too narrow
     let show_now_playing = width >= 60;
     let controls_height = 1;
@@ -24,12 +70,10 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
 
     draw_header(frame, header, app);
 
-    if app.mini_player {
-        // Mini-player: full-width now playing, no library
+    if app.zoomed && app.active_panel == Panel::NowPlaying {
         draw_now_playing(frame, main_area, app);
-    } else if show_now_playing {
-        // Responsive split: narrower left panel on smaller terminals
-        let left_pct = if width >= 120 { 35 } else if width >= 80 { 40 } else { 45 };
+    } else if show_now_playing && !app.zoomed {
+        let left_pct = app.split_percent.unwrap_or_else(|| default_split(width));
         let [left_panel, right_panel] = Layout::horizontal([
             Constraint::Percentage(left_pct),
             Constraint::Percentage(100 - left_pct),
@@ -39,46 +83,596 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         draw_now_playing(frame, left_panel, app);
         draw_library(frame, right_panel, app);
     } else {
-        // Too narrow: full-width library, no artwork
+        // Too narrow, or the library zoomed: full-width library, no artwork
         draw_library(frame, main_area, app);
     }
 
-    draw_controls(frame, bottom_bar, app);
+    if !app.command_mode && !app.rating_mode {
+        draw_controls(frame, bottom_bar, app);
+    }
+    draw_overlays(frame, bottom_bar, app);
 
-    // Notification overlay (rendered last so it paints on top)
-    if let Some((ref msg, _)) = app.notification {
-        let area = frame.area();
-        let popup_width = (msg.len() as u16 + 4).min(area.width.saturating_sub(4));
-        let popup_height = 3;
-        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
-        let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-        let popup_area = Rect::new(x, y, popup_width, popup_height);
-
-        frame.render_widget(Clear, popup_area);
-        let popup = Paragraph::new(Line::from(msg.as_str()).alignment(Alignment::Center))
+    // Toasts (rendered last so they paint on top)
+    draw_toasts(frame, app);
+}
+
+/// Prompts and dialogs that take the keys while open, over whatever the
+/// screen shows. `bottom` is the row for the command line and rating prompt.
+fn draw_overlays(frame: &mut Frame, bottom: Rect, app: &App) {
+    if app.command_mode || app.rating_mode {
+        frame.render_widget(Clear, bottom);
+    }
+    if app.command_mode {
+        draw_command_line(frame, bottom, app);
+    } else if app.rating_mode {
+        draw_rating_prompt(frame, bottom);
+    }
+
+    if let Some(ref popup) = app.popup {
+        draw_popup(frame, popup);
+    }
+
+    if let Some(ref tap) = app.bpm_tap {
+        draw_bpm_tap(frame, tap);
+    }
+
+    if let Some(ref chooser) = app.artwork_chooser {
+        draw_artwork_chooser(frame, chooser);
+    }
+
+    if let Some(ref editor) = app.lyrics_editor {
+        draw_lyrics_editor(frame, editor);
+    }
+
+    if let Some(ref modal) = app.modal {
+        draw_modal(frame, modal, app);
+    }
+}
+
+/// Now Playing panel width in percent when none is set: narrower on
+/// bigger terminals.
+pub fn default_split(width: u16) -> u16 {
+    if width >= 120 {
+        35
+    } else if width >= 80 {
+        40
+    } else {
+        45
+    }
+}
+
+/// The mini-player: title, artist, progress and a key hint on up to four
+/// lines, for a small tmux pane. The newest toast replaces the hint.
+fn draw_mini_player(frame: &mut Frame, area: Rect, app: &App) {
+    let width = area.width as usize;
+    let state_icon = match app.player.state {
+        PlayState::Playing => "\u{25b6}",
+        PlayState::Paused => "\u{2016}",
+        PlayState::Stopped => "\u{25a0}",
+    };
+    let title = if app.player.track_name.is_empty() {
+        "Not playing".to_string()
+    } else {
+        app.player.track_name.clone()
+    };
+    let artist = match (app.player.artist.is_empty(), app.player.album.is_empty()) {
+        (false, false) => format!("{} \u{2014} {}", app.player.artist, app.player.album),
+        _ => app.player.artist.clone(),
+    };
+
+    // Progress: "1:23 ━━━━━━────── 3:45"
+    let elapsed = format_time(app.player.position);
+    let total = format_time(app.player.duration);
+    let bar_width = width.saturating_sub(elapsed.len() + total.len() + 4);
+    let ratio = if app.player.duration > 0.0 {
+        (app.player.position / app.player.duration).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let filled = ((bar_width as f64) * ratio).round() as usize;
+    let progress = Line::from(vec![
+        Span::from(format!(" {elapsed} ")).white(),
+        Span::from("\u{2501}".repeat(filled)).cyan(),
+        Span::from("\u{2500}".repeat(bar_width - filled)).dark_gray(),
+        Span::from(format!(" {total}")).white(),
+    ]);
+
+    let hint = match app.toasts.last() {
+        Some(toast) => {
+            let color = match toast.level {
+                Level::Info => Color::Cyan,
+                Level::Success => Color::Green,
+                Level::Error => Color::Red,
+            };
+            Line::from(truncate(&format!(" {}", toast.text), width)).fg(color)
+        }
+        None => Line::from(truncate(" space:play/pause  S-\u{2190}/\u{2192}:track  ,/.:seek  +/-:vol  n:expand", width))
+            .dark_gray(),
+    };
+
+    let mut lines = vec![
+        Line::from(truncate(&format!(" {state_icon} {title}"), width)).bold().white(),
+        Line::from(truncate(&format!("   {artist}"), width)).cyan(),
+        progress,
+        hint,
+    ];
+    // In three rows or fewer, keep the progress over the artist and hint
+    if area.height < 4 {
+        lines.pop();
+    }
+    if area.height < 3 {
+        lines.remove(1);
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn draw_toasts(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    // Stacked up from just above the controls, newest at the bottom
+    let mut bottom = area.bottom().saturating_sub(2);
+    for toast in app.toasts.iter().rev() {
+        let (icon, color) = match toast.level {
+            Level::Info => ("", Color::Cyan),
+            Level::Success => ("\u{2713} ", Color::Green),
+            Level::Error => ("\u{2717} ", Color::Red),
+        };
+        let text = format!("{icon}{}", toast.text);
+        let height = 3;
+        if bottom < area.y + height {
+            break;
+        }
+        let width = (text.width() as u16 + 4).min(area.width.saturating_sub(2));
+        let toast_area = Rect::new(
+            area.right().saturating_sub(width + 1),
+            bottom - height,
+            width,
+            height,
+        );
+        bottom -= height;
+
+        frame.render_widget(Clear, toast_area);
+        let popup = Paragraph::new(Line::from(text))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Green))
+                    .border_style(Style::default().fg(color))
             )
-            .style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
-        frame.render_widget(popup, popup_area);
+            .style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+        frame.render_widget(popup, toast_area);
+    }
+}
+
+/// Full-screen guide shown at startup until Music is running and accepts
+/// our Apple Events.
+pub fn draw_onboarding(frame: &mut Frame, access: &Access) {
+    let (headline, steps): (&str, Vec<String>) = match access {
+        Access::Ready => return,
+        Access::MusicNotRunning => (
+            "Music isn't running",
+            vec!["Press o to open Music".into()],
+        ),
+        Access::NotAuthorized => (
+            "cli-music isn't allowed to control Music",
+            vec![
+                "Press p to open System Settings \u{2192} Privacy & Security \u{2192} Automation".into(),
+                "and turn on Music under your terminal app".into(),
+            ],
+        ),
+        Access::Unavailable(err) => ("Can't talk to Music", vec![err.clone()]),
+    };
+
+    let mut lines = vec![Line::from(headline).bold().yellow(), Line::default()];
+    lines.extend(steps.into_iter().map(Line::from));
+    lines.push(Line::default());
+    lines.push(Line::from("Checking again every few seconds \u{2022} r retry now \u{2022} q quit").dark_gray());
+
+    let area = frame.area();
+    let content_width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16;
+    let width = (content_width + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" cli-music "),
+        ),
+        Rect::new(x, y, width, height),
+    );
+}
+
+fn draw_popup(frame: &mut Frame, popup: &Popup) {
+    let area = frame.area();
+    let content_width = popup
+        .lines
+        .iter()
+        .map(|l| l.width())
+        .chain(std::iter::once(popup.title.width()))
+        .max()
+        .unwrap_or(0) as u16;
+    let popup_width = (content_width + 4).min(area.width.saturating_sub(4));
+    let popup_height = (popup.lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let lines: Vec<Line> = popup.lines.iter().map(|l| Line::from(l.as_str())).collect();
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(popup.title.as_str())
+                .padding(Padding::horizontal(1)),
+        ),
+        popup_area,
+    );
+}
+
+/// Thumbnail size in the artwork chooser (cells; 2 pixel rows per cell).
+const THUMB_WIDTH: u16 = 16;
+const THUMB_HEIGHT: u16 = 8;
+
+fn draw_artwork_chooser(frame: &mut Frame, chooser: &ArtworkChooser) {
+    let area = frame.area();
+    // Each candidate: a bordered thumbnail with the release name below it
+    let tile_width = THUMB_WIDTH + 2;
+    let tile_height = THUMB_HEIGHT + 3;
+    let count = chooser.candidates.as_ref().map_or(1, Vec::len).max(1) as u16;
+    let fit = (area.width.saturating_sub(4) / tile_width).max(1);
+    let shown = count.min(fit);
+    let popup_width = (shown * tile_width + 2).max(30).min(area.width);
+    let popup_height = (tile_height + 3).min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Artwork: {} ", chooser.album))
+        .title_bottom(
+            Line::from(" \u{2190}/\u{2192} choose \u{2022} Enter use for album \u{2022} Esc cancel ")
+                .dark_gray(),
+        );
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let Some(ref candidates) = chooser.candidates else {
+        frame.render_widget(
+            Paragraph::new(Line::from("Looking for covers...").dark_gray())
+                .alignment(Alignment::Center),
+            inner,
+        );
+        return;
+    };
+
+    // Scroll so the selection stays visible
+    let first = chooser.selected.saturating_sub(shown as usize - 1);
+    let visible = candidates.iter().enumerate().skip(first).take(shown as usize);
+    for (slot, (i, candidate)) in visible.enumerate() {
+        let tile = Rect::new(
+            inner.x + slot as u16 * tile_width,
+            inner.y,
+            tile_width,
+            tile_height,
+        )
+        .intersection(inner);
+        let selected = i == chooser.selected;
+        let border = if selected { Color::Yellow } else { Color::DarkGray };
+        let tile_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border));
+        let tile_inner = tile_block.inner(tile);
+        frame.render_widget(tile_block, tile);
+
+        let [art_area, label_area] =
+            Layout::vertical([Constraint::Length(THUMB_HEIGHT), Constraint::Length(1)]).areas(tile_inner);
+        if let Some(ref thumb) = candidate.thumbnail {
+            let lines = crate::artwork::image_to_halfblocks(thumb, art_area.width, art_area.height);
+            frame.render_widget(Paragraph::new(lines), art_area);
+        }
+        let label = truncate(&candidate.release, label_area.width as usize);
+        let label = if selected { Line::from(label).yellow() } else { Line::from(label) };
+        frame.render_widget(Paragraph::new(label), label_area);
+    }
+}
+
+fn draw_bpm_tap(frame: &mut Frame, tap: &BpmTap) {
+    let area = frame.area();
+    let popup_width = 44.min(area.width.saturating_sub(4));
+    let popup_height = 7.min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let reading = match tap.bpm() {
+        Some(bpm) => Span::from(format!("{bpm:.1} BPM")).bold().white(),
+        None => Span::from("tap along to the beat").dark_gray(),
+    };
+    let lines = vec![
+        Line::from(truncate(&tap.target_name, popup_width.saturating_sub(4) as usize)).cyan(),
+        Line::default(),
+        Line::from(reading),
+        Line::default(),
+        Line::from("b/space:tap  r:reset  enter:save  esc:cancel").dark_gray(),
+    ];
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" BPM Tap "),
+        ),
+        popup_area,
+    );
+}
+
+fn draw_lyrics_editor(frame: &mut Frame, editor: &LyricsEditor) {
+    let area = frame.area();
+    let popup_width = (area.width * 7 / 10).max(40).min(area.width);
+    let popup_height = (area.height * 8 / 10).max(8).min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    let hint = if editor.saving {
+        " Saving... ".to_string()
+    } else {
+        let target = if editor.write_lrc { "Music + .lrc" } else { "Music" };
+        format!(" Ctrl+S save to {target} \u{2022} Esc cancel ")
+    };
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Edit lyrics: {} ", editor.query.name))
+        .title_bottom(Line::from(hint).dark_gray());
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    // Scroll just enough to keep the cursor in view
+    let height = inner.height as usize;
+    let top = (editor.row + 1).saturating_sub(height);
+    let before: String = editor.lines[editor.row].chars().take(editor.col).collect();
+    let left = before.width().saturating_sub(inner.width as usize - 1);
+    let lines: Vec<Line> = editor.lines[top..]
+        .iter()
+        .take(height)
+        .map(|line| {
+            let mut skipped = 0;
+            let visible: String = line
+                .chars()
+                .skip_while(|c| {
+                    let skip = skipped < left;
+                    skipped += c.width().unwrap_or(0);
+                    skip
+                })
+                .collect();
+            Line::from(truncate(&visible, inner.width as usize))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+    if !editor.saving {
+        let cursor_x = inner.x + (before.width() - left) as u16;
+        let cursor_y = inner.y + (editor.row - top) as u16;
+        frame.set_cursor_position((cursor_x, cursor_y));
     }
 }
 
+/// Tracks listed in the shuffle preview.
+const SHUFFLE_PREVIEW_ROWS: usize = 20;
+
+fn draw_modal(frame: &mut Frame, modal: &Modal, app: &App) {
+    let area = frame.area();
+    let popup_width = 50.min(area.width.saturating_sub(4));
+    let inner_width = popup_width.saturating_sub(4) as usize;
+
+    let (title, lines) = match modal {
+        Modal::Input { title, value, .. } => {
+            // Keep the end of long input (where the cursor is) in view
+            let visible: String = {
+                let mut tail: Vec<char> = Vec::new();
+                let mut used = 0;
+                for c in value.chars().rev() {
+                    used += c.width().unwrap_or(0);
+                    if used + 3 > inner_width {
+                        break;
+                    }
+                    tail.push(c);
+                }
+                tail.into_iter().rev().collect()
+            };
+            let lines = vec![
+                Line::from(vec![
+                    Span::from("> ").yellow().bold(),
+                    Span::from(visible).white(),
+                    Span::from("\u{2588}").yellow(),
+                ]),
+                Line::default(),
+                Line::from("enter:ok  esc:cancel").dark_gray(),
+            ];
+            (title.as_str(), lines)
+        }
+        Modal::PickPlaylist { title, filter, selected, .. } => {
+            let matches = app.picker_matches(filter);
+            let selected = (*selected).min(matches.len().saturating_sub(1));
+            // Show a window of the matches around the selection
+            let rows = 8usize;
+            let first = selected.saturating_sub(rows - 1);
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::from("> ").yellow().bold(),
+                    Span::from(filter.clone()).white(),
+                    Span::from("\u{2588}").yellow(),
+                ]),
+                Line::default(),
+            ];
+            for (i, p) in matches.iter().enumerate().skip(first).take(rows) {
+                let name = truncate(&p.name, inner_width.saturating_sub(2));
+                lines.push(if i == selected {
+                    Line::from(format!("\u{25b6} {name}")).cyan().bold()
+                } else {
+                    Line::from(format!("  {name}")).white()
+                });
+            }
+            if matches.is_empty() {
+                lines.push(Line::from("  no matching playlists").dark_gray());
+            }
+            lines.push(Line::default());
+            lines.push(Line::from("type:filter  \u{2191}/\u{2193}:pick  enter:ok  esc:cancel").dark_gray());
+            (title.as_str(), lines)
+        }
+        Modal::Confirm { message, .. } => {
+            let lines = vec![
+                Line::from(truncate(message, inner_width)).white(),
+                Line::default(),
+                Line::from("y:yes  n:no").dark_gray(),
+            ];
+            (" Confirm ", lines)
+        }
+        Modal::ShufflePreview { playlist, tracks } => {
+            let mut lines = vec![
+                Line::from(truncate(&playlist.name, inner_width)).cyan().bold(),
+                Line::default(),
+            ];
+            for (i, t) in tracks.iter().take(SHUFFLE_PREVIEW_ROWS).enumerate() {
+                let number = format!("{:>2}. ", i + 1);
+                let text = format!("{} \u{2014} {}", t.name, t.artist);
+                lines.push(Line::from(vec![
+                    Span::from(number).dark_gray(),
+                    Span::from(truncate(&text, inner_width.saturating_sub(4))).white(),
+                ]));
+            }
+            if tracks.len() > SHUFFLE_PREVIEW_ROWS {
+                lines.push(Line::from(format!("    \u{2026} and {} more", tracks.len() - SHUFFLE_PREVIEW_ROWS)).dark_gray());
+            }
+            lines.push(Line::default());
+            lines.push(Line::from("r:re-roll  enter:play  esc:cancel").dark_gray());
+            (" Shuffle preview ", lines)
+        }
+        Modal::Audio { settings, selected } => {
+            let Some(settings) = settings else {
+                let lines = vec![Line::from("Reading Music's settings...").dark_gray()];
+                return draw_modal_box(frame, " Audio ", lines, popup_width);
+            };
+            let row = |i: usize, text: String| {
+                if i == *selected {
+                    Line::from(format!("\u{25b6} {text}")).cyan().bold()
+                } else {
+                    Line::from(format!("  {text}")).white()
+                }
+            };
+            let mut lines = vec![
+                row(0, format!("EQ: {}", if settings.eq_enabled { "on" } else { "off" })),
+                match settings.crossfade {
+                    Some(crossfade) if crossfade.enabled => row(1, format!("Crossfade: {}s", crossfade.seconds)),
+                    Some(_) => row(1, "Crossfade: off".to_string()),
+                    None => row(1, "Crossfade: unavailable".to_string()),
+                },
+                Line::default(),
+            ];
+            // A window of presets around the selection
+            let rows = 8usize;
+            let first = selected.saturating_sub(AUDIO_PRESET_ROW).saturating_sub(rows - 1);
+            for (i, preset) in settings.presets.iter().enumerate().skip(first).take(rows) {
+                let mark = if *preset == settings.preset { "\u{25cf}" } else { " " };
+                let name = truncate(preset, inner_width.saturating_sub(4));
+                lines.push(row(i + AUDIO_PRESET_ROW, format!("{mark} {name}")));
+            }
+            if settings.crossfade.is_none() {
+                lines.push(Line::default());
+                lines.push(Line::from("Crossfade needs Accessibility permission").dark_gray());
+                lines.push(Line::from("for this terminal (System Settings \u{203a} Privacy)").dark_gray());
+            }
+            lines.push(Line::default());
+            lines.push(Line::from("\u{2191}/\u{2193}:pick  enter:toggle/use  \u{2190}/\u{2192}:fade  esc:close").dark_gray());
+            (" Audio ", lines)
+        }
+    };
+    draw_modal_box(frame, title, lines, popup_width);
+}
+
+/// Draw a modal's lines in a centered, bordered box `width` cells wide.
+fn draw_modal_box(frame: &mut Frame, title: &str, lines: Vec<Line>, popup_width: u16) {
+    let area = frame.area();
+
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(title)
+                .padding(Padding::horizontal(1)),
+        ),
+        popup_area,
+    );
+}
+
+fn draw_command_line(frame: &mut Frame, area: Rect, app: &App) {
+    let line = Line::from(vec![
+        Span::from(":").yellow().bold(),
+        Span::from(app.command_input.clone()).white(),
+        Span::from("\u{2588}").yellow(),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_rating_prompt(frame: &mut Frame, area: Rect) {
+    let line = Line::from(vec![
+        Span::from(" Rate ").yellow().bold(),
+        Span::from("0\u{2013}5 stars").white(),
+        Span::from("  Esc:cancel").dark_gray(),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
 fn draw_header(frame: &mut Frame, area: Rect, app: &App) {
     let width = area.width as usize;
 
     let mut spans = vec![
         Span::from(" \u{266b} cli-music ").bold().cyan(),
     ];
+    if app.safe_mode {
+        spans.push(Span::from(" SAFE MODE ").bold().black().on_yellow());
+    }
+
+    // With tabs open, the tab bar takes the place of the hints
+    if !app.tabs.is_empty() {
+        for (i, label) in app.tab_labels().into_iter().enumerate() {
+            let label = format!(" {} {} ", i + 1, truncate(&label, 20));
+            spans.push(Span::from(" "));
+            spans.push(if i == app.active_tab {
+                Span::from(label).bold().black().on_cyan()
+            } else {
+                Span::from(label).dark_gray()
+            });
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+        return;
+    }
 
     // Only show keybindings if there's room
     let play_hint = match app.player.state {
         PlayState::Playing => "space:pause",
         _ => "space:play",
     };
-    let hints = format!("  {play_hint}  S-\u{2190}/\u{2192}:track  m:mode  s:search  f:save  n:mini");
+    let hints = format!("  {play_hint}  S-\u{2190}/\u{2192}:track  m:mode  s:search  f:save  y:lyrics  n:mini  ::cmd");
     if width > 50 {
         spans.push(Span::from(hints).dark_gray());
     }
@@ -102,6 +696,15 @@ fn draw_now_playing(frame: &mut Frame, area: Rect, app: &App) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    // Blurred artwork fills the panel behind the cover and text
+    if app.config.now_playing_backdrop {
+        if let Some(ref backdrop) = app.backdrop {
+            let fill = area.inner(Margin::new(1, 1));
+            let lines = crate::artwork::image_to_halfblocks(backdrop, fill.width, fill.height);
+            frame.render_widget(Paragraph::new(lines), fill);
+        }
+    }
+
     if app.player.track_name.is_empty() {
         let center_y = inner.y + inner.height / 2;
         let msg_area = Rect { y: center_y, height: 1, ..inner };
@@ -125,8 +728,10 @@ fn draw_now_playing(frame: &mut Frame, area: Rect, app: &App) {
         ])
         .areas(inner);
 
-        // Render artwork (centered if narrower than area)
-        if let Some(ref img) = app.artwork {
+        // Render lyrics or artwork (centered if narrower than area)
+        if app.show_lyrics {
+            render_lyrics(frame, art_area, app);
+        } else if let Some(ref img) = app.artwork {
             // Keep artwork square-ish: width = height * 2 (half-blocks are ~2:1)
             let art_w = art_area.width.min(art_area.height * 2);
             let art_x = art_area.x + (art_area.width.saturating_sub(art_w)) / 2;
@@ -159,18 +764,283 @@ fn draw_now_playing(frame: &mut Frame, area: Rect, app: &App) {
     }
 }
 
+fn render_lyrics(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(ref lyrics) = app.lyrics else {
+        let msg = if app.lyrics_loading { "Loading lyrics..." } else { "No lyrics found" };
+        let center_y = area.y + area.height / 2;
+        let msg_area = Rect { y: center_y, height: 1, ..area };
+        frame.render_widget(
+            Paragraph::new(msg).dark_gray().alignment(Alignment::Center),
+            msg_area,
+        );
+        return;
+    };
+
+    let [lines_area, credit_area] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+
+    // Center the picked line, or the line being sung; plain lyrics start at the top
+    let height = lines_area.height as usize;
+    let current = lyrics.current_line(app.player.position);
+    let top = app
+        .lyrics_cursor
+        .or(current)
+        .map_or(0, |focus| focus.saturating_sub(height / 2));
+    let query = app.lyrics_query.to_lowercase();
+
+    let lines: Vec<Line> = lyrics
+        .lines
+        .iter()
+        .enumerate()
+        .skip(top)
+        .take(height)
+        .map(|(i, l)| {
+            let mut style = match current {
+                Some(c) if i == c => Style::default().fg(Color::White).bold(),
+                Some(c) if i < c => Style::default().fg(Color::DarkGray),
+                _ => Style::default().fg(Color::Gray),
+            };
+            if !query.is_empty() && l.text.to_lowercase().contains(&query) {
+                style = style.fg(Color::Yellow);
+            }
+            if app.lyrics_cursor == Some(i) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Line::from(Span::styled(l.text.clone(), style))
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), lines_area);
+
+    if app.lyrics_search_mode {
+        let search_line = Line::from(vec![
+            Span::from(" / ").yellow().bold(),
+            Span::from(app.lyrics_query.clone()).white(),
+            Span::from("\u{2588}").yellow(),
+        ]);
+        frame.render_widget(Paragraph::new(search_line), credit_area);
+    } else {
+        frame.render_widget(
+            Paragraph::new(format!("lyrics: {}", lyrics.provider.label()))
+                .dark_gray()
+                .alignment(Alignment::Right),
+            credit_area,
+        );
+    }
+}
+
+fn draw_karaoke(frame: &mut Frame, area: Rect, app: &App) {
+    if app.config.karaoke_backdrop {
+        if let Some(ref backdrop) = app.backdrop {
+            let lines = crate::artwork::image_to_halfblocks(backdrop, area.width, area.height);
+            frame.render_widget(Paragraph::new(lines), area);
+        }
+    }
+
+    let [_, lyrics_area, _, info_area] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(7),
+        Constraint::Fill(1),
+        Constraint::Length(2),
+    ])
+    .areas(area);
+
+    let title = format!("{} \u{2014} {}", app.player.track_name, app.player.artist);
+    let ratio = if app.player.duration > 0.0 {
+        (app.player.position / app.player.duration).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let bar_width = (area.width as usize).saturating_sub(4);
+    let filled = (bar_width as f64 * ratio).round() as usize;
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(Span::from(title).dark_gray()),
+            Line::from(vec![
+                Span::from("\u{2501}".repeat(filled)).cyan(),
+                Span::from("\u{2500}".repeat(bar_width - filled)).dark_gray(),
+            ]),
+        ])
+        .alignment(Alignment::Center),
+        info_area,
+    );
+
+    let synced = app.lyrics.as_ref().filter(|l| l.synced);
+    let Some(lyrics) = synced else {
+        let msg = if app.lyrics_loading {
+            "Loading lyrics..."
+        } else {
+            "No synced lyrics for this track"
+        };
+        frame.render_widget(
+            Paragraph::new(vec![Line::default(), Line::default(), Line::from(msg).dark_gray()])
+                .alignment(Alignment::Center),
+            lyrics_area,
+        );
+        return;
+    };
+
+    let position = app.player.position;
+    let current = lyrics.current_line(position);
+    let line_text = |i: usize| lyrics.lines.get(i).map_or("", |l| l.text.as_str());
+    let width = area.width as usize;
+
+    let previous = current.and_then(|c| c.checked_sub(1)).map_or("", line_text);
+    let next_index = current.map_or(0, |c| c + 1);
+
+    // Progress through the current line, estimated from the next line's start
+    let progress = current.map_or(0.0, |c| {
+        let start = lyrics.lines[c].time.unwrap_or(0.0);
+        let end = lyrics.lines.get(c + 1).and_then(|l| l.time).unwrap_or(start + 5.0);
+        ((position - start) / (end - start).max(0.1)).clamp(0.0, 1.0)
+    });
+
+    let mut lines = vec![
+        Line::from(Span::from(previous.to_string()).dark_gray()),
+        Line::default(),
+    ];
+    lines.push(match current {
+        Some(c) => karaoke_line(line_text(c), progress, width),
+        None => Line::from("\u{266a}").dark_gray(),
+    });
+    lines.push(Line::default());
+    lines.push(Line::from(Span::from(line_text(next_index).to_string()).white()));
+    lines.push(Line::from(Span::from(line_text(next_index + 1).to_string()).dark_gray()));
+
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), lyrics_area);
+}
+
+/// Dimmed artwork with the title underneath, drifting slowly around the
+/// screen so nothing stays lit in one place.
+fn draw_screensaver(frame: &mut Frame, area: Rect, app: &App) {
+    frame.render_widget(Block::default().style(Style::default().bg(Color::Black)), area);
+
+    let art_height = if app.artwork.is_some() { (area.height / 2).max(4) } else { 0 };
+    let title = format!("{} \u{2014} {}", app.player.track_name, app.player.artist);
+    let width = (art_height * 2).max(title.width() as u16).min(area.width);
+    let height = (art_height + 2).min(area.height);
+
+    // Bounce between the edges, x and y at different speeds
+    let step = app.tick / SCREENSAVER_STEP;
+    let bounce = |step: u64, range: u16| {
+        let range = range as u64;
+        if range == 0 {
+            return 0;
+        }
+        let phase = step % (range * 2);
+        (if phase < range { phase } else { range * 2 - phase }) as u16
+    };
+    let x = area.x + bounce(step, area.width - width);
+    let y = area.y + bounce(step * 2 / 3, area.height - height);
+    let [art_area, _, title_area] = Layout::vertical([
+        Constraint::Length(art_height),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(Rect::new(x, y, width, height));
+
+    if let Some(ref img) = app.artwork {
+        let art_width = (art_height * 2).min(art_area.width);
+        let centered_art = Rect {
+            x: art_area.x + (art_area.width - art_width) / 2,
+            width: art_width,
+            ..art_area
+        };
+        let lines: Vec<Line> = crate::artwork::image_to_halfblocks(img, art_width, art_area.height)
+            .into_iter()
+            .map(dim_line)
+            .collect();
+        frame.render_widget(Paragraph::new(lines), centered_art);
+    }
+    frame.render_widget(
+        Paragraph::new(truncate(&title, title_area.width as usize))
+            .dark_gray()
+            .alignment(Alignment::Center),
+        title_area,
+    );
+}
+
+/// Halve the brightness of a rendered artwork line.
+fn dim_line(line: Line<'static>) -> Line<'static> {
+    let dim = |color: Option<Color>| match color {
+        Some(Color::Rgb(r, g, b)) => Some(Color::Rgb(r / 2, g / 2, b / 2)),
+        other => other,
+    };
+    let spans: Vec<Span> = line
+        .spans
+        .into_iter()
+        .map(|span| {
+            let style = Style { fg: dim(span.style.fg), bg: dim(span.style.bg), ..span.style };
+            Span::styled(span.content, style)
+        })
+        .collect();
+    Line::from(spans)
+}
+
+/// Render the line being sung, letter-spaced when it fits, with words already
+/// sung in cyan and the current word emphasized. `progress` is 0.0–1.0
+/// through the line.
+fn karaoke_line(text: &str, progress: f64, width: usize) -> Line<'static> {
+    let spaced = text.width() * 2 <= width;
+    let total: usize = text.chars().count().max(1);
+    let sung = (total as f64 * progress).round() as usize;
+
+    let mut spans = Vec::new();
+    let mut consumed = 0;
+    for (i, word) in text.split(' ').enumerate() {
+        if i > 0 {
+            spans.push(Span::from(if spaced { "   " } else { " " }));
+            consumed += 1;
+        }
+        let len = word.chars().count();
+        let style = if consumed + len <= sung {
+            Style::default().fg(Color::Cyan).bold()
+        } else if consumed <= sung {
+            Style::default().fg(Color::Yellow).bold().underlined()
+        } else {
+            Style::default().fg(Color::White).bold()
+        };
+        let word = if spaced {
+            word.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+        } else {
+            word.to_string()
+        };
+        spans.push(Span::styled(word, style));
+        consumed += len;
+    }
+    Line::from(spans)
+}
+
 fn render_track_info(frame: &mut Frame, area: Rect, app: &App) {
     let elapsed = format_time(app.player.position);
     let total = format_time(app.player.duration);
 
+    let width = area.width as usize;
+    let times = format!("  {elapsed} / {total}");
+    let album_width = width.saturating_sub(times.width());
+
+    let rating = match stars(app.player.rating) {
+        s if s.is_empty() => s,
+        s => format!("  {s}"),
+    };
+    let artist_width = width.saturating_sub(rating.width());
+    let badge = opinion_badge(app.player.favorited, app.player.disliked);
+    let title_width = width.saturating_sub(badge.as_ref().map_or(0, |b| b.width()));
+    let mut title = vec![Span::from(marquee(&app.player.track_name, title_width, app.tick)).bold().white()];
+    title.extend(badge);
+
     let info_text = vec![
-        Line::from(Span::from(app.player.track_name.clone()).bold().white()),
+        Line::from(title),
         Line::from(vec![
-            Span::from(app.player.artist.clone()).cyan(),
+            Span::from(marquee(&app.player.artist, artist_width, app.tick)).cyan(),
+            Span::from(rating).yellow(),
         ]),
         Line::from(vec![
-            Span::from(app.player.album.clone()).dark_gray(),
-            Span::from(format!("  {elapsed} / {total}")).dark_gray(),
+            Span::from(marquee(&app.player.album, album_width, app.tick)).dark_gray(),
+            Span::from(times).dark_gray(),
         ]),
     ];
 
@@ -178,23 +1048,63 @@ fn render_track_info(frame: &mut Frame, area: Rect, app: &App) {
 }
 
 fn draw_library(frame: &mut Frame, area: Rect, app: &mut App) {
-    let border_style = if app.active_panel == Panel::Library {
+    // The column browser puts its artist and album columns left of the tracks
+    let area = if app.browser.is_some() {
+        let [artists, albums, tracks] = Layout::horizontal([
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+        ])
+        .areas(area);
+        draw_browser_columns(frame, artists, albums, app);
+        tracks
+    } else {
+        area
+    };
+    let tracks_focused = app
+        .browser
+        .as_ref()
+        .is_none_or(|b| b.focus == BrowserColumn::Tracks);
+
+    let border_style = if app.active_panel == Panel::Library && tracks_focused {
         Style::default().fg(Color::Cyan)
     } else {
         Style::default().fg(Color::DarkGray)
     };
 
-    // Build title with track count
+    // Build title with the selected position, e.g. "342/1200"
+    let (selected, len) = app.list_position();
+    let position = format!("{}/{}", selected.map_or(0, |i| i + 1), len);
     let title = match app.view {
-        LibraryView::Playlists => format!(
-            " Playlists ({}) ",
-            app.playlists.len()
-        ),
-        LibraryView::Tracks => format!(
-            " {} \u{2014} {} tracks ",
-            app.tracks.first().map(|t| t.album.as_str()).unwrap_or("Tracks"),
-            app.tracks.len()
-        ),
+        LibraryView::Menu => " Library ".to_string(),
+        LibraryView::Playlists => format!(" Playlists \u{2014} {position} "),
+        LibraryView::History => format!(" History \u{2014} {position} "),
+        LibraryView::Tracks => {
+            let incomplete = app
+                .open_incomplete()
+                .map(|i| format!(" \u{00b7} incomplete {}/{} (r: load the rest)", i.fetched.len(), i.total))
+                .or_else(|| {
+                    let p = app.open_progress()?;
+                    Some(format!(" \u{00b7} loading {}/{}\u{2026}", p.loaded, p.total))
+                })
+                .unwrap_or_default();
+            let marked = match app.marked_count() {
+                0 if app.visual_anchor.is_some() => " \u{00b7} VISUAL".to_string(),
+                0 => String::new(),
+                n if app.visual_anchor.is_some() => format!(" \u{00b7} VISUAL {n} marked"),
+                n => format!(" \u{00b7} {n} marked"),
+            };
+            let name = match (&app.open_search, app.open_list) {
+                (Some(query), _) => format!("Search: {query}"),
+                (None, Some(list)) => list.label().to_string(),
+                (None, None) => app.tracks.first().map_or("Tracks".to_string(), |t| t.album.clone()),
+            };
+            format!(
+                " {name} \u{2014} {position} \u{00b7} {} {}{marked}{incomplete} ",
+                app.sort_key.label(),
+                if app.sort_descending { "\u{2193}" } else { "\u{2191}" }
+            )
+        }
     };
 
     let block = Block::default()
@@ -221,38 +1131,196 @@ fn draw_library(frame: &mut Frame, area: Rect, app: &mut App) {
             Constraint::Fill(1),
             Constraint::Length(1),
         ])
-        .areas(block.inner(area));
+        .areas(draw_playlist_header(frame, block.inner(area), app));
 
         frame.render_widget(block, area);
         render_library_list(frame, list_area, app);
+        render_scrollbar(frame, area, list_area, app);
 
-        let search_line = Line::from(vec![
+        let mut search_line = Line::from(vec![
             Span::from(" / ").yellow().bold(),
             Span::from(app.search_query.clone()).white(),
             Span::from("\u{2588}").yellow(), // blinking cursor
         ]);
+        if let Some(error) = &app.filter_error {
+            search_line.push_span(Span::from(format!("  {error}")).red());
+        }
         frame.render_widget(Paragraph::new(search_line), search_area);
     } else {
-        let inner = block.inner(area);
+        let inner = draw_playlist_header(frame, block.inner(area), app);
         frame.render_widget(block, area);
         render_library_list(frame, inner, app);
+        render_scrollbar(frame, area, inner, app);
+    }
+}
+
+/// Playlist cover height in the Tracks view header (cells).
+const PLAYLIST_COVER_HEIGHT: u16 = 7;
+
+/// The open playlist's cover and summary above its tracks, when the cover is
+/// loaded and there's room. Returns the area left for the list.
+fn draw_playlist_header(frame: &mut Frame, area: Rect, app: &App) -> Rect {
+    let Some(playlist) = app.open_playlist() else { return area };
+    let cover = app
+        .playlist_cover
+        .as_ref()
+        .filter(|(key, _)| *key == app.playlist_cover_requested)
+        .map(|(_, img)| img);
+    let Some(cover) = cover else { return area };
+    if area.height < PLAYLIST_COVER_HEIGHT * 3 || area.width < 40 {
+        return area;
+    }
+    let [header, rest] =
+        Layout::vertical([Constraint::Length(PLAYLIST_COVER_HEIGHT + 1), Constraint::Fill(1)]).areas(area);
+    // Half-blocks are about twice as tall as wide, so 2 columns per row is square
+    let [cover_area, _, info_area] = Layout::horizontal([
+        Constraint::Length(PLAYLIST_COVER_HEIGHT * 2),
+        Constraint::Length(2),
+        Constraint::Fill(1),
+    ])
+    .areas(Rect { height: PLAYLIST_COVER_HEIGHT, ..header });
+    frame.render_widget(
+        Paragraph::new(crate::artwork::image_to_halfblocks(cover, cover_area.width, cover_area.height)),
+        cover_area,
+    );
+
+    let total: f64 = app.tracks.iter().map(|t| t.duration).sum();
+    let count = match app.tracks.len() {
+        1 => "1 track".to_string(),
+        n => format!("{n} tracks"),
+    };
+    let width = info_area.width as usize;
+    let lines = vec![
+        Line::default(),
+        Line::from(truncate(&playlist.name, width)).bold().white(),
+        Line::from(format!("{count} \u{00b7} {}", format_time(total))).dark_gray(),
+    ];
+    frame.render_widget(Paragraph::new(lines), info_area);
+    rest
+}
+
+/// The column browser's artist and album lists, each led by an "All" row.
+fn draw_browser_columns(frame: &mut Frame, artists_area: Rect, albums_area: Rect, app: &mut App) {
+    let library_active = app.active_panel == Panel::Library;
+    let tick = app.tick;
+    let Some(browser) = app.browser.as_mut() else { return };
+
+    let highlight_style = Style::default()
+        .bg(Color::Cyan)
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let columns = [
+        (BrowserColumn::Artists, " Artists ", artists_area, &browser.artists, &mut browser.artist_state),
+        (BrowserColumn::Albums, " Albums ", albums_area, &browser.albums, &mut browser.album_state),
+    ];
+    for (column, title, area, names, state) in columns {
+        let focused = library_active && browser.focus == column;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::DarkGray }))
+            .title(title);
+        let name_width = (block.inner(area).width as usize).saturating_sub(HIGHLIGHT_SYMBOL.width());
+        let selected = state.selected();
+        let all = format!("All ({})", names.len());
+        let items: Vec<ListItem> = std::iter::once(&all)
+            .chain(names.iter())
+            .enumerate()
+            .map(|(i, name)| {
+                let text = if selected == Some(i) && focused {
+                    marquee(name, name_width, tick)
+                } else {
+                    truncate(name, name_width)
+                };
+                if i == 0 {
+                    ListItem::new(text).dark_gray()
+                } else {
+                    ListItem::new(text)
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(if focused { highlight_style } else { Style::default().bold() })
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .highlight_spacing(HighlightSpacing::Always);
+        frame.render_stateful_widget(list, area, state);
+    }
+}
+
+/// Draw a scrollbar over the right border of the library panel when the
+/// list is longer than `list_area` can show.
+fn render_scrollbar(frame: &mut Frame, panel: Rect, list_area: Rect, app: &App) {
+    let (selected, len) = app.list_position();
+    let visible = list_rows(app, list_area) as usize;
+    if len <= visible {
+        return;
+    }
+
+    let mut state = ScrollbarState::new(len)
+        .viewport_content_length(visible)
+        .position(selected.unwrap_or(0));
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_stateful_widget(
+        scrollbar,
+        panel.inner(Margin { vertical: 1, horizontal: 0 }),
+        &mut state,
+    );
+}
+
+/// Rows of list content that fit in `list_area` for the current view.
+fn list_rows(app: &App, list_area: Rect) -> u16 {
+    // The track table spends a row on its header
+    match app.view {
+        LibraryView::Menu | LibraryView::Playlists | LibraryView::History => list_area.height,
+        LibraryView::Tracks => list_area.height.saturating_sub(1),
     }
 }
 
 fn render_library_list(frame: &mut Frame, area: Rect, app: &mut App) {
+    app.library_height = list_rows(app, area);
+
     let highlight_style = Style::default()
         .bg(Color::Cyan)
         .fg(Color::White)
         .add_modifier(Modifier::BOLD);
 
     match app.view {
+        LibraryView::Menu => {
+            let items: Vec<ListItem> = MENU
+                .iter()
+                .map(|item| {
+                    ListItem::new(Line::from(vec![
+                        Span::from(item.label()),
+                        Span::from(" \u{203a}").dark_gray(),
+                    ]))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(highlight_style)
+                .highlight_symbol(HIGHLIGHT_SYMBOL);
+
+            frame.render_stateful_widget(list, area, &mut app.menu_state);
+        }
         LibraryView::Playlists => {
+            let selected = app.playlist_state.selected();
+            // Room left after the highlight symbol and the › hint
+            let name_width = (area.width as usize).saturating_sub(5);
             let items: Vec<ListItem> = app
                 .playlists
                 .iter()
-                .map(|p| {
+                .enumerate()
+                .map(|(i, p)| {
+                    let name = if selected == Some(i) {
+                        marquee(&p.name, name_width, app.tick)
+                    } else {
+                        p.name.clone()
+                    };
                     ListItem::new(Line::from(vec![
-                        Span::from(p.name.clone()),
+                        Span::from(name),
                         Span::from(" \u{203a}").dark_gray(), // › arrow hint
                     ]))
                 })
@@ -260,80 +1328,260 @@ fn render_library_list(frame: &mut Frame, area: Rect, app: &mut App) {
 
             let list = List::new(items)
                 .highlight_style(highlight_style)
-                .highlight_symbol(" \u{25b6} ");
+                .highlight_symbol(HIGHLIGHT_SYMBOL);
 
             frame.render_stateful_widget(list, area, &mut app.playlist_state);
         }
-        LibraryView::Tracks => {
+        LibraryView::History => {
+            let selected = app.history_state.selected();
             let items: Vec<ListItem> = app
+                .history_rows()
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    let when = format!("{:>9}  ", crate::history::ago(e.played_at));
+                    let title = format!("{} \u{2014} {}", e.name, e.artist);
+                    let width = (area.width as usize).saturating_sub(when.width() + 3);
+                    let title = if selected == Some(i) {
+                        marquee(&title, width, app.tick)
+                    } else {
+                        truncate(&title, width)
+                    };
+                    ListItem::new(Line::from(vec![Span::from(when).dark_gray(), Span::from(title)]))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(highlight_style)
+                .highlight_symbol(HIGHLIGHT_SYMBOL);
+
+            frame.render_stateful_widget(list, area, &mut app.history_state);
+        }
+        LibraryView::Tracks => {
+            let selected = app.track_state.selected();
+            let columns = &app.config.columns;
+            let widths = column_widths(columns, app.tracks.len(), area.width);
+
+            let header = Row::new(columns.iter().map(|c| column_title(*c)))
+                .style(Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+            let rows: Vec<Row> = app
                 .tracks
                 .iter()
-                .map(|t| {
+                .enumerate()
+                .map(|(i, t)| {
                     let is_playing = !app.player.track_name.is_empty()
                         && t.name == app.player.track_name
                         && t.artist == app.player.artist;
 
-                    let prefix = if is_playing {
-                        Span::styled("\u{266b} ", Style::default().fg(Color::Green))
-                    } else {
-                        Span::from("  ")
-                    };
-
                     let name_style = if is_playing {
                         Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(Color::White)
                     };
 
-                    let spans = vec![
-                        prefix,
-                        Span::styled(t.name.clone(), name_style),
-                        Span::styled("  ", Style::default()),
-                        Span::styled(t.artist.clone(), Style::default().fg(Color::Cyan)),
-                    ];
+                    // Only the selected row scrolls; others are truncated
+                    let fit = |text: &str, width: u16| {
+                        if selected == Some(i) {
+                            marquee(text, width as usize, app.tick)
+                        } else {
+                            truncate(text, width as usize)
+                        }
+                    };
+
+                    let cells = columns.iter().zip(&widths).map(|(column, &w)| match column {
+                        Column::Number if is_playing => {
+                            Cell::from("\u{266b}").style(Style::default().fg(Color::Green))
+                        }
+                        Column::Number => Cell::from(format!("{}", i + 1)).dark_gray(),
+                        Column::Title => {
+                            // The last play failing trumps the favorite badge
+                            let badge = if app.play_errors.contains_key(&t.id) {
+                                Some(Span::from(" \u{26a0}").red().bold())
+                            } else {
+                                opinion_badge(t.favorited, t.disliked)
+                            };
+                            let w = w.saturating_sub(badge.as_ref().map_or(0, |b| b.width() as u16));
+                            let mut spans = vec![Span::styled(fit(&t.name, w), name_style)];
+                            spans.extend(badge);
+                            Cell::from(Line::from(spans))
+                        }
+                        Column::Artist => Cell::from(fit(&t.artist, w)).cyan(),
+                        Column::Album => Cell::from(fit(&t.album, w)).dark_gray(),
+                        Column::Rating => Cell::from(stars(t.rating)).yellow(),
+                        Column::Time => Cell::from(
+                            Line::from(format_time(t.duration)).alignment(Alignment::Right),
+                        )
+                        .dark_gray(),
+                    });
 
-                    ListItem::new(Line::from(spans))
+                    let row = Row::new(cells);
+                    if app.is_marked(i) {
+                        row.style(Style::default().bg(Color::Blue))
+                    } else {
+                        row
+                    }
                 })
                 .collect();
 
-            let list = List::new(items)
-                .highlight_style(highlight_style)
-                .highlight_symbol(" \u{25b6} ");
+            let table = Table::new(rows, widths.iter().map(|&w| Constraint::Length(w)))
+                .header(header)
+                .column_spacing(COLUMN_SPACING)
+                .row_highlight_style(highlight_style)
+                .highlight_symbol(HIGHLIGHT_SYMBOL)
+                .highlight_spacing(HighlightSpacing::Always);
 
-            frame.render_stateful_widget(list, area, &mut app.track_state);
+            frame.render_stateful_widget(table, area, &mut app.track_state);
         }
     }
 }
 
-fn draw_controls(frame: &mut Frame, area: Rect, app: &App) {
-    let inner = area;
+/// A 0–100 rating as filled stars (empty for unrated tracks).
+fn stars(rating: u8) -> String {
+    "\u{2605}".repeat((rating as usize + 10) / 20)
+}
 
-    let state_icon = match app.player.state {
-        PlayState::Playing => "\u{25b6}",
-        PlayState::Paused => "\u{2016}",
-        PlayState::Stopped => "\u{25a0}",
-    };
+/// Heart for favorited tracks, a struck circle for disliked ones.
+fn opinion_badge(favorited: bool, disliked: bool) -> Option<Span<'static>> {
+    if favorited {
+        Some(Span::from(" \u{2665}").red())
+    } else if disliked {
+        Some(Span::from(" \u{2298}").dark_gray())
+    } else {
+        None
+    }
+}
 
-    let elapsed = format_time(app.player.position);
-    let total = format_time(app.player.duration);
+/// Header label for a track table column.
+fn column_title(column: Column) -> &'static str {
+    match column {
+        Column::Number => "#",
+        Column::Title => "Title",
+        Column::Artist => "Artist",
+        Column::Album => "Album",
+        Column::Rating => "Rating",
+        Column::Time => "Time",
+    }
+}
 
-    let mode = if app.player.shuffle {
-        "\u{2921} shuffle"
-    } else {
-        match app.player.repeat {
+/// Resolve track table column widths for a table `width` columns wide.
+///
+/// `#` and time get fixed widths; the remaining space is shared between the
+/// text columns, with the title getting the largest share.
+fn column_widths(columns: &[Column], track_count: usize, width: u16) -> Vec<u16> {
+    let number_width = track_count.max(1).to_string().len() as u16;
+    let constraints = columns.iter().map(|c| match c {
+        Column::Number => Constraint::Length(number_width),
+        Column::Title => Constraint::Fill(3),
+        Column::Artist | Column::Album => Constraint::Fill(2),
+        Column::Rating => Constraint::Length(6),
+        Column::Time => Constraint::Length(5),
+    });
+    let available = width.saturating_sub(HIGHLIGHT_SYMBOL.width() as u16);
+    Layout::horizontal(constraints)
+        .spacing(COLUMN_SPACING)
+        .split(Rect::new(0, 0, available, 1))
+        .iter()
+        .map(|r| r.width)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Status widgets
+// ---------------------------------------------------------------------------
+
+/// A status widget's text, or None when it has nothing to show.
+fn status_widget(widget: StatusWidget, app: &App) -> Option<String> {
+    Some(match widget {
+        StatusWidget::Transport => match app.player.state {
+            PlayState::Playing => "\u{25b6}",
+            PlayState::Paused => "\u{2016}",
+            PlayState::Stopped => "\u{25a0}",
+        }
+        .to_string(),
+        StatusWidget::Mode if app.player.shuffle => "\u{2921} shuffle".to_string(),
+        StatusWidget::Mode => match app.player.repeat {
             RepeatMode::All => "\u{21bb} repeat all",
             RepeatMode::One => "\u{21bb} repeat one",
             RepeatMode::Off => "normal",
         }
-    };
+        .to_string(),
+        StatusWidget::Volume => match app.muted_volume {
+            Some(previous) => format!("\u{2298} muted ({previous}%)"),
+            None => format!("vol {}%", app.player.volume),
+        },
+        StatusWidget::Meter => app.level_meter.as_ref()?.render(LEVEL_METER_WIDTH),
+        StatusWidget::Output if app.output_device.is_empty() => return None,
+        StatusWidget::Output => format!("\u{21e5} {}", app.output_device),
+        StatusWidget::Clock => clock(),
+        StatusWidget::Progress => {
+            format!("{} / {}", format_time(app.player.position), format_time(app.player.duration))
+        }
+    })
+}
 
-    let vol = app.player.volume.clamp(0, 100);
+/// Join the texts of `widgets`, divided by bars except after the transport
+/// icon and before the meter, which sit close to their neighbours. Also
+/// returns the char range of the output device name, if shown.
+fn status_text(widgets: &[StatusWidget], app: &App) -> (String, Option<std::ops::Range<usize>>) {
+    let mut text = String::new();
+    let mut device_range = None;
+    let mut previous = None;
+    for &widget in widgets {
+        let Some(part) = status_widget(widget, app) else { continue };
+        if let Some(previous) = previous {
+            let tight = previous == StatusWidget::Transport || widget == StatusWidget::Meter;
+            text.push_str(if tight { "  " } else { "  \u{2502}  " });
+        }
+        if widget == StatusWidget::Output {
+            // Past the "⇥ " prefix
+            let start = text.chars().count() + 2;
+            device_range = Some(start..start + app.output_device.chars().count());
+        }
+        text.push_str(&part);
+        previous = Some(widget);
+    }
+    (text, device_range)
+}
 
-    let left = format!(" {state_icon}  {mode}  \u{2502}  vol {vol}%");
-    let right = format!("{elapsed} / {total} ");
+/// Local time of day, "HH:MM".
+fn clock() -> String {
+    let secs = crate::history::now() as i64 + utc_offset();
+    format!("{:02}:{:02}", secs.rem_euclid(86_400) / 3600, secs.rem_euclid(3600) / 60)
+}
+
+/// The local UTC offset in seconds, read once from `date +%z` (0 if that
+/// fails).
+fn utc_offset() -> i64 {
+    static OFFSET: std::sync::OnceLock<i64> = std::sync::OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        let Ok(output) = std::process::Command::new("date").arg("+%z").output() else { return 0 };
+        let text = String::from_utf8_lossy(&output.stdout);
+        let text = text.trim();
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, text.trim_start_matches('+')),
+        };
+        let (hours, minutes) = digits.split_at(digits.len().min(2));
+        sign * (hours.parse::<i64>().unwrap_or(0) * 3600 + minutes.parse::<i64>().unwrap_or(0) * 60)
+    })
+}
+
+fn draw_controls(frame: &mut Frame, area: Rect, app: &App) {
+    let inner = area;
+
+    let (left, left_device) = status_text(&app.config.status_left, app);
+    let (right, right_device) = status_text(&app.config.status_right, app);
+    let left = format!(" {left}");
+    let right = format!("{right} ");
     let w = inner.width as usize;
     let pad = w.saturating_sub(left.chars().count() + right.chars().count());
     let full_text = format!("{left}{:pad$}{right}", "");
+    // Char range of the output device name in the full text
+    let right_start = left.chars().count() + pad;
+    let device_range = left_device
+        .map(|r| r.start + 1..r.end + 1)
+        .or_else(|| right_device.map(|r| r.start + right_start..r.end + right_start));
 
     let ratio = if app.player.duration > 0.0 {
         (app.player.position / app.player.duration).clamp(0.0, 1.0)
@@ -341,21 +1589,107 @@ fn draw_controls(frame: &mut Frame, area: Rect, app: &App) {
         0.0
     };
     let filled = ((w as f64) * ratio).round() as usize;
+    // Section starts from synced lyrics, marked as ticks along the gauge
+    let ticks: Vec<usize> = if app.player.duration > 0.0 {
+        app.sections()
+            .iter()
+            .filter(|&&t| t > 0.0)
+            .map(|t| ((w as f64) * (t / app.player.duration)).round() as usize)
+            .filter(|&col| col < w)
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-    // Split text into filled (progress color) and unfilled portions
-    let chars: Vec<char> = full_text.chars().collect();
-    let filled_str: String = chars[..filled.min(chars.len())].iter().collect();
-    let unfilled_str: String = chars[filled.min(chars.len())..].iter().collect();
+    let filled_style = Style::default().bg(Color::Cyan).fg(Color::White).bold();
+    let unfilled_style = Style::default().fg(Color::DarkGray);
+    let flash_style = Style::default().fg(Color::Yellow).bold();
+    let tick_style = Style::default().fg(Color::Yellow);
+    let flash_range = device_range.filter(|_| app.output_flashing());
 
-    let line = Line::from(vec![
-        Span::styled(filled_str, Style::default().bg(Color::Cyan).fg(Color::White).bold()),
-        Span::styled(unfilled_str, Style::default().fg(Color::DarkGray)),
-    ]);
+    // Split text into filled (progress color) and unfilled portions, with
+    // section ticks and the flashing device name overriding the foreground
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_style = None;
+    for (i, mut c) in full_text.chars().enumerate() {
+        let mut style = if i < filled { filled_style } else { unfilled_style };
+        if ticks.contains(&i) {
+            style = style.patch(tick_style);
+            if c == ' ' {
+                c = '\u{2575}';
+            }
+        }
+        if flash_range.as_ref().is_some_and(|r| r.contains(&i)) {
+            style = style.patch(flash_style);
+        }
+        if let Some(prev) = run_style.filter(|s| *s != style) {
+            spans.push(Span::styled(std::mem::take(&mut run), prev));
+        }
+        run_style = Some(style);
+        run.push(c);
+    }
+    if let Some(style) = run_style {
+        spans.push(Span::styled(run, style));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), inner);
+}
+
+/// Fit `text` into `width` terminal columns, scrolling it one column per tick
+/// when it overflows. Text that already fits is returned unchanged.
+fn marquee(text: &str, width: usize, tick: u64) -> String {
+    let text_width = text.width();
+    if width == 0 || text_width <= width {
+        return text.to_string();
+    }
+
+    let offset = (tick as usize) % (text_width + MARQUEE_GAP);
+    let looped = format!("{text}{:MARQUEE_GAP$}{text}", "");
 
-    frame.render_widget(Paragraph::new(line), inner);
+    let mut out = String::new();
+    let mut col = 0;
+    let mut used = 0;
+    for c in looped.chars() {
+        let w = c.width().unwrap_or(0);
+        if col < offset {
+            col += w;
+            continue;
+        }
+        if used + w > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    // A wide char straddling either edge leaves a gap; pad so the row doesn't jitter
+    out.extend(std::iter::repeat_n(' ', width - used));
+    out
+}
+
+/// Truncate `text` to `width` terminal columns, ending with "…" if clipped.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width - 1 {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('\u{2026}');
+    out
 }
 
-fn format_time(seconds: f64) -> String {
+pub fn format_time(seconds: f64) -> String {
     let s = seconds as u64;
     format!("{}:{:02}", s / 60, s % 60)
 }
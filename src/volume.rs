@@ -0,0 +1,132 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// A player volume: a percentage, always within 0–100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct Volume(u8);
+
+impl Volume {
+    pub const MIN: Volume = Volume(0);
+
+    /// The volume at `percent`, clamped to 0–100.
+    pub fn new(percent: i32) -> Self {
+        Volume(percent.clamp(0, 100) as u8)
+    }
+
+    /// The volume at `percent`, or None outside 0–100.
+    pub fn checked(percent: i32) -> Option<Self> {
+        (0..=100).contains(&percent).then_some(Volume(percent as u8))
+    }
+
+    pub fn percent(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_muted(self) -> bool {
+        self.0 == 0
+    }
+
+    /// `delta` points up or down, stopping at the bounds.
+    pub fn step(self, delta: i32) -> Self {
+        Volume::new(i32::from(self.0).saturating_add(delta))
+    }
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Volume(50)
+    }
+}
+
+impl fmt::Display for Volume {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A volume setting from the user: `80` (or `80%`) sets it, `+10` and
+/// `-10` step from the current volume.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VolumeChange {
+    To(Volume),
+    By(i32),
+}
+
+impl VolumeChange {
+    /// Parse `80`, `80%`, `+10` or `-10`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let digits = input.strip_suffix('%').unwrap_or(input);
+        let relative = digits.starts_with(['+', '-']);
+        let value: i32 = digits
+            .parse()
+            .map_err(|_| format!("Not a volume: {input} (use 0-100, +N or -N)"))?;
+        if relative {
+            if value.abs() > 100 {
+                return Err(format!("Volume change out of range: {input}"));
+            }
+            Ok(VolumeChange::By(value))
+        } else {
+            Volume::checked(value)
+                .map(VolumeChange::To)
+                .ok_or_else(|| format!("Volume must be 0-100, not {input}"))
+        }
+    }
+
+    /// The volume this leaves `current` at.
+    pub fn apply(self, current: Volume) -> Volume {
+        match self {
+            VolumeChange::To(volume) => volume,
+            VolumeChange::By(delta) => current.step(delta),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_absolute() {
+        assert_eq!(VolumeChange::parse("80"), Ok(VolumeChange::To(Volume::new(80))));
+        assert_eq!(VolumeChange::parse("80%"), Ok(VolumeChange::To(Volume::new(80))));
+        assert_eq!(VolumeChange::parse(" 0 "), Ok(VolumeChange::To(Volume::MIN)));
+        assert_eq!(VolumeChange::parse("100"), Ok(VolumeChange::To(Volume::new(100))));
+    }
+
+    #[test]
+    fn parse_relative() {
+        assert_eq!(VolumeChange::parse("+10"), Ok(VolumeChange::By(10)));
+        assert_eq!(VolumeChange::parse("-10"), Ok(VolumeChange::By(-10)));
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert!(VolumeChange::parse("101").is_err());
+        assert!(VolumeChange::parse("+101").is_err());
+        assert!(VolumeChange::parse("abc").is_err());
+        assert!(VolumeChange::parse("").is_err());
+        assert!(VolumeChange::parse("%").is_err());
+    }
+
+    #[test]
+    fn step_stops_at_bounds() {
+        assert_eq!(Volume::new(95).step(10), Volume::new(100));
+        assert_eq!(Volume::new(5).step(-10), Volume::MIN);
+        assert_eq!(Volume::new(50).step(i32::MAX), Volume::new(100));
+        assert_eq!(Volume::new(50).step(i32::MIN), Volume::MIN);
+        assert_eq!(Volume::new(150).percent(), 100);
+        assert_eq!(Volume::new(-5).percent(), 0);
+    }
+
+    #[test]
+    fn apply() {
+        let current = Volume::new(40);
+        assert_eq!(VolumeChange::To(Volume::new(80)).apply(current), Volume::new(80));
+        assert_eq!(VolumeChange::By(10).apply(current), Volume::new(50));
+        assert_eq!(VolumeChange::By(100).apply(current), Volume::new(100));
+        assert_eq!(VolumeChange::By(-100).apply(current), Volume::MIN);
+    }
+}